@@ -0,0 +1,214 @@
+//! Paměťový rozpočet exporteru samotného (SELF_MEMORY_BUDGET_BYTES).
+//!
+//! Když RSS exporteru rozpočet překročí, vypne se každý cyklus jeden
+//! volitelný collector - od nejvyšší kardinality - a jeho série se zahodí.
+//! Vypnutý collector zůstává vypnutý až do restartu: alokátor paměť
+//! systému stejně hned nevrací, takže návrat pod rozpočet nic neznamená.
+//! Observer se nesmí stát noisy neighbourem.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+use tracing::warn;
+
+use crate::metrics::{MemoryBudgetMetrics, Metrics};
+
+/// Collectory, které lze při překročení rozpočtu obětovat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sheddable {
+    PodProcesses,
+    Siblings,
+    PerPid,
+    Containers,
+    Recursive,
+    Glob,
+    PeerTcp,
+    Exec,
+}
+
+/// Pořadí vypínání - od nejvyšší kardinality.
+const SHED_ORDER: [Sheddable; 8] = [
+    Sheddable::PodProcesses,
+    Sheddable::Siblings,
+    Sheddable::PerPid,
+    Sheddable::Containers,
+    Sheddable::Recursive,
+    Sheddable::Glob,
+    Sheddable::PeerTcp,
+    Sheddable::Exec,
+];
+
+static SHED: [AtomicBool; SHED_ORDER.len()] = [const { AtomicBool::new(false) }; SHED_ORDER.len()];
+
+impl Sheddable {
+    fn index(self) -> usize {
+        SHED_ORDER
+            .iter()
+            .position(|&c| c == self)
+            .expect("every Sheddable is in SHED_ORDER")
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Sheddable::PodProcesses => "pod_processes",
+            Sheddable::Siblings => "sibling_containers",
+            Sheddable::PerPid => "per_pid_processes",
+            Sheddable::Containers => "pod_cgroup_containers",
+            Sheddable::Recursive => "recursive_cgroups",
+            Sheddable::Glob => "cgroup_glob",
+            Sheddable::PeerTcp => "tcp_peers",
+            Sheddable::Exec => "exec_commands",
+        }
+    }
+
+    /// Je collector vůbec zapnutý v konfiguraci?
+    fn configured(self, metrics: &Metrics) -> bool {
+        match self {
+            Sheddable::PodProcesses => metrics.pod_processes.is_some(),
+            Sheddable::Siblings => metrics.siblings.is_some(),
+            Sheddable::PerPid => metrics.per_pid.is_some() || metrics.taskstats.is_some(),
+            Sheddable::Containers => metrics.containers.is_some(),
+            Sheddable::Recursive => metrics.recursive.is_some(),
+            Sheddable::Glob => metrics.glob.is_some(),
+            Sheddable::PeerTcp => metrics.tcp.peer_connections.is_some(),
+            Sheddable::Exec => metrics.exec.is_some(),
+        }
+    }
+
+    /// Zahodí série collectoru, ať se paměť skutečně uvolní.
+    fn release(self, metrics: &Metrics) {
+        match self {
+            Sheddable::PodProcesses => {
+                if let Some(ref m) = metrics.pod_processes {
+                    m.processes.reset();
+                    m.cpu_seconds.reset();
+                    m.rss_bytes.reset();
+                }
+            }
+            Sheddable::Siblings => {
+                if let Some(ref m) = metrics.siblings {
+                    m.processes.reset();
+                    m.cpu_seconds.reset();
+                    m.rss_bytes.reset();
+                }
+            }
+            Sheddable::PerPid => {
+                if let Some(ref m) = metrics.per_pid {
+                    m.cpu_user_seconds.reset();
+                    m.cpu_system_seconds.reset();
+                    m.start_time_seconds.reset();
+                    m.mem_rss_bytes.reset();
+                    m.mem_vms_bytes.reset();
+                    m.mem_swap_bytes.reset();
+                    m.io_read_bytes_total.reset();
+                    m.io_write_bytes_total.reset();
+                    m.info.reset();
+                }
+                if let Some(ref m) = metrics.taskstats {
+                    m.delay_seconds_total.reset();
+                    m.delays_total.reset();
+                }
+            }
+            Sheddable::Containers => {
+                if let Some(ref m) = metrics.containers {
+                    m.cpu_usage_seconds.reset();
+                    m.cpu_throttled_seconds.reset();
+                    m.mem_current_bytes.reset();
+                    m.mem_max_bytes.reset();
+                }
+            }
+            Sheddable::Recursive => {
+                if let Some(ref m) = metrics.recursive {
+                    m.cpu_usage_seconds.reset();
+                    m.cpu_throttled_seconds.reset();
+                    m.mem_current_bytes.reset();
+                    m.cgroups.reset();
+                    m.frozen_children.reset();
+                }
+            }
+            Sheddable::Glob => {
+                if let Some(ref m) = metrics.glob {
+                    m.cpu_usage_seconds.reset();
+                    m.cpu_throttled_seconds.reset();
+                    m.mem_current_bytes.reset();
+                }
+            }
+            Sheddable::PeerTcp => {
+                if let Some(ref m) = metrics.tcp.peer_connections {
+                    m.reset();
+                }
+            }
+            Sheddable::Exec => {
+                if let Some(ref m) = metrics.exec {
+                    m.success.reset();
+                    m.duration_seconds.reset();
+                    m.collector.clear();
+                }
+            }
+        }
+    }
+}
+
+/// Byl collector kvůli rozpočtu vypnut?
+pub fn is_shed(collector: Sheddable) -> bool {
+    SHED[collector.index()].load(Ordering::Relaxed)
+}
+
+/// Nastaví výchozí stav (0) pro nakonfigurované collectory a rozpočet.
+pub fn init(budget_metrics: &MemoryBudgetMetrics, metrics: &Metrics, budget_bytes: u64) {
+    budget_metrics.budget_bytes.set(budget_bytes as i64);
+    for c in SHED_ORDER.iter().filter(|c| c.configured(metrics)) {
+        budget_metrics
+            .collector_shed
+            .with_label_values(&[c.name()])
+            .set(0);
+    }
+}
+
+fn self_rss_bytes() -> Result<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").context("read /proc/self/status")?;
+    let kb = status
+        .lines()
+        .find_map(|l| l.strip_prefix("VmRSS:"))
+        .and_then(|v| v.split_whitespace().next())
+        .and_then(|v| v.parse::<u64>().ok())
+        .context("VmRSS missing in /proc/self/status")?;
+    Ok(kb * 1024)
+}
+
+/// Porovná RSS s rozpočtem; při překročení vypne další collector v pořadí.
+pub fn enforce(
+    budget_metrics: &MemoryBudgetMetrics,
+    metrics: &Metrics,
+    budget_bytes: u64,
+) -> Result<()> {
+    let rss = self_rss_bytes()?;
+    budget_metrics.rss_bytes.set(rss as i64);
+    if rss <= budget_bytes {
+        return Ok(());
+    }
+
+    let Some(next) = SHED_ORDER
+        .iter()
+        .copied()
+        .find(|&c| c.configured(metrics) && !is_shed(c))
+    else {
+        // vše už je vypnuté, víc udělat nejde
+        return Ok(());
+    };
+
+    SHED[next.index()].store(true, Ordering::Relaxed);
+    next.release(metrics);
+    budget_metrics
+        .collector_shed
+        .with_label_values(&[next.name()])
+        .set(1);
+    warn!(
+        rss_bytes = rss,
+        budget_bytes,
+        collector = next.name(),
+        "exporter memory budget exceeded, disabling collector until restart"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Cache zakódovaného /metrics (METRICS_CACHE_SECONDS).
+//!
+//! Data se mění jen při background updatu, ne při scrapu - několik scraperů
+//! a health-checker mesh proxy tak nemusí pokaždé znovu kódovat registry.
+//! Každý dokončený update zvýší generaci; exposition se přegeneruje až
+//! při prvním scrapu po změně generace.
+
+use std::sync::{
+    Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+use hyper::body::Bytes;
+use once_cell::sync::Lazy;
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+struct Cached {
+    generation: u64,
+    body: Bytes,
+    content_type: String,
+}
+
+static CACHED: Lazy<Mutex<Option<Cached>>> = Lazy::new(|| Mutex::new(None));
+
+/// Volá se po každém dokončeném updatu metrik.
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::Release);
+}
+
+/// Vrátí exposition z cache, nebo ji zakóduje přes `encode` a uloží.
+pub fn get_or_encode(encode: impl FnOnce() -> (Vec<u8>, String)) -> (Bytes, String) {
+    let generation = GENERATION.load(Ordering::Acquire);
+    let mut cached = CACHED.lock().expect("metrics cache mutex poisoned");
+
+    if let Some(ref c) = *cached
+        && c.generation == generation
+    {
+        return (c.body.clone(), c.content_type.clone());
+    }
+
+    let (buffer, content_type) = encode();
+    let body = Bytes::from(buffer);
+    *cached = Some(Cached {
+        generation,
+        body: body.clone(),
+        content_type: content_type.clone(),
+    });
+    (body, content_type)
+}
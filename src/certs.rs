@@ -0,0 +1,112 @@
+//! Expirace PEM certifikátů z namountovaných secretů (CERT_PATHS).
+//!
+//! Cesta může být soubor (i bundle s více certifikáty) nebo adresář -
+//! z adresáře se berou soubory *.crt / *.pem (typicky tls.crt, ca.crt).
+//! Čte se každý cyklus, protože cert-manager secrety rotuje za běhu.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use prometheus::GaugeVec;
+use x509_parser::pem::Pem;
+
+use crate::metrics::LiveSeries;
+
+fn cert_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(path).context("read cert directory")? {
+        let p = entry?.path();
+        // k8s secret volume: soubory jsou symlinky do ..data, is_file je následuje
+        if p.is_file() && p.extension().is_some_and(|e| e == "crt" || e == "pem") {
+            files.push(p);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// (subject, notAfter jako unix timestamp) všech certifikátů v souboru.
+fn read_expiries(file: &Path) -> Result<Vec<(String, i64)>> {
+    let raw = std::fs::read(file).context("read certificate file")?;
+
+    let mut result = Vec::new();
+    for pem in Pem::iter_from_buffer(&raw) {
+        let pem = pem.context("parse PEM")?;
+        if pem.label != "CERTIFICATE" {
+            // klíče a jiné bloky ve stejném souboru přeskočíme
+            continue;
+        }
+        let cert = pem.parse_x509().context("parse X.509 certificate")?;
+        result.push((
+            cert.subject().to_string(),
+            cert.validity().not_after.timestamp(),
+        ));
+    }
+    Ok(result)
+}
+
+pub fn update(expiry: &GaugeVec, paths: &[PathBuf]) -> Result<()> {
+    // rotovaný secret může mít jiný subject - staré série zmizí přes prune
+    let mut live = LiveSeries::default();
+    let mut errors = Vec::new();
+    for path in paths {
+        let files = match cert_files(path) {
+            Ok(f) => f,
+            Err(e) => {
+                errors.push(format!("{}: {e:#}", path.display()));
+                continue;
+            }
+        };
+
+        for file in files {
+            match read_expiries(&file) {
+                Ok(certs) => {
+                    let file_label = file.display().to_string();
+                    for (subject, not_after) in certs {
+                        let labels = [file_label.as_str(), subject.as_str()];
+                        expiry.with_label_values(&labels).set(not_after as f64);
+                        live.insert(&labels);
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {e:#}", file.display())),
+            }
+        }
+    }
+
+    live.prune(expiry);
+
+    if !errors.is_empty() {
+        anyhow::bail!("{}", errors.join("; "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/certs")
+    }
+
+    #[test]
+    fn read_expiries_parses_fixture_certificate() {
+        let certs = read_expiries(&fixtures().join("tls.crt")).unwrap();
+        assert_eq!(
+            certs,
+            vec![("CN=fixture.example.com, O=Fixtures".to_string(), 2107533054)]
+        );
+    }
+
+    #[test]
+    fn cert_files_picks_crt_and_pem_from_directory() {
+        assert_eq!(
+            cert_files(&fixtures()).unwrap(),
+            vec![fixtures().join("tls.crt")]
+        );
+    }
+}
@@ -1,16 +1,136 @@
-use std::path::Path;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
 use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
 
-use crate::metrics::CgroupMetrics;
+use crate::{
+    delta::DeltaTracker, hierarchy::Layout, kubepods, metrics::CgroupMetrics,
+    monotonic::CounterOffsets, peaks,
+};
 
-fn read_to_string(path: &Path) -> Result<String> {
-    Ok(std::fs::read_to_string(path)?.trim().to_string())
+/// Předchozí hodnoty cpu.stat pro odvozené metriky (klíč = cesta + položka).
+static DELTAS: Lazy<Mutex<DeltaTracker>> = Lazy::new(|| Mutex::new(DeltaTracker::new()));
+
+/// Offsety counterů - znovu vytvořená cgroupa (restart kontejneru) začíná od nuly.
+pub static OFFSETS: Lazy<Mutex<CounterOffsets>> = Lazy::new(|| Mutex::new(CounterOffsets::new()));
+
+/// Soubory, ze kterých collector čte - pro GET /debug/cgroup.
+const RAW_FILES: &[&str] = &[
+    "cpu.stat",
+    "cpu.max",
+    "cpu.max.burst",
+    "cpu.pressure",
+    "memory.pressure",
+    "io.pressure",
+    "cpu.uclamp.min",
+    "cpu.uclamp.max",
+    "cpu.idle",
+    "cpuset.cpus.effective",
+    "cpuset.mems.effective",
+    "memory.current",
+    "memory.peak",
+    "memory.max",
+    "memory.high",
+    "memory.low",
+    "memory.min",
+    "memory.events",
+    "memory.events.local",
+    "memory.swap.current",
+    "memory.swap.max",
+    "memory.swap.high",
+    "memory.swap.events",
+    "memory.zswap.current",
+    "memory.zswap.max",
+    "memory.stat",
+    "misc.current",
+    "misc.max",
+    "cgroup.stat",
+    "cgroup.controllers",
+    "cgroup.subtree_control",
+    "cgroup.events",
+    "cgroup.freeze",
+    "io.stat",
+];
+
+/// Adresář cgroupy (v2) daného PIDu podle `/proc/<pid>/cgroup` pod mountem
+/// `cgroup_mount`. Cesta v souboru je relativní ke cgroup namespace čtoucího
+/// procesu, takže to sedí jen když exporter vidí stejnou hierarchii.
+pub fn dir_of_pid(proc_root: &Path, cgroup_mount: &Path, pid: i32) -> Option<PathBuf> {
+    let raw = std::fs::read_to_string(proc_root.join(pid.to_string()).join("cgroup")).ok()?;
+    let rel = raw.lines().find_map(|l| l.strip_prefix("0::"))?;
+    let rel = rel.trim().trim_start_matches('/');
+    let dir = if rel.is_empty() {
+        cgroup_mount.to_path_buf()
+    } else {
+        cgroup_mount.join(rel)
+    };
+    dir.is_dir().then_some(dir)
+}
+
+/// Cgroupa exporteru, když kontejner běží bez vlastního cgroup namespace:
+/// v `/proc/self/cgroup` je pak celá hostitelská cesta místo `/` a CGROUP_ROOT
+/// (mount) by ukazoval čísla celého nodu. Jen čistá v2 a jen když poslední
+/// segment vypadá jako kontejner - exporter jako systemd služba na hostu
+/// má sledovat dál celý mount.
+pub fn own_container_dir(proc_root: &Path, cgroup_mount: &Path) -> Option<PathBuf> {
+    if !cgroup_mount.join("cgroup.controllers").exists() {
+        return None;
+    }
+    let raw = std::fs::read_to_string(proc_root.join("self/cgroup")).ok()?;
+    let rel = raw.lines().find_map(|l| l.strip_prefix("0::"))?.trim();
+    // privátní namespace: vlastní cgroupa je kořen
+    let rel = rel.trim_start_matches('/');
+    let last = rel.rsplit('/').next().filter(|s| !s.is_empty())?;
+    // krátké hex segmenty (session-3.scope) nejsou kontejnery
+    kubepods::container_id_from_segment(last).filter(|id| id.len() >= 12)?;
+    let dir = cgroup_mount.join(rel);
+    dir.is_dir().then_some(dir)
+}
+
+/// Syrový obsah čtených cgroup souborů (None = soubor chybí / nejde číst).
+pub fn read_raw(root: &Path) -> BTreeMap<&'static str, Option<String>> {
+    let layout = Layout::detect(root);
+    RAW_FILES
+        .iter()
+        .map(|&name| (name, layout.read(name)))
+        .collect()
+}
+
+/// Počet položek v seznamu typu "0-3,8,10-11" (cpuset formát).
+fn count_cpu_list(list: &str) -> Option<u64> {
+    let mut count = 0;
+    for part in list.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        count += match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (from.parse::<u64>().ok()?, to.parse::<u64>().ok()?);
+                to.checked_sub(from)? + 1
+            }
+            None => {
+                part.parse::<u64>().ok()?;
+                1
+            }
+        };
+    }
+    Some(count)
 }
 
 pub fn update(metrics: &CgroupMetrics, root: &Path) -> Result<()> {
+    let layout = Layout::detect(root);
+    let read = |name: &str| layout.read(name).with_context(|| format!("read {name}"));
+
+    // cesta se může změnit (CGROUP_FOLLOW_TARGET) - vždy jen jedna série
+    metrics.info.reset();
+    metrics
+        .info
+        .with_label_values(&[&root.display().to_string(), layout.mode.as_str()])
+        .set(1);
+
     // cpu.stat
-    let cpu_stat = read_to_string(&root.join("cpu.stat")).context("read cpu.stat")?;
+    let cpu_stat = read("cpu.stat")?;
 
     let mut usage_usec = None;
     let mut user_usec = None;
@@ -18,6 +138,9 @@ pub fn update(metrics: &CgroupMetrics, root: &Path) -> Result<()> {
     let mut nr_periods = None;
     let mut nr_throttled = None;
     let mut throttled_usec = None;
+    let mut nr_bursts = None;
+    let mut burst_usec = None;
+    let mut force_idle_usec = None;
 
     for line in cpu_stat.lines() {
         let mut parts = line.split_whitespace();
@@ -30,89 +153,536 @@ pub fn update(metrics: &CgroupMetrics, root: &Path) -> Result<()> {
             "nr_periods" => nr_periods = Some(val),
             "nr_throttled" => nr_throttled = Some(val),
             "throttled_usec" => throttled_usec = Some(val),
+            "nr_bursts" => nr_bursts = Some(val),
+            "burst_usec" => burst_usec = Some(val),
+            "core_sched.force_idle_usec" => force_idle_usec = Some(val),
             _ => {}
         }
     }
 
-    if let Some(v) = usage_usec {
-        metrics.cpu_usage_seconds.set(v as f64 / 1_000_000.0);
-    }
-    if let Some(v) = user_usec {
-        metrics.cpu_user_seconds.set(v as f64 / 1_000_000.0);
-    }
-    if let Some(v) = system_usec {
-        metrics.cpu_system_seconds.set(v as f64 / 1_000_000.0);
+    {
+        let mut offsets = OFFSETS.lock().expect("cgroup OFFSETS mutex poisoned");
+        let mut adjust =
+            |name: &str, v: f64| offsets.adjust(&format!("{}:{name}", root.display()), v);
+
+        if let Some(v) = usage_usec {
+            metrics
+                .cpu_usage_seconds
+                .set(adjust("usage_usec", v as f64) / 1_000_000.0);
+        }
+        if let Some(v) = user_usec {
+            metrics
+                .cpu_user_seconds
+                .set(adjust("user_usec", v as f64) / 1_000_000.0);
+        }
+        if let Some(v) = system_usec {
+            metrics
+                .cpu_system_seconds
+                .set(adjust("system_usec", v as f64) / 1_000_000.0);
+        }
+        if let Some(v) = nr_periods {
+            metrics
+                .cpu_nr_periods
+                .set(adjust("nr_periods", v as f64) as i64);
+        }
+        if let Some(v) = nr_throttled {
+            metrics
+                .cpu_nr_throttled
+                .set(adjust("nr_throttled", v as f64) as i64);
+        }
+        if let Some(v) = throttled_usec {
+            metrics
+                .cpu_throttled_seconds
+                .set(adjust("throttled_usec", v as f64) / 1_000_000.0);
+        }
+        if let Some(v) = nr_bursts {
+            metrics
+                .cpu_nr_bursts
+                .set(adjust("nr_bursts", v as f64) as i64);
+        }
+        if let Some(v) = burst_usec {
+            metrics
+                .cpu_burst_seconds
+                .set(adjust("burst_usec", v as f64) / 1_000_000.0);
+        }
+        if let Some(v) = force_idle_usec {
+            metrics
+                .cpu_core_sched_force_idle_seconds
+                .set(adjust("core_sched.force_idle_usec", v as f64) / 1_000_000.0);
+        }
     }
-    if let Some(v) = nr_periods {
-        metrics.cpu_nr_periods.set(v as i64);
+
+    // spotřeba CPU v jádrech za poslední interval (DERIVED_RATES / PEAK_WINDOWS,
+    // vždy pro cgroup_cpu_utilization_ratio)
+    let mut usage_cores = None;
+    if let Some(v) = usage_usec {
+        let key = format!("{}:usage_usec", root.display());
+        usage_cores = DELTAS
+            .lock()
+            .expect("cgroup DELTAS mutex poisoned")
+            .observe(&key, v as f64 / 1_000_000.0)
+            .and_then(|d| d.per_second());
+
+        if let Some(cores) = usage_cores {
+            if let Some(ref gauge) = metrics.cpu_usage_cores {
+                gauge.set(cores);
+            }
+            peaks::observe(&key, &metrics.cpu_usage_cores_peaks, cores);
+        }
     }
-    if let Some(v) = nr_throttled {
-        metrics.cpu_nr_throttled.set(v as i64);
+
+    // throttled ratio za poslední interval
+    let mut throttled_ratio = None;
+    if let (Some(periods), Some(throttled)) = (nr_periods, nr_throttled) {
+        let key = root.display().to_string();
+        let mut deltas = DELTAS.lock().expect("cgroup DELTAS mutex poisoned");
+        let d_periods = deltas.observe(&format!("{key}:nr_periods"), periods as f64);
+        let d_throttled = deltas.observe(&format!("{key}:nr_throttled"), throttled as f64);
+
+        if let (Some(p), Some(t)) = (d_periods, d_throttled) {
+            let ratio = if p.value > 0.0 {
+                t.value / p.value
+            } else {
+                0.0
+            };
+            let ratio = ratio.clamp(0.0, 1.0);
+            metrics.cpu_throttled_ratio.set(ratio);
+            throttled_ratio = Some(ratio);
+        }
     }
-    if let Some(v) = throttled_usec {
-        metrics.cpu_throttled_seconds.set(v as f64 / 1_000_000.0);
+
+    // saturation = horší z (PSI some avg10, throttled ratio) - jedno číslo na alert
+    let psi_some = read("cpu.pressure")
+        .ok()
+        .and_then(|s| {
+            s.lines()
+                .find_map(|l| l.strip_prefix("some "))
+                .and_then(|rest| {
+                    rest.split_whitespace()
+                        .find_map(|kv| kv.strip_prefix("avg10="))
+                        .and_then(|v| v.parse::<f64>().ok())
+                })
+        })
+        .map(|pct| (pct / 100.0).clamp(0.0, 1.0));
+    match (psi_some, throttled_ratio) {
+        (Some(p), Some(t)) => metrics.cpu_saturation_ratio.set(p.max(t)),
+        (Some(v), None) | (None, Some(v)) => metrics.cpu_saturation_ratio.set(v),
+        (None, None) => {}
     }
 
     // cpu.max
-    let cpu_max = read_to_string(&root.join("cpu.max")).context("read cpu.max")?;
+    let cpu_max = read("cpu.max")?;
     let parts: Vec<&str> = cpu_max.split_whitespace().collect();
+    let period_usec = parts.get(1).and_then(|p| p.parse::<u64>().ok());
     if parts.len() >= 2 {
         if parts[0] == "max" {
             metrics.cpu_limit_cores.set(f64::INFINITY);
-        } else if let (Ok(quota), Ok(period)) = (parts[0].parse::<u64>(), parts[1].parse::<u64>()) {
-            if period > 0 {
-                let cores = quota as f64 / period as f64;
-                metrics.cpu_limit_cores.set(cores);
-            }
+        } else if let (Ok(quota), Ok(period)) = (parts[0].parse::<u64>(), parts[1].parse::<u64>())
+            && period > 0
+        {
+            let cores = quota as f64 / period as f64;
+            metrics.cpu_limit_cores.set(cores);
         }
     }
 
-    // memory.*
-    if let Ok(s) = read_to_string(&root.join("memory.current")) {
-        if let Ok(v) = s.parse::<u64>() {
-            metrics.mem_current_bytes.set(v as f64);
+    // využití limitu za poslední interval; bez limitu 0 (usage / +Inf)
+    if let Some(cores) = usage_cores {
+        let limit = metrics.cpu_limit_cores.get();
+        if limit > 0.0 {
+            metrics.cpu_utilization_ratio.set(cores / limit);
         }
     }
-    if let Ok(s) = read_to_string(&root.join("memory.peak")) {
-        if let Ok(v) = s.parse::<u64>() {
-            metrics.mem_peak_bytes.set(v as f64);
+
+    if let Ok(s) = read("cpu.max.burst")
+        && let Ok(v) = s.parse::<u64>()
+    {
+        metrics.cpu_max_burst_seconds.set(v as f64 / 1_000_000.0);
+        if let Some(period) = period_usec.filter(|&p| p > 0) {
+            metrics.cpu_burst_cores.set(v as f64 / period as f64);
         }
     }
-    if let Ok(s) = read_to_string(&root.join("memory.max")) {
+
+    // cpu.uclamp.* - jen s CONFIG_UCLAMP_TASK_GROUP
+    for (bound, file) in [("min", "cpu.uclamp.min"), ("max", "cpu.uclamp.max")] {
+        let Ok(s) = read(file) else {
+            continue;
+        };
+        let pct = if s == "max" {
+            Some(100.0)
+        } else {
+            s.parse::<f64>().ok()
+        };
+        if let Some(pct) = pct {
+            metrics
+                .cpu_uclamp_percent
+                .with_label_values(&[bound])
+                .set(pct);
+        }
+    }
+
+    // cpu.idle (5.15+)
+    if let Ok(s) = read("cpu.idle")
+        && let Ok(v) = s.parse::<i64>()
+    {
+        metrics.cpu_idle.set(v);
+    }
+
+    // cpuset (static CPU manager policy pinuje pody na konkrétní CPU)
+    let cpus = read("cpuset.cpus.effective").ok();
+    let mems = read("cpuset.mems.effective").ok();
+    if let Some(count) = cpus.as_deref().and_then(count_cpu_list) {
+        metrics.cpuset_cpus.set(count as i64);
+    }
+    if let Some(count) = mems.as_deref().and_then(count_cpu_list) {
+        metrics.cpuset_mems.set(count as i64);
+    }
+    if cpus.is_some() || mems.is_some() {
+        metrics.cpuset_info.reset();
+        metrics
+            .cpuset_info
+            .with_label_values(&[cpus.as_deref().unwrap_or(""), mems.as_deref().unwrap_or("")])
+            .set(1);
+    }
+
+    // PSI: "some avg10=0.00 avg60=0.00 avg300=0.00 total=0" (+ "full ...")
+    for resource in ["cpu", "memory", "io"] {
+        let Ok(content) = read(&format!("{resource}.pressure")) else {
+            continue;
+        };
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let Some(kind) = parts.next() else {
+                continue;
+            };
+            for kv in parts {
+                let Some((key, val)) = kv.split_once('=') else {
+                    continue;
+                };
+                if key == "total" {
+                    if let Ok(usec) = val.parse::<u64>() {
+                        let total = OFFSETS
+                            .lock()
+                            .expect("cgroup OFFSETS mutex poisoned")
+                            .adjust(
+                                &format!("{}:{resource}.pressure:{kind}", root.display()),
+                                usec as f64,
+                            );
+                        metrics
+                            .pressure_stall_seconds
+                            .with_label_values(&[resource, kind])
+                            .set(total / 1_000_000.0);
+                    }
+                } else if let Ok(pct) = val.parse::<f64>() {
+                    metrics
+                        .pressure_avg_percent
+                        .with_label_values(&[resource, kind, key])
+                        .set(pct);
+                }
+            }
+        }
+    }
+
+    // memory.*
+    let mut mem_current = None;
+    if let Ok(s) = read("memory.current")
+        && let Ok(v) = s.parse::<u64>()
+    {
+        mem_current = Some(v);
+        metrics.mem_current_bytes.set(v as f64);
+        peaks::observe(
+            &format!("{}:memory.current", root.display()),
+            &metrics.mem_current_peaks,
+            v as f64,
+        );
+    }
+    if let Ok(s) = read("memory.peak")
+        && let Ok(v) = s.parse::<u64>()
+    {
+        metrics.mem_peak_bytes.set(v as f64);
+    }
+    let mut mem_max = None;
+    if let Ok(s) = read("memory.max") {
         if s == "max" {
-            metrics.mem_max_bytes.set(f64::INFINITY);
+            mem_max = Some(f64::INFINITY);
         } else if let Ok(v) = s.parse::<u64>() {
-            metrics.mem_max_bytes.set(v as f64);
+            mem_max = Some(v as f64);
         }
     }
-    if let Ok(s) = read_to_string(&root.join("memory.high")) {
+    if let Some(v) = mem_max {
+        metrics.mem_max_bytes.set(v);
+    }
+    if let Ok(s) = read("memory.high") {
         if s == "max" {
             metrics.mem_high_bytes.set(f64::INFINITY);
         } else if let Ok(v) = s.parse::<u64>() {
             metrics.mem_high_bytes.set(v as f64);
         }
     }
-    if let Ok(s) = read_to_string(&root.join("memory.low")) {
+    if let Ok(s) = read("memory.low") {
         if s == "max" {
             metrics.mem_low_bytes.set(f64::INFINITY);
         } else if let Ok(v) = s.parse::<u64>() {
             metrics.mem_low_bytes.set(v as f64);
         }
     }
+    if let Ok(s) = read("memory.min") {
+        if s == "max" {
+            metrics.mem_min_bytes.set(f64::INFINITY);
+        } else if let Ok(v) = s.parse::<u64>() {
+            metrics.mem_min_bytes.set(v as f64);
+        }
+    }
 
-    if let Ok(ev) = read_to_string(&root.join("memory.events")) {
+    // memory.stat - celý rozpad (anon vs page cache, slab, faulty, ...)
+    let mut inactive_file = 0;
+    if let Ok(content) = read("memory.stat") {
+        let mut offsets = OFFSETS.lock().expect("cgroup OFFSETS mutex poisoned");
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(Ok(v))) = (parts.next(), parts.next().map(str::parse::<u64>))
+            else {
+                continue;
+            };
+            if key == "inactive_file" {
+                inactive_file = v;
+            }
+            metrics.mem_stat.with_label_values(&[key]).set(v as f64);
+
+            // thrashing signály jako samostatné countery
+            let counter = match key {
+                "workingset_refault_anon" => metrics
+                    .mem_workingset_refault_total
+                    .with_label_values(&["anon"]),
+                "workingset_refault_file" => metrics
+                    .mem_workingset_refault_total
+                    .with_label_values(&["file"]),
+                "workingset_activate_anon" => metrics
+                    .mem_workingset_activate_total
+                    .with_label_values(&["anon"]),
+                "workingset_activate_file" => metrics
+                    .mem_workingset_activate_total
+                    .with_label_values(&["file"]),
+                "pgscan" => metrics.mem_pgscan_total.clone(),
+                "pgsteal" => metrics.mem_pgsteal_total.clone(),
+                _ => continue,
+            };
+            counter.set(offsets.adjust(&format!("{}:memory.stat:{key}", root.display()), v as f64));
+        }
+    }
+
+    // working set + headroom - stejně jako kubelet pro eviction
+    if let Some(current) = mem_current {
+        let working_set = current.saturating_sub(inactive_file) as f64;
+        metrics.mem_working_set_bytes.set(working_set);
+
+        if let Some(max) = mem_max {
+            metrics.mem_headroom_bytes.set(max - working_set);
+        }
+    }
+
+    // memory.events je hierarchický (včetně potomků), .local jen tahle cgroupa
+    for (file, vec) in [
+        ("memory.events", &metrics.mem_events_total),
+        ("memory.events.local", &metrics.mem_events_local_total),
+    ] {
+        let Ok(ev) = read(file) else {
+            continue;
+        };
+        let mut offsets = OFFSETS.lock().expect("cgroup OFFSETS mutex poisoned");
         for line in ev.lines() {
             let mut parts = line.split_whitespace();
             let key = parts.next().unwrap_or("");
             let val = parts.next().unwrap_or("0").parse::<u64>().unwrap_or(0);
             if !key.is_empty() {
+                vec.with_label_values(&[key]).set(
+                    offsets.adjust(&format!("{}:{file}:{key}", root.display()), val as f64) as i64,
+                );
+            }
+        }
+    }
+
+    // memory.swap.*
+    if let Ok(s) = read("memory.swap.current")
+        && let Ok(v) = s.parse::<u64>()
+    {
+        metrics.mem_swap_current_bytes.set(v as f64);
+    }
+    for (name, gauge) in [
+        ("memory.swap.max", &metrics.mem_swap_max_bytes),
+        ("memory.swap.high", &metrics.mem_swap_high_bytes),
+    ] {
+        if let Ok(s) = read(name) {
+            if s == "max" {
+                gauge.set(f64::INFINITY);
+            } else if let Ok(v) = s.parse::<u64>() {
+                gauge.set(v as f64);
+            }
+        }
+    }
+    // memory.zswap.* (6.x kernely se zswap)
+    if let Ok(s) = read("memory.zswap.current")
+        && let Ok(v) = s.parse::<u64>()
+    {
+        metrics.mem_zswap_current_bytes.set(v as f64);
+    }
+    if let Ok(s) = read("memory.zswap.max") {
+        if s == "max" {
+            metrics.mem_zswap_max_bytes.set(f64::INFINITY);
+        } else if let Ok(v) = s.parse::<u64>() {
+            metrics.mem_zswap_max_bytes.set(v as f64);
+        }
+    }
+    if let Ok(ev) = read("memory.swap.events") {
+        let mut offsets = OFFSETS.lock().expect("cgroup OFFSETS mutex poisoned");
+        for line in ev.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(Ok(val))) = (parts.next(), parts.next().map(str::parse::<u64>))
+            else {
+                continue;
+            };
+            metrics
+                .mem_swap_events_total
+                .with_label_values(&[key])
+                .set(offsets.adjust(
+                    &format!("{}:memory.swap.events:{key}", root.display()),
+                    val as f64,
+                ) as i64);
+        }
+    }
+
+    // io.stat: "8:0 rbytes=.. wbytes=.. rios=.. wios=.. dbytes=.. dios=.."
+    if let Ok(content) = read("io.stat") {
+        let mut offsets = OFFSETS.lock().expect("cgroup OFFSETS mutex poisoned");
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let Some(device) = parts.next() else {
+                continue;
+            };
+            for kv in parts {
+                let Some((key, val)) = kv.split_once('=') else {
+                    continue;
+                };
+                let vec = match key {
+                    "rbytes" => &metrics.io_read_bytes,
+                    "wbytes" => &metrics.io_write_bytes,
+                    "rios" => &metrics.io_reads,
+                    "wios" => &metrics.io_writes,
+                    "dbytes" => &metrics.io_discard_bytes,
+                    _ => continue,
+                };
+                let Ok(v) = val.parse::<u64>() else {
+                    continue;
+                };
+                vec.with_label_values(&[device]).set(offsets.adjust(
+                    &format!("{}:io.stat:{device}:{key}", root.display()),
+                    v as f64,
+                ));
+            }
+        }
+    }
+
+    // controllery - "proč jsou pids metriky nulové" (controller chybí)
+    metrics.controller_enabled.reset();
+    for (file, scope) in [
+        ("cgroup.controllers", "self"),
+        ("cgroup.subtree_control", "subtree"),
+    ] {
+        if let Ok(content) = read(file) {
+            for controller in content.split_whitespace() {
                 metrics
-                    .mem_events_total
-                    .with_label_values(&[key])
-                    .set(val as i64);
+                    .controller_enabled
+                    .with_label_values(&[controller, scope, "v2"])
+                    .set(1);
             }
         }
     }
+    for controller in layout.v1_controllers() {
+        metrics
+            .controller_enabled
+            .with_label_values(&[&controller, "self", "v1"])
+            .set(1);
+    }
+
+    // freezer - zmrazený pod vypadá jako záhadné latence
+    if let Ok(content) = read("cgroup.events") {
+        for line in content.lines() {
+            if let Some(v) = line.strip_prefix("frozen ")
+                && let Ok(v) = v.trim().parse::<i64>()
+            {
+                metrics.frozen.set(v);
+            }
+        }
+    }
+    if let Ok(s) = read("cgroup.freeze")
+        && let Ok(v) = s.parse::<i64>()
+    {
+        metrics.freeze_requested.set(v);
+    }
+
+    // cgroup.stat (jen v2)
+    if let Ok(content) = read("cgroup.stat") {
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(Ok(v))) = (parts.next(), parts.next().map(str::parse::<i64>))
+            else {
+                continue;
+            };
+            match key {
+                "nr_descendants" => metrics.descendants.set(v),
+                "nr_dying_descendants" => metrics.dying_descendants.set(v),
+                _ => {}
+            }
+        }
+    }
+
+    // misc controller - jen kde je zapnutý (typicky confidential computing nody)
+    for (file, vec) in [
+        ("misc.current", &metrics.misc_current),
+        ("misc.max", &metrics.misc_max),
+    ] {
+        let Ok(content) = read(file) else {
+            continue;
+        };
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(res), Some(val)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let v = if val == "max" {
+                f64::INFINITY
+            } else if let Ok(v) = val.parse::<u64>() {
+                v as f64
+            } else {
+                continue;
+            };
+            vec.with_label_values(&[res]).set(v);
+        }
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn update_reads_fixture_cgroup() {
+        let cfg = Config::for_fixtures();
+        let registry = Registry::new();
+        let metrics = CgroupMetrics::new_for_roots(&registry, &cfg)
+            .unwrap()
+            .remove(0);
+
+        update(&metrics, &cfg.cgroup_root).unwrap();
+
+        assert_eq!(metrics.cpu_usage_seconds.get(), 1052.012345);
+        assert_eq!(metrics.cpu_nr_throttled.get(), 4120);
+        assert_eq!(metrics.cpu_throttled_seconds.get(), 88.120334);
+        assert_eq!(metrics.cpu_limit_cores.get(), 0.5);
+        assert_eq!(metrics.mem_current_bytes.get(), 536870912.0);
+    }
+}
@@ -0,0 +1,91 @@
+//! Změny všech číselných metrik mezi posledními dvěma cykly (GET /delta).
+//!
+//! Po každém updatu se uloží snapshot registry; endpoint vrací rozdíl
+//! posledního snapshotu proti předchozímu, ne proti okamžiku requestu.
+
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::metrics::{Metrics, sample_value};
+
+type Snapshot = BTreeMap<String, f64>;
+
+#[derive(Default)]
+struct History {
+    previous: Option<(Instant, Snapshot)>,
+    current: Option<(Instant, Snapshot)>,
+}
+
+static HISTORY: Lazy<Mutex<History>> = Lazy::new(|| Mutex::new(History::default()));
+
+#[derive(Debug, Serialize)]
+pub struct Changes {
+    /// Čas mezi oběma cykly; None dokud neproběhnou aspoň dva.
+    pub elapsed_seconds: Option<f64>,
+    /// Série `name{label="value",...}` → změna hodnoty.
+    pub deltas: BTreeMap<String, f64>,
+}
+
+/// Uloží snapshot registry po dokončeném cyklu.
+pub fn record(metrics: &Metrics) {
+    let mut snapshot = Snapshot::new();
+    for mf in metrics.registry.gather() {
+        for m in mf.get_metric() {
+            let Some(v) = sample_value(m, mf.get_field_type()) else {
+                continue;
+            };
+            let labels: Vec<String> = m
+                .get_label()
+                .iter()
+                .map(|lp| format!("{}=\"{}\"", lp.name(), lp.value()))
+                .collect();
+            let series = if labels.is_empty() {
+                mf.name().to_string()
+            } else {
+                format!("{}{{{}}}", mf.name(), labels.join(","))
+            };
+            snapshot.insert(series, v);
+        }
+    }
+
+    let mut history = HISTORY.lock().expect("changes HISTORY mutex poisoned");
+    history.previous = history.current.take();
+    history.current = Some((Instant::now(), snapshot));
+}
+
+/// Rozdíl posledních dvou snapshotů; série bez předchozí hodnoty
+/// nebo s nekonečnem (např. limit "max") se vynechají.
+pub fn last() -> Changes {
+    let history = HISTORY.lock().expect("changes HISTORY mutex poisoned");
+    let (Some((prev_at, prev)), Some((cur_at, cur))) = (&history.previous, &history.current) else {
+        return Changes {
+            elapsed_seconds: None,
+            deltas: BTreeMap::new(),
+        };
+    };
+
+    let deltas = cur
+        .iter()
+        .filter_map(|(series, &v)| {
+            let old = *prev.get(series)?;
+            let d = v - old;
+            d.is_finite().then(|| (series.clone(), d))
+        })
+        .collect();
+
+    Changes {
+        elapsed_seconds: Some(
+            cur_at
+                .checked_duration_since(*prev_at)
+                .unwrap_or(Duration::ZERO)
+                .as_secs_f64(),
+        ),
+        deltas,
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::{Result, bail};
+
+/// Přepínače z příkazové řádky (vše ostatní se konfiguruje přes env).
+#[derive(Debug, Clone, Default)]
+pub struct Cli {
+    /// `--once` - jeden sběr metrik, výpis na stdout a konec
+    pub once: bool,
+
+    /// `--version` - vypíše verzi + build info a skončí
+    pub version: bool,
+
+    /// `--dev-stub` - místo čtení /proc a /sys servíruje syntetické hodnoty
+    pub dev_stub: bool,
+}
+
+impl Cli {
+    pub fn from_args() -> Result<Cli> {
+        let mut cli = Cli::default();
+
+        for arg in std::env::args().skip(1) {
+            match arg.as_str() {
+                "--once" => cli.once = true,
+                "--version" | "-V" => cli.version = true,
+                "--dev-stub" => cli.dev_stub = true,
+                other => {
+                    bail!("unknown argument '{other}' (supported: --once, --version, --dev-stub)")
+                }
+            }
+        }
+
+        Ok(cli)
+    }
+}
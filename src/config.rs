@@ -1,4 +1,10 @@
-use std::{collections::HashMap, env, net::SocketAddr, path::PathBuf};
+use std::{
+    collections::HashMap,
+    env,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    time::Duration,
+};
 
 use anyhow::{Context, Result};
 use regex::Regex;
@@ -10,19 +16,147 @@ pub enum ProcessTarget {
     Single(i32),
     /// Explicitní seznam PIDů (TARGET_PID_LIST)
     PidList(Vec<i32>),
-    /// Regex pro výběr procesů podle cmdline/comm (TARGET_PID_REGEXP)
-    Regex(Regex),
+    /// Regex pro výběr procesů podle cmdline/comm (TARGET_PID_REGEXP);
+    /// nalezené PIDy se drží `cache_ttl` (TARGET_PID_REGEXP_CACHE_SECS)
+    Regex { re: Regex, cache_ttl: Duration },
+    /// Členové cgroupy podle jejího cgroup.procs (TARGET_CGROUP_PROCS)
+    Cgroup(PathBuf),
+}
+
+impl ProcessTarget {
+    /// Lidsky čitelný popis selektoru (pro debug endpointy).
+    pub fn describe(&self) -> String {
+        match self {
+            ProcessTarget::Single(pid) => format!("pid:{pid}"),
+            ProcessTarget::PidList(pids) => format!(
+                "pid_list:{}",
+                pids.iter()
+                    .map(|p| p.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            ProcessTarget::Regex { re, .. } => format!("regex:{}", re.as_str()),
+            ProcessTarget::Cgroup(dir) => format!("cgroup:{}", dir.display()),
+        }
+    }
+}
+
+/// Co dělat s potomky CGROUP_ROOT (CGROUP_RECURSIVE).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecursiveMode {
+    /// Součet listů do jedné série per root
+    Aggregate,
+    /// Série per potomek s labelem cgroup
+    PerChild,
+}
+
+/// Porovnávací operátor threshold pravidla.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThresholdOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl ThresholdOp {
+    pub fn eval(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            ThresholdOp::Gt => lhs > rhs,
+            ThresholdOp::Ge => lhs >= rhs,
+            ThresholdOp::Lt => lhs < rhs,
+            ThresholdOp::Le => lhs <= rhs,
+            ThresholdOp::Eq => lhs == rhs,
+            ThresholdOp::Ne => lhs != rhs,
+        }
+    }
+}
+
+/// Jedno pravidlo z THRESHOLDS, např.
+/// `mem_high:cgroup_memory_current_bytes>8e8:60s` nebo
+/// `oom:cgroup_memory_events_total{type="oom_kill"}>0`.
+#[derive(Debug, Clone)]
+pub struct Threshold {
+    pub name: String,
+    /// Jméno metriky bez METRICS_PREFIX
+    pub metric: String,
+    /// Volitelný selektor labelů (všechny musí sedět)
+    pub labels: Vec<(String, String)>,
+    pub op: ThresholdOp,
+    pub value: f64,
+    /// Jak dlouho musí podmínka trvat, než se hlásí breach (default 0).
+    pub for_duration: Duration,
+}
+
+/// Životní cyklus HTTP/1 spojení - scrapery drží spojení otevřená hodiny.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Povolit keep-alive (default true); false = po každé odpovědi zavřít.
+    pub keep_alive: bool,
+    /// Zavřít spojení bez requestu déle než tato doba. None = bez limitu.
+    pub idle_timeout: Option<Duration>,
+    /// Po tolika requestech spojení zavřít (Connection: close). None = bez limitu.
+    pub max_requests_per_connection: Option<u64>,
+    /// Odpovědi na pipelinované requesty posílat najednou (pipeline_flush).
+    pub pipeline_flush: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub listen_addr: SocketAddr,
+
+    /// Nastavení HTTP spojení (HTTP_KEEP_ALIVE, HTTP_IDLE_TIMEOUT_SECS,
+    /// HTTP_MAX_REQUESTS_PER_CONNECTION, HTTP_PIPELINE_FLUSH).
+    pub http: HttpConfig,
     pub cgroup_root: PathBuf,
+
+    /// Sledované cgroup roots (CGROUP_ROOTS nebo alias CGROUP_TARGETS,
+    /// `name=path,...`). Bez nich jediná root `default` = CGROUP_ROOT.
+    pub cgroup_roots: Vec<CgroupRoot>,
+
+    /// Kořen procfs (PROC_ROOT, default /proc) - např. host /proc namountovaný
+    /// jinam, nebo adresář s fixtures.
+    pub proc_root: PathBuf,
+
+    /// Kořen sysfs (SYS_ROOT, default /sys).
+    pub sys_root: PathBuf,
+
     pub downward_dir: Option<PathBuf>,
 
     /// Jaké procesy sledovat v /proc (Single PID, list, nebo regexp).
     pub process_target: Option<ProcessTarget>,
 
+    /// Kromě součtu přes skupinu exportovat i série per proces s labely
+    /// pid + comm (PROCESS_METRICS_MODE=per_pid, default aggregate).
+    pub process_per_pid: bool,
+
+    /// Rozšířit cílovou skupinu o všechny potomky matchnutých procesů
+    /// (FOLLOW_CHILDREN) - prefork servery s TARGET_PID na master procesu.
+    pub follow_children: bool,
+
+    /// Pojmenované skupiny procesů (TARGET_GROUPS="web=nginx.*;worker=celery.*"),
+    /// každá agregovaná stejně jako TARGET_PID* (process_group_* s labelem group).
+    pub target_groups: Vec<TargetGroup>,
+
+    /// CPU čas vláken cílových procesů rozpadnutý podle jména vlákna
+    /// (PROCESS_THREAD_CPU, /proc/<pid>/task/*/stat). Default vypnuto.
+    pub process_thread_cpu: bool,
+
+    /// PSS / USS z /proc/<pid>/smaps_rollup (PROCESS_SMAPS). Dražší než
+    /// status (kernel prochází všechna mapování), proto default vypnuto.
+    pub process_smaps: bool,
+
+    /// Vlákna cílových procesů v uninterruptible sleep (D) a wchan toho
+    /// nejdéle zaseklého (PROCESS_STALLS) - visící NFS / disk. Prochází
+    /// task/*/stat všech vláken, proto default vypnuto.
+    pub process_stalls: bool,
+
+    /// CPU / blkio / swapin delay per cílový proces přes taskstats netlink
+    /// (TASKSTATS). Potřebuje CAP_NET_ADMIN a host network namespace.
+    pub taskstats: bool,
+
     /// Prefix / namespace pro všechny metriky (např. "nac", "kip")
     pub metrics_prefix: Option<String>,
 
@@ -42,11 +176,154 @@ pub struct Config {
     pub update_interval_secs: u64,
 
     /// Network interface, který chceme sledovat (např. "eth0").
-    /// Default: "eth0".
+    /// Když cílový proces běží v jiném net namespace, čte se z jeho
+    /// /proc/<pid>/net/dev. Default: "eth0".
     pub net_interface: String,
 
     /// Jméno nodu (pokud je k dispozici z env NODE_NAME)
     pub node_name: Option<String>,
+
+    /// Exportovat i odvozené rate metriky (*_per_second, *_cores) spočtené
+    /// z rozdílů mezi updaty (DERIVED_RATES). Default vypnuto.
+    pub derived_rates: bool,
+
+    /// Klouzavá okna pro *_peak_<okno> metriky (PEAK_WINDOWS="5m,1h").
+    /// Dvojice (label pro jméno metriky, délka okna).
+    pub peak_windows: Vec<(String, Duration)>,
+
+    /// Okno pro process_availability_ratio (AVAILABILITY_WINDOW, default 1h).
+    pub availability_window: Duration,
+
+    /// Soubor pro perzistenci stavu mezi restarty (STATE_FILE), typicky emptyDir.
+    pub state_file: Option<PathBuf>,
+
+    /// Jak často stav ukládat (STATE_SAVE_INTERVAL_SECS, default 60s).
+    pub state_save_interval_secs: u64,
+
+    /// Jednoduchá threshold pravidla vyhodnocovaná každý cyklus (THRESHOLDS,
+    /// oddělená ';'), exportovaná jako exporter_threshold_breached{name}.
+    pub thresholds: Vec<Threshold>,
+
+    /// PEM certifikáty (soubory nebo adresáře), jejichž expiraci exportujeme
+    /// (CERT_PATHS, oddělené čárkou).
+    pub cert_paths: Vec<PathBuf>,
+
+    /// Cgroup metriky sbírat z cgroupy cílového procesu (ProcessTarget) místo
+    /// CGROUP_ROOT (CGROUP_FOLLOW_TARGET). Default vypnuto.
+    pub cgroup_follow_target: bool,
+
+    /// Cgroup metriky sbírat z cgroupy systemd unity nebo slice
+    /// (CGROUP_SYSTEMD_UNIT, např. `myapp.service`) místo CGROUP_ROOT.
+    pub cgroup_systemd_unit: Option<String>,
+
+    /// Cgroup metriky sbírat z cgroupy kontejneru s tímto ID (nebo prefixem
+    /// ID) - runtime a cgroup driver se rozpoznají z cesty (CGROUP_CONTAINER_ID).
+    pub cgroup_container_id: Option<String>,
+
+    /// Bez vlastního cgroup namespace (kontejner vidí hostitelskou hierarchii)
+    /// sestoupit do cgroupy kontejneru (CGROUP_NS_DETECT, default zapnuto).
+    /// Jen když CGROUP_ROOT není nastavený ručně.
+    pub cgroup_ns_detect: bool,
+
+    /// Node režim: agregace všech procesů z PROC_ROOT per pod (NODE_MODE).
+    /// Předpokládá hostPID a hostitelský /proc.
+    pub node_mode: bool,
+
+    /// Adresář kubeletu s `<namespace>_<pod>_<uid>` podadresáři pro
+    /// překlad UID na jméno podu (PODS_LOG_DIR, default /var/log/pods).
+    pub pods_log_dir: PathBuf,
+
+    /// Sdílený PID namespace: agregace viditelných procesů per kontejner
+    /// (SIBLING_CONTAINERS). Default vypnuto.
+    pub sibling_containers: bool,
+
+    /// Symlinky kubeletu `<pod>_<ns>_<container>-<id>.log` pro překlad ID
+    /// kontejneru na jméno (CONTAINERS_LOG_DIR, default /var/log/containers).
+    pub containers_log_dir: PathBuf,
+
+    /// CGROUP_ROOT je cgroupa celého podu - sbírat i per-kontejnerové metriky
+    /// z jejích potomků (POD_CGROUP_MODE). Default vypnuto.
+    pub pod_cgroup_mode: bool,
+
+    /// Jako POD_CGROUP_MODE, ale cgroupu podu najde sidecar sám jako rodiče
+    /// vlastní cgroupy (POD_CGROUP_DISCOVER). Default vypnuto.
+    pub pod_cgroup_discover: bool,
+
+    /// Procházet potomky cgroup roots (CGROUP_RECURSIVE=aggregate|per-child)
+    /// do hloubky CGROUP_RECURSIVE_MAX_DEPTH (default 3). Default vypnuto.
+    pub cgroup_recursive: Option<RecursiveMode>,
+    pub cgroup_recursive_max_depth: usize,
+
+    /// Hlídat memory.events přes inotify a počítat OOM kill okamžitě
+    /// (OOM_WATCH). Default vypnuto.
+    pub oom_watch: bool,
+
+    /// PSI triggery na pressure souborech cgroupy (PSI_TRIGGERS,
+    /// `resource:kind:stall_ms:window_ms` oddělené čárkou).
+    pub psi_triggers: Vec<PsiTrigger>,
+
+    /// Cgroupy pod CGROUP_ROOT podle vzoru (CGROUP_GLOB, např.
+    /// `system.slice/*.service`), exportované s labelem path.
+    pub cgroup_glob: Option<CgroupGlob>,
+
+    /// HTTP probe (PROBE_HTTP, URL oddělené čárkou) a TCP probe
+    /// (PROBE_TCP, host:port oddělené čárkou) spouštěné každý cyklus.
+    pub probe_http: Vec<HttpProbe>,
+    pub probe_tcp: Vec<String>,
+    /// DNS jména resolvovaná každý cyklus (PROBE_DNS; "true" = kubernetes.default).
+    pub probe_dns: Vec<String>,
+
+    /// Timeout jedné probe (PROBE_TIMEOUT_MS, default 2000).
+    pub probe_timeout: Duration,
+
+    /// Příkazy exec collectoru (EXEC_COMMANDS="name=cmd;name2=cmd2"),
+    /// jejich stdout je Prometheus text exposition (série dostanou label
+    /// exec_command se jménem příkazu).
+    pub exec_commands: Vec<ExecCommand>,
+
+    /// Jak často příkazy spouštět (EXEC_INTERVAL_SECS, default 60) a jak
+    /// dlouho nejvýš smí běžet (EXEC_TIMEOUT_SECS, default 10).
+    pub exec_interval: Duration,
+    pub exec_timeout: Duration,
+
+    /// Bloková zařízení (jména ze /sys/block, BLOCK_DEVICES), pro která
+    /// exportujeme saturaci. Prázdné = vypnuto.
+    pub block_devices: Vec<String>,
+
+    /// Mapování remote CIDR → logická služba (PEER_CIDRS), TCP spojení se
+    /// pak počítají per služba s pevnou kardinalitou.
+    pub peer_cidrs: Vec<PeerCidr>,
+
+    /// Interfacy, pro které exportujeme tc qdisc/class statistiky
+    /// (TC_INTERFACES, oddělené čárkou). Prázdné = vypnuto.
+    pub tc_interfaces: Vec<String>,
+
+    /// Cesty (mountpointy), jejichž využití exportujeme (FS_PATHS).
+    pub fs_paths: Vec<PathBuf>,
+
+    /// statvfs volat v mount namespace cílového procesu (FS_TARGET_MOUNT_NS),
+    /// vyžaduje CAP_SYS_ADMIN a ProcessTarget.
+    pub fs_target_mount_ns: bool,
+
+    /// Soubory, jejichž existenci, velikost a stáří sledujeme
+    /// (WATCH_FILES, oddělené čárkou).
+    pub watch_files: Vec<PathBuf>,
+
+    /// Cache zakódovaného /metrics mezi updaty a Cache-Control max-age
+    /// (METRICS_CACHE_SECONDS). None = kódovat při každém scrapu.
+    pub metrics_cache_seconds: Option<u64>,
+
+    /// Horní mez RSS exporteru (SELF_MEMORY_BUDGET_BYTES); po překročení
+    /// se vypínají volitelné collectory s vysokou kardinalitou.
+    pub memory_budget_bytes: Option<u64>,
+
+    /// Proaktivní reclaim sonda (MEMORY_RECLAIM_PROBE_BYTES,
+    /// MEMORY_RECLAIM_PROBE_INTERVAL_SECS). Default vypnuto.
+    pub memory_reclaim_probe: Option<ReclaimProbe>,
+
+    /// Interval rychlého vzorkování memory.current a run queue mezi updaty
+    /// (FAST_SAMPLE_INTERVAL_MS). None = vypnuto.
+    pub fast_sample_interval_ms: Option<u64>,
 }
 
 impl Config {
@@ -55,7 +332,55 @@ impl Config {
         let listen = env::var("EXPORTER_LISTEN").unwrap_or_else(|_| "0.0.0.0:9100".to_string());
         let listen_addr: SocketAddr = listen.parse().context("EXPORTER_LISTEN parse error")?;
 
-        let cgroup_root = env::var("CGROUP_ROOT").unwrap_or_else(|_| "/sys/fs/cgroup".to_string());
+        let http = HttpConfig {
+            keep_alive: env::var("HTTP_KEEP_ALIVE").is_err() || env_bool("HTTP_KEEP_ALIVE"),
+            idle_timeout: env::var("HTTP_IDLE_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .filter(|&s| s > 0)
+                .map(Duration::from_secs),
+            max_requests_per_connection: env::var("HTTP_MAX_REQUESTS_PER_CONNECTION")
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .filter(|&n| n > 0),
+            pipeline_flush: env_bool("HTTP_PIPELINE_FLUSH"),
+        };
+
+        let proc_root =
+            PathBuf::from(env::var("PROC_ROOT").unwrap_or_else(|_| "/proc".to_string()));
+        let sys_root = PathBuf::from(env::var("SYS_ROOT").unwrap_or_else(|_| "/sys".to_string()));
+
+        // bez CGROUP_ROOT odvodíme cestu od SYS_ROOT (default /sys/fs/cgroup)
+        let cgroup_root = env::var("CGROUP_ROOT")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| sys_root.join("fs/cgroup"));
+
+        // CGROUP_TARGETS je alias (stejný formát), obě najednou nedávají smysl
+        let roots_var = match (env_list("CGROUP_ROOTS"), env_list("CGROUP_TARGETS")) {
+            (roots, targets) if !roots.is_empty() && !targets.is_empty() => {
+                anyhow::bail!("CGROUP_ROOTS and CGROUP_TARGETS are mutually exclusive")
+            }
+            (roots, _) if !roots.is_empty() => ("CGROUP_ROOTS", roots),
+            (_, targets) => ("CGROUP_TARGETS", targets),
+        };
+        let mut cgroup_roots = Vec::new();
+        for item in roots_var.1 {
+            let root = parse_cgroup_root(&item)
+                .with_context(|| format!("{} parse error at '{item}'", roots_var.0))?;
+            if cgroup_roots
+                .iter()
+                .any(|r: &CgroupRoot| r.name == root.name)
+            {
+                anyhow::bail!("{}: duplicate root name '{}'", roots_var.0, root.name);
+            }
+            cgroup_roots.push(root);
+        }
+        if cgroup_roots.is_empty() {
+            cgroup_roots.push(CgroupRoot {
+                name: "default".to_string(),
+                path: cgroup_root.clone(),
+            });
+        }
 
         let downward_dir = env::var("DOWNWARD_API_DIR").ok().map(PathBuf::from);
 
@@ -67,8 +392,28 @@ impl Config {
         let target_pid_regexp_env = env::var("TARGET_PID_REGEXP")
             .ok()
             .filter(|v| !v.trim().is_empty());
+        // "1"/"true" = CGROUP_ROOT, jinak cesta ke cgroupě
+        let target_cgroup_env = env::var("TARGET_CGROUP_PROCS")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| {
+                !v.is_empty()
+                    && !matches!(
+                        v.to_ascii_lowercase().as_str(),
+                        "0" | "false" | "no" | "off"
+                    )
+            });
+        if target_cgroup_env.is_some()
+            && (target_pid_env.is_some()
+                || target_pid_list_env.is_some()
+                || target_pid_regexp_env.is_some())
+        {
+            warn!(
+                "TARGET_CGROUP_PROCS is set together with TARGET_PID / TARGET_PID_LIST / TARGET_PID_REGEXP - ignoring TARGET_CGROUP_PROCS"
+            );
+        }
 
-        // Priorita: TARGET_PID > TARGET_PID_LIST > TARGET_PID_REGEXP
+        // Priorita: TARGET_PID > TARGET_PID_LIST > TARGET_PID_REGEXP > TARGET_CGROUP_PROCS
         let process_target = if let Some(pid_str) = target_pid_env {
             if target_pid_list_env.is_some() {
                 warn!(
@@ -114,10 +459,40 @@ impl Config {
             }
         } else if let Some(re_str) = target_pid_regexp_env {
             let re = Regex::new(&re_str).context("TARGET_PID_REGEXP invalid regex")?;
-            Some(ProcessTarget::Regex(re))
+            let cache_ttl = Duration::from_secs(
+                env::var("TARGET_PID_REGEXP_CACHE_SECS")
+                    .ok()
+                    .and_then(|s| s.trim().parse::<u64>().ok())
+                    .unwrap_or(0),
+            );
+            Some(ProcessTarget::Regex { re, cache_ttl })
+        } else if let Some(v) = target_cgroup_env {
+            let dir = if matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on") {
+                cgroup_root.clone()
+            } else {
+                PathBuf::from(v)
+            };
+            Some(ProcessTarget::Cgroup(dir))
         } else {
             None
         };
+        let process_thread_cpu = env_bool("PROCESS_THREAD_CPU");
+        let follow_children = env_bool("FOLLOW_CHILDREN");
+        let process_smaps = env_bool("PROCESS_SMAPS");
+        let process_stalls = env_bool("PROCESS_STALLS");
+        let taskstats = env_bool("TASKSTATS");
+        let process_per_pid = match env::var("PROCESS_METRICS_MODE")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "aggregate" => false,
+            "per_pid" | "per-pid" => true,
+            other => {
+                anyhow::bail!("PROCESS_METRICS_MODE must be aggregate or per_pid, got {other:?}")
+            }
+        };
 
         // --- Metrics prefix / labels / K8s resource hints ---
         let metrics_prefix = env::var("METRICS_PREFIX")
@@ -131,6 +506,11 @@ impl Config {
 
         let static_labels =
             parse_static_labels(&env::var("METRICS_STATIC_LABELS").unwrap_or_default());
+        // label root nesou všechny cgroup série (CGROUP_ROOTS) - stejnojmenný
+        // const label by shodil registraci metrik
+        if static_labels.contains_key("root") {
+            anyhow::bail!("METRICS_STATIC_LABELS: label 'root' is reserved for CGROUP_ROOTS");
+        }
 
         let cpu_requests_mcpu = env::var("CPU_REQUESTS_MCPU")
             .ok()
@@ -160,11 +540,248 @@ impl Config {
 
         let node_name = env::var("NODE_NAME").ok().filter(|s| !s.is_empty());
 
+        let derived_rates = env_bool("DERIVED_RATES");
+        let pod_cgroup_mode = env_bool("POD_CGROUP_MODE");
+        let pod_cgroup_discover = env_bool("POD_CGROUP_DISCOVER");
+        let oom_watch = env_bool("OOM_WATCH");
+        let mut psi_triggers = Vec::new();
+        for item in env_list("PSI_TRIGGERS") {
+            psi_triggers.push(
+                parse_psi_trigger(&item)
+                    .with_context(|| format!("PSI_TRIGGERS parse error at '{item}'"))?,
+            );
+        }
+        let cgroup_recursive = match env::var("CGROUP_RECURSIVE")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "0" | "false" | "no" | "off" => None,
+            "aggregate" => Some(RecursiveMode::Aggregate),
+            "per-child" | "1" | "true" | "yes" | "on" => Some(RecursiveMode::PerChild),
+            other => {
+                anyhow::bail!("CGROUP_RECURSIVE must be aggregate or per-child, got {other:?}")
+            }
+        };
+        let cgroup_glob = match env::var("CGROUP_GLOB") {
+            Ok(v) if !v.trim().is_empty() => Some(
+                parse_cgroup_glob(&v)
+                    .with_context(|| format!("CGROUP_GLOB parse error at '{v}'"))?,
+            ),
+            _ => None,
+        };
+        let cgroup_recursive_max_depth = match env::var("CGROUP_RECURSIVE_MAX_DEPTH") {
+            Ok(v) => v
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|&d| d > 0)
+                .context("CGROUP_RECURSIVE_MAX_DEPTH must be a positive integer")?,
+            Err(_) => 3,
+        };
+        let cgroup_follow_target = env_bool("CGROUP_FOLLOW_TARGET");
+        let cgroup_systemd_unit = env::var("CGROUP_SYSTEMD_UNIT")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let cgroup_container_id = env::var("CGROUP_CONTAINER_ID")
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let cgroup_ns_detect = env::var("CGROUP_ROOT").is_err()
+            && (env::var("CGROUP_NS_DETECT").is_err() || env_bool("CGROUP_NS_DETECT"));
+        let node_mode = env_bool("NODE_MODE");
+        let sibling_containers = env_bool("SIBLING_CONTAINERS");
+        let containers_log_dir = PathBuf::from(
+            env::var("CONTAINERS_LOG_DIR").unwrap_or_else(|_| "/var/log/containers".to_string()),
+        );
+        let pods_log_dir =
+            PathBuf::from(env::var("PODS_LOG_DIR").unwrap_or_else(|_| "/var/log/pods".to_string()));
+
+        let state_file = env::var("STATE_FILE")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(PathBuf::from);
+
+        let state_save_interval_secs = env::var("STATE_SAVE_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(60)
+            .max(1);
+
+        let fast_sample_interval_ms = env::var("FAST_SAMPLE_INTERVAL_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|&ms| ms > 0)
+            .map(|ms| ms.max(50)); // rozumné minimum, ať nepálíme CPU
+
+        let metrics_cache_seconds = env::var("METRICS_CACHE_SECONDS")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .filter(|&s| s > 0);
+
+        let memory_budget_bytes = env::var("SELF_MEMORY_BUDGET_BYTES")
+            .ok()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .filter(|&b| b > 0);
+
+        let memory_reclaim_probe = match env::var("MEMORY_RECLAIM_PROBE_BYTES") {
+            Ok(v) if !v.trim().is_empty() => {
+                let bytes = v
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|&b| b > 0)
+                    .context("MEMORY_RECLAIM_PROBE_BYTES must be a positive integer")?;
+                let secs = env::var("MEMORY_RECLAIM_PROBE_INTERVAL_SECS")
+                    .ok()
+                    .and_then(|s| s.trim().parse::<u64>().ok())
+                    .unwrap_or(600);
+                if secs < MIN_RECLAIM_PROBE_INTERVAL_SECS {
+                    warn!(
+                        requested = secs,
+                        used = MIN_RECLAIM_PROBE_INTERVAL_SECS,
+                        "MEMORY_RECLAIM_PROBE_INTERVAL_SECS too low, clamping"
+                    );
+                }
+                Some(ReclaimProbe {
+                    bytes,
+                    interval: Duration::from_secs(secs.max(MIN_RECLAIM_PROBE_INTERVAL_SECS)),
+                })
+            }
+            _ => None,
+        };
+
+        let cert_paths = env_paths("CERT_PATHS");
+        let watch_files = env_paths("WATCH_FILES");
+        let fs_paths = env_paths("FS_PATHS");
+        let tc_interfaces = env_list("TC_INTERFACES");
+        let block_devices = env_list("BLOCK_DEVICES");
+        let fs_target_mount_ns = env_bool("FS_TARGET_MOUNT_NS");
+
+        let mut probe_http = Vec::new();
+        let mut peer_cidrs = Vec::new();
+        for item in env_list("PEER_CIDRS") {
+            peer_cidrs.push(
+                parse_peer_cidr(&item)
+                    .with_context(|| format!("PEER_CIDRS parse error at '{item}'"))?,
+            );
+        }
+
+        for url in env_list("PROBE_HTTP") {
+            probe_http.push(
+                parse_http_probe(&url)
+                    .with_context(|| format!("PROBE_HTTP parse error at '{url}'"))?,
+            );
+        }
+        let probe_tcp = env_list("PROBE_TCP");
+        let probe_dns = if env_bool("PROBE_DNS") {
+            // přes search domény z resolv.conf, stejně jako aplikace v podu
+            vec!["kubernetes.default".to_string()]
+        } else {
+            env_list("PROBE_DNS")
+        };
+
+        let mut target_groups: Vec<TargetGroup> = Vec::new();
+        for part in env::var("TARGET_GROUPS").unwrap_or_default().split(';') {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let (name, re) = part.split_once('=').with_context(|| {
+                format!("TARGET_GROUPS parse error at '{part}' (expected name=regex)")
+            })?;
+            let name = name.trim().to_string();
+            if target_groups.iter().any(|g| g.name == name) {
+                anyhow::bail!("TARGET_GROUPS: duplicate group name '{name}'");
+            }
+            let regex = Regex::new(re.trim())
+                .with_context(|| format!("TARGET_GROUPS invalid regex for group '{name}'"))?;
+            target_groups.push(TargetGroup { name, regex });
+        }
+
+        let mut exec_commands = Vec::new();
+        for part in env::var("EXEC_COMMANDS").unwrap_or_default().split(';') {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let (name, command) = part.split_once('=').with_context(|| {
+                format!("EXEC_COMMANDS parse error at '{part}' (expected name=command)")
+            })?;
+            exec_commands.push(ExecCommand {
+                name: name.trim().to_string(),
+                command: command.trim().to_string(),
+            });
+        }
+        let exec_interval = Duration::from_secs(
+            env::var("EXEC_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(60)
+                .max(1),
+        );
+        let exec_timeout = Duration::from_secs(
+            env::var("EXEC_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(10)
+                .max(1),
+        );
+        let probe_timeout = Duration::from_millis(
+            env::var("PROBE_TIMEOUT_MS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(2000)
+                .max(1),
+        );
+
+        let mut thresholds = Vec::new();
+        for part in env::var("THRESHOLDS").unwrap_or_default().split(';') {
+            let rule = part.trim();
+            if rule.is_empty() {
+                continue;
+            }
+            thresholds.push(
+                parse_threshold(rule)
+                    .with_context(|| format!("THRESHOLDS parse error at '{rule}'"))?,
+            );
+        }
+
+        let availability_window = match env::var("AVAILABILITY_WINDOW") {
+            Ok(s) if !s.trim().is_empty() => parse_duration(s.trim())
+                .with_context(|| format!("AVAILABILITY_WINDOW parse error at '{s}'"))?,
+            _ => Duration::from_secs(3600),
+        };
+
+        let mut peak_windows = Vec::new();
+        for part in env::var("PEAK_WINDOWS").unwrap_or_default().split(',') {
+            let label = part.trim();
+            if label.is_empty() {
+                continue;
+            }
+            let window = parse_duration(label)
+                .with_context(|| format!("PEAK_WINDOWS parse error at '{label}'"))?;
+            peak_windows.push((label.to_string(), window));
+        }
+
         Ok(Self {
             listen_addr,
-            cgroup_root: PathBuf::from(cgroup_root),
+            http,
+            cgroup_root,
+            cgroup_roots,
+            proc_root,
+            sys_root,
             downward_dir,
             process_target,
+            process_per_pid,
+            follow_children,
+            target_groups,
+            process_thread_cpu,
+            process_smaps,
+            process_stalls,
+            taskstats,
             metrics_prefix,
             static_labels,
             cpu_requests_mcpu,
@@ -174,8 +791,489 @@ impl Config {
             update_interval_secs,
             net_interface,
             node_name,
+            derived_rates,
+            peak_windows,
+            availability_window,
+            state_file,
+            state_save_interval_secs,
+            thresholds,
+            fast_sample_interval_ms,
+            memory_budget_bytes,
+            memory_reclaim_probe,
+            metrics_cache_seconds,
+            cert_paths,
+            watch_files,
+            fs_paths,
+            tc_interfaces,
+            block_devices,
+            peer_cidrs,
+            fs_target_mount_ns,
+            probe_http,
+            probe_tcp,
+            probe_dns,
+            probe_timeout,
+            exec_commands,
+            exec_interval,
+            exec_timeout,
+            pod_cgroup_mode,
+            pod_cgroup_discover,
+            cgroup_recursive,
+            cgroup_recursive_max_depth,
+            cgroup_glob,
+            oom_watch,
+            psi_triggers,
+            cgroup_follow_target,
+            cgroup_systemd_unit,
+            cgroup_container_id,
+            cgroup_ns_detect,
+            node_mode,
+            pods_log_dir,
+            sibling_containers,
+            containers_log_dir,
         })
     }
+
+    /// Výchozí konfigurace nad `fixtures/` pro testy - nezávislá na prostředí,
+    /// ve kterém testy běží.
+    #[cfg(test)]
+    pub fn for_fixtures() -> Config {
+        let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let cgroup_root = fixtures.join("sys/fs/cgroup");
+        Config {
+            listen_addr: "127.0.0.1:9100".parse().unwrap(),
+            http: HttpConfig {
+                keep_alive: true,
+                idle_timeout: None,
+                max_requests_per_connection: None,
+                pipeline_flush: false,
+            },
+            cgroup_roots: vec![CgroupRoot {
+                name: "default".to_string(),
+                path: cgroup_root.clone(),
+            }],
+            cgroup_root,
+            proc_root: fixtures.join("proc"),
+            sys_root: fixtures.join("sys"),
+            downward_dir: None,
+            process_target: None,
+            process_per_pid: false,
+            follow_children: false,
+            target_groups: Vec::new(),
+            process_thread_cpu: false,
+            process_smaps: false,
+            process_stalls: false,
+            taskstats: false,
+            metrics_prefix: None,
+            static_labels: HashMap::new(),
+            cpu_requests_mcpu: None,
+            cpu_limits_mcpu: None,
+            memory_requests_bytes: None,
+            memory_limits_bytes: None,
+            update_interval_secs: 5,
+            net_interface: "eth0".to_string(),
+            node_name: None,
+            derived_rates: false,
+            peak_windows: Vec::new(),
+            availability_window: Duration::from_secs(3600),
+            state_file: None,
+            state_save_interval_secs: 60,
+            thresholds: Vec::new(),
+            fast_sample_interval_ms: None,
+            memory_budget_bytes: None,
+            memory_reclaim_probe: None,
+            metrics_cache_seconds: None,
+            cert_paths: Vec::new(),
+            watch_files: Vec::new(),
+            fs_paths: Vec::new(),
+            tc_interfaces: Vec::new(),
+            block_devices: Vec::new(),
+            peer_cidrs: Vec::new(),
+            fs_target_mount_ns: false,
+            probe_http: Vec::new(),
+            probe_tcp: Vec::new(),
+            probe_dns: Vec::new(),
+            probe_timeout: Duration::from_millis(2000),
+            exec_commands: Vec::new(),
+            exec_interval: Duration::from_secs(60),
+            exec_timeout: Duration::from_secs(10),
+            pod_cgroup_mode: false,
+            pod_cgroup_discover: false,
+            cgroup_recursive: None,
+            cgroup_recursive_max_depth: 3,
+            cgroup_glob: None,
+            oom_watch: false,
+            psi_triggers: Vec::new(),
+            cgroup_follow_target: false,
+            cgroup_systemd_unit: None,
+            cgroup_container_id: None,
+            cgroup_ns_detect: false,
+            node_mode: false,
+            pods_log_dir: fixtures.join("pods-log"),
+            sibling_containers: false,
+            containers_log_dir: fixtures.join("containers-log"),
+        }
+    }
+}
+
+/// Pojmenovaná skupina procesů z TARGET_GROUPS (`name=regex`), regex se
+/// zkouší na cmdline a comm stejně jako TARGET_PID_REGEXP.
+#[derive(Debug, Clone)]
+pub struct TargetGroup {
+    pub name: String,
+    pub regex: Regex,
+}
+
+/// Příkaz exec collectoru z EXEC_COMMANDS (`name=command`).
+#[derive(Debug, Clone)]
+pub struct ExecCommand {
+    pub name: String,
+    pub command: String,
+}
+
+/// Jeden PSI trigger z PSI_TRIGGERS (`memory:some:150:1000` = aspoň 150 ms
+/// stall v okně 1000 ms).
+#[derive(Debug, Clone)]
+pub struct PsiTrigger {
+    /// cpu | memory | io
+    pub resource: String,
+    /// some | full
+    pub kind: String,
+    pub stall_ms: u64,
+    pub window_ms: u64,
+}
+
+impl PsiTrigger {
+    /// Řádek pro zápis do *.pressure (mikrosekundy).
+    pub fn spec(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.kind,
+            self.stall_ms * 1000,
+            self.window_ms * 1000
+        )
+    }
+
+    /// Hodnota labelu threshold, např. `150ms/1000ms`.
+    pub fn threshold_label(&self) -> String {
+        format!("{}ms/{}ms", self.stall_ms, self.window_ms)
+    }
+}
+
+fn parse_psi_trigger(item: &str) -> Result<PsiTrigger> {
+    let parts: Vec<&str> = item.split(':').map(str::trim).collect();
+    let [resource, kind, stall, window] = parts[..] else {
+        anyhow::bail!("expected resource:kind:stall_ms:window_ms");
+    };
+    if !matches!(resource, "cpu" | "memory" | "io") {
+        anyhow::bail!("resource must be cpu, memory or io");
+    }
+    if !matches!(kind, "some" | "full") {
+        anyhow::bail!("kind must be some or full");
+    }
+    let stall_ms: u64 = stall.parse().context("stall_ms")?;
+    let window_ms: u64 = window.parse().context("window_ms")?;
+    // limity kernelu: okno 500 ms - 10 s, práh menší než okno
+    if !(500..=10_000).contains(&window_ms) {
+        anyhow::bail!("window_ms must be between 500 and 10000");
+    }
+    if stall_ms == 0 || stall_ms >= window_ms {
+        anyhow::bail!("stall_ms must be positive and below window_ms");
+    }
+    Ok(PsiTrigger {
+        resource: resource.to_string(),
+        kind: kind.to_string(),
+        stall_ms,
+        window_ms,
+    })
+}
+
+/// Reclaim sonda nesmí běžet častěji - zápis do memory.reclaim paměť
+/// skutečně uvolňuje.
+const MIN_RECLAIM_PROBE_INTERVAL_SECS: u64 = 60;
+
+/// Kolik zkusit uvolnit a jak často (MEMORY_RECLAIM_PROBE_*).
+#[derive(Debug, Clone)]
+pub struct ReclaimProbe {
+    pub bytes: u64,
+    pub interval: Duration,
+}
+
+/// Jedna sledovaná cgroup root z CGROUP_ROOTS.
+#[derive(Debug, Clone)]
+pub struct CgroupRoot {
+    /// Hodnota labelu root
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// `name=path`, nebo jen `path` - jméno je pak poslední komponenta cesty.
+fn parse_cgroup_root(item: &str) -> Result<CgroupRoot> {
+    let (name, path) = match item.split_once('=') {
+        Some((name, path)) => (name.trim().to_string(), PathBuf::from(path.trim())),
+        None => {
+            let path = PathBuf::from(item.trim());
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .context("cannot derive root name from path")?;
+            (name, path)
+        }
+    };
+    if name.is_empty() {
+        anyhow::bail!("missing name");
+    }
+    Ok(CgroupRoot { name, path })
+}
+
+/// CGROUP_GLOB přeložený na regex nad cestou relativní ke CGROUP_ROOT.
+#[derive(Debug, Clone)]
+pub struct CgroupGlob {
+    pub pattern: String,
+    pub regex: Regex,
+    /// Počet segmentů vzoru = hloubka, do které má smysl procházet
+    pub depth: usize,
+}
+
+/// `*` a `?` platí jen v rámci jednoho segmentu cesty, zbytek je literál.
+fn parse_cgroup_glob(pattern: &str) -> Result<CgroupGlob> {
+    let pattern = pattern.trim().trim_matches('/');
+    if pattern.is_empty() {
+        anyhow::bail!("empty pattern");
+    }
+    let mut re = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    Ok(CgroupGlob {
+        pattern: pattern.to_string(),
+        regex: Regex::new(&re).context("glob to regex")?,
+        depth: pattern.split('/').count(),
+    })
+}
+
+/// Jeden CIDR logické upstream služby z PEER_CIDRS (`db=10.1.0.0/16`).
+/// Stejné jméno může mít víc CIDRů.
+#[derive(Debug, Clone)]
+pub struct PeerCidr {
+    pub name: String,
+    pub network: IpAddr,
+    pub prefix_len: u8,
+}
+
+impl PeerCidr {
+    /// Patří adresa do CIDRu? IPv4 a IPv6 se nikdy neshodují.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (self.network, addr) {
+            (IpAddr::V4(net), IpAddr::V4(a)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(*a) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(a)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix_len))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(*a) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn parse_peer_cidr(item: &str) -> Result<PeerCidr> {
+    let (name, cidr) = item.split_once('=').context("expected name=cidr")?;
+    let name = name.trim();
+    if name.is_empty() {
+        anyhow::bail!("missing name");
+    }
+    let (network, prefix_len) = match cidr.trim().split_once('/') {
+        Some((ip, len)) => (ip, Some(len)),
+        None => (cidr.trim(), None),
+    };
+    let network: IpAddr = network.parse().context("invalid IP address")?;
+    let max_len = if network.is_ipv4() { 32 } else { 128 };
+    let prefix_len = match prefix_len {
+        Some(len) => len.parse::<u8>().context("invalid prefix length")?,
+        None => max_len,
+    };
+    if prefix_len > max_len {
+        anyhow::bail!("prefix length {prefix_len} out of range");
+    }
+
+    Ok(PeerCidr {
+        name: name.to_string(),
+        network,
+        prefix_len,
+    })
+}
+
+/// Jedna HTTP probe z PROBE_HTTP (jen http://).
+#[derive(Debug, Clone)]
+pub struct HttpProbe {
+    /// Původní URL - label target
+    pub url: String,
+    /// host:port pro connect
+    pub addr: String,
+    /// Hodnota Host hlavičky
+    pub host: String,
+    pub path: String,
+}
+
+fn parse_http_probe(url: &str) -> Result<HttpProbe> {
+    let rest = url
+        .strip_prefix("http://")
+        .context("only http:// URLs are supported")?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        anyhow::bail!("missing host");
+    }
+    // IPv6 literál [::1]:8080 má ':' i bez portu
+    let has_port = authority
+        .rsplit_once(':')
+        .is_some_and(|(_, p)| p.chars().all(|c| c.is_ascii_digit()) && !p.is_empty());
+    let addr = if has_port {
+        authority.to_string()
+    } else {
+        format!("{authority}:80")
+    };
+
+    Ok(HttpProbe {
+        url: url.to_string(),
+        addr,
+        host: authority.to_string(),
+        path: path.to_string(),
+    })
+}
+
+/// `name:metric{l="v",...}<op><value>[:for]`
+fn parse_threshold(rule: &str) -> Result<Threshold> {
+    let (name, rest) = rule
+        .split_once(':')
+        .context("expected 'name:metric<op>value[:for]'")?;
+    let name = name.trim();
+    if name.is_empty() {
+        anyhow::bail!("threshold name is empty");
+    }
+
+    // operátor hledáme až za selektorem labelů (hodnoty labelů mohou obsahovat cokoliv)
+    let search_from = rest.find('}').map(|i| i + 1).unwrap_or(0);
+    let (op_pos, op, op_len) = ["!=", ">=", "<=", "==", ">", "<"]
+        .iter()
+        .filter_map(|&op| rest[search_from..].find(op).map(|i| (search_from + i, op)))
+        .min_by_key(|&(i, op)| (i, std::cmp::Reverse(op.len())))
+        .map(|(i, op)| {
+            let parsed = match op {
+                "!=" => ThresholdOp::Ne,
+                ">=" => ThresholdOp::Ge,
+                "<=" => ThresholdOp::Le,
+                "==" => ThresholdOp::Eq,
+                ">" => ThresholdOp::Gt,
+                _ => ThresholdOp::Lt,
+            };
+            (i, parsed, op.len())
+        })
+        .context("missing comparison operator (>, >=, <, <=, ==, !=)")?;
+
+    let selector = rest[..op_pos].trim();
+    let (value_str, for_duration) = match rest[op_pos + op_len..].split_once(':') {
+        Some((v, d)) => (v, parse_duration(d)?),
+        None => (&rest[op_pos + op_len..], Duration::ZERO),
+    };
+    let value: f64 = value_str
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid threshold value '{}'", value_str.trim()))?;
+
+    let (metric, labels) = match selector.split_once('{') {
+        Some((metric, labels)) => {
+            let labels = labels
+                .strip_suffix('}')
+                .context("unterminated label selector")?;
+            let mut parsed = Vec::new();
+            for pair in labels.split(',') {
+                let pair = pair.trim();
+                if pair.is_empty() {
+                    continue;
+                }
+                let (k, v) = pair
+                    .split_once('=')
+                    .with_context(|| format!("invalid label matcher '{pair}'"))?;
+                parsed.push((k.trim().to_string(), v.trim().trim_matches('"').to_string()));
+            }
+            (metric.trim(), parsed)
+        }
+        None => (selector, Vec::new()),
+    };
+    if metric.is_empty() {
+        anyhow::bail!("metric name is empty");
+    }
+
+    Ok(Threshold {
+        name: name.to_string(),
+        metric: metric.to_string(),
+        labels,
+        op,
+        value,
+        for_duration,
+    })
+}
+
+/// Délka ve formátu "90", "30s", "5m", "1h", "1d" (bez jednotky = sekundy).
+fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let (num, mult) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 3_600),
+        Some((i, 'd')) => (&s[..i], 86_400),
+        _ => (s, 1),
+    };
+    let n: u64 = num
+        .parse()
+        .with_context(|| format!("invalid duration '{s}'"))?;
+    if n == 0 {
+        anyhow::bail!("duration '{s}' must be positive");
+    }
+    let secs = n
+        .checked_mul(mult)
+        .with_context(|| format!("duration '{s}' is too large"))?;
+    Ok(Duration::from_secs(secs))
+}
+
+/// "1", "true", "yes", "on" (case-insensitive) → true, cokoliv jiného → false.
+fn env_bool(name: &str) -> bool {
+    env::var(name)
+        .map(|v| {
+            matches!(
+                v.trim().to_ascii_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// Hodnoty oddělené čárkou; prázdné položky se ignorují.
+fn env_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn env_paths(name: &str) -> Vec<PathBuf> {
+    env_list(name).into_iter().map(PathBuf::from).collect()
 }
 
 fn parse_static_labels(s: &str) -> HashMap<String, String> {
@@ -216,3 +1314,107 @@ fn normalize_prefix(raw: String) -> Option<String> {
 
     Some(trimmed.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_root_with_and_without_name() {
+        let root = parse_cgroup_root("app=/sys/fs/cgroup/app.slice").unwrap();
+        assert_eq!(root.name, "app");
+        assert_eq!(root.path, PathBuf::from("/sys/fs/cgroup/app.slice"));
+
+        let root = parse_cgroup_root("/sys/fs/cgroup/jobs.slice").unwrap();
+        assert_eq!(root.name, "jobs.slice");
+
+        assert!(parse_cgroup_root("=/sys/fs/cgroup").is_err());
+        assert!(parse_cgroup_root("/").is_err());
+    }
+
+    #[test]
+    fn psi_trigger_within_kernel_limits() {
+        let t = parse_psi_trigger("memory:some:150:1000").unwrap();
+        assert_eq!((t.resource.as_str(), t.kind.as_str()), ("memory", "some"));
+        assert_eq!(t.spec(), "some 150000 1000000");
+        assert_eq!(t.threshold_label(), "150ms/1000ms");
+
+        assert!(parse_psi_trigger("net:some:150:1000").is_err());
+        assert!(parse_psi_trigger("cpu:all:150:1000").is_err());
+        assert!(parse_psi_trigger("cpu:some:150:100").is_err());
+        assert!(parse_psi_trigger("cpu:some:1000:1000").is_err());
+        assert!(parse_psi_trigger("cpu:some:150").is_err());
+    }
+
+    #[test]
+    fn threshold_with_selector_and_for() {
+        let t = parse_threshold(r#"hot:cgroup_cpu_usage_cores{name="a>b"}>=1.5:5m"#).unwrap();
+        assert_eq!(t.name, "hot");
+        assert_eq!(t.metric, "cgroup_cpu_usage_cores");
+        assert_eq!(t.labels, vec![("name".to_string(), "a>b".to_string())]);
+        assert_eq!(t.op, ThresholdOp::Ge);
+        assert_eq!(t.value, 1.5);
+        assert_eq!(t.for_duration, Duration::from_secs(300));
+
+        let t = parse_threshold("oom:cgroup_oom_kills!=0").unwrap();
+        assert_eq!(t.op, ThresholdOp::Ne);
+        assert!(t.labels.is_empty());
+        assert_eq!(t.for_duration, Duration::ZERO);
+
+        assert!(parse_threshold("nameonly").is_err());
+        assert!(parse_threshold("x:metric").is_err());
+        assert!(parse_threshold("x:metric>abc").is_err());
+        assert!(parse_threshold("x:>1").is_err());
+    }
+
+    #[test]
+    fn duration_units() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3_600));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86_400));
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("999999999999999999d").is_err());
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn peer_cidr_prefix_and_contains() {
+        let db = parse_peer_cidr("db=10.1.0.0/16").unwrap();
+        assert_eq!(db.name, "db");
+        assert_eq!(db.prefix_len, 16);
+        assert!(db.contains(&"10.1.200.3".parse().unwrap()));
+        assert!(!db.contains(&"10.2.0.1".parse().unwrap()));
+        assert!(!db.contains(&"::ffff:10.1.0.1".parse().unwrap()));
+
+        let host = parse_peer_cidr("cache=fd00::1").unwrap();
+        assert_eq!(host.prefix_len, 128);
+
+        let any = parse_peer_cidr("all=0.0.0.0/0").unwrap();
+        assert!(any.contains(&"192.168.1.1".parse().unwrap()));
+
+        assert!(parse_peer_cidr("10.0.0.0/8").is_err());
+        assert!(parse_peer_cidr("db=10.0.0.0/33").is_err());
+        assert!(parse_peer_cidr("db=not-an-ip").is_err());
+    }
+
+    #[test]
+    fn http_probe_default_port_and_path() {
+        let p = parse_http_probe("http://app:8080/healthz").unwrap();
+        assert_eq!(p.addr, "app:8080");
+        assert_eq!(p.host, "app:8080");
+        assert_eq!(p.path, "/healthz");
+
+        let p = parse_http_probe("http://app").unwrap();
+        assert_eq!(p.addr, "app:80");
+        assert_eq!(p.path, "/");
+
+        let p = parse_http_probe("http://[::1]/ready").unwrap();
+        assert_eq!(p.addr, "[::1]:80");
+
+        assert!(parse_http_probe("https://app/").is_err());
+        assert!(parse_http_probe("http:///x").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Cgroupa kontejneru podle jeho ID (CGROUP_CONTAINER_ID), bez znalosti
+//! node-specifické cesty. Pojmenování podle runtime a cgroup driveru:
+//!
+//! - docker:     `/docker/<id>` (cgroupfs), `docker-<id>.scope` (systemd)
+//! - containerd: `/kubepods/.../<id>` (cgroupfs), `cri-containerd-<id>.scope` (systemd)
+//! - cri-o:      `crio-<id>` / `crio-<id>.scope` (`crio-conmon-<id>` je monitor, ne kontejner)
+//! - podman:     `libpod-<id>.scope`
+//!
+//! Stačí prefix ID (zkrácené ID z `docker ps` / `crictl ps`).
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use once_cell::sync::Lazy;
+use tracing::debug;
+use walkdir::WalkDir;
+
+use crate::kubepods;
+
+/// kubepods/QoS/pod/kontejner + pár úrovní navíc pro vnořené hierarchie
+const MAX_DEPTH: usize = 8;
+
+/// Naposledy nalezená cesta - dokud existuje, strom znovu neprocházíme
+/// (na nodu s desítkami podů to jsou tisíce adresářů každý cyklus).
+static LAST: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+/// (runtime, driver) podle jména adresáře a jeho rodiče.
+fn detect(name: &str, parent: &str) -> (&'static str, &'static str) {
+    let runtime = [
+        ("docker-", "docker"),
+        ("cri-containerd-", "containerd"),
+        ("crio-", "cri-o"),
+        ("libpod-", "podman"),
+    ]
+    .iter()
+    .find(|(prefix, _)| name.starts_with(prefix))
+    .map(|(_, runtime)| *runtime);
+
+    match runtime {
+        Some(runtime) if name.ends_with(".scope") => (runtime, "systemd"),
+        Some(runtime) => (runtime, "cgroupfs"),
+        None if parent == "docker" => ("docker", "cgroupfs"),
+        None => ("containerd", "cgroupfs"),
+    }
+}
+
+/// Adresář cgroupy kontejneru pod `cgroup_mount`; None, když není vidět.
+pub fn resolve(cgroup_mount: &Path, id: &str) -> Option<PathBuf> {
+    let unified = cgroup_mount.join("unified");
+    let mount = if unified.join("cgroup.controllers").exists() {
+        unified
+    } else {
+        cgroup_mount.to_path_buf()
+    };
+    let id = id.trim().to_ascii_lowercase();
+
+    let mut last = LAST.lock().expect("container_cgroup LAST mutex poisoned");
+    if let Some(ref dir) = *last
+        && dir.is_dir()
+    {
+        return Some(dir.clone());
+    }
+
+    let entry = WalkDir::new(&mount)
+        .min_depth(1)
+        .max_depth(MAX_DEPTH)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .find(|e| {
+            let name = e.file_name().to_string_lossy();
+            !name.starts_with("crio-conmon-")
+                && kubepods::container_id_from_segment(&name).is_some_and(|c| c.starts_with(&id))
+        })?;
+
+    let name = entry.file_name().to_string_lossy();
+    let parent = entry
+        .path()
+        .parent()
+        .and_then(Path::file_name)
+        .map(|p| p.to_string_lossy())
+        .unwrap_or_default();
+    let (runtime, driver) = detect(&name, &parent);
+    debug!(path = %entry.path().display(), runtime, driver, "located container cgroup");
+    *last = Some(entry.into_path());
+    last.clone()
+}
@@ -0,0 +1,106 @@
+//! Multi-container režim (POD_CGROUP_MODE): CGROUP_ROOT ukazuje na cgroupu
+//! celého podu a per-kontejnerové metriky se sbírají z jejích potomků.
+//!
+//! Label `container` je zkrácené ID z runtime (prvních 12 znaků, jako
+//! `crictl ps`) - jména kontejnerů v cgroup hierarchii nejsou.
+//!
+//! Restart kontejneru = nové ID = nová série, takže countery offsety
+//! (viz monotonic.rs) nepotřebují.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    kubepods,
+    metrics::{ContainerMetrics, LiveSeries},
+};
+
+fn read_u64(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// memory.max: "max" = +Inf
+fn read_limit(path: &Path) -> Option<f64> {
+    let s = std::fs::read_to_string(path).ok()?;
+    match s.trim() {
+        "max" => Some(f64::INFINITY),
+        v => v.parse::<u64>().ok().map(|v| v as f64),
+    }
+}
+
+/// Cgroupa podu, ve kterém exporter běží jako sidecar (POD_CGROUP_DISCOVER):
+/// rodič vlastní cgroupy z /proc/self/cgroup pod `cgroup_mount`. S privátním
+/// cgroup namespace je vlastní cgroupa `/` a sourozenci nejsou vidět.
+pub fn own_pod_root(cgroup_mount: &Path) -> Result<PathBuf> {
+    let path = kubepods::cgroup_path_of(Path::new("/proc"), "self")
+        .context("own cgroup is not under kubepods (private cgroup namespace?)")?;
+    let parsed = kubepods::parse_cgroup_path(&path).context("parse own cgroup path")?;
+    parsed
+        .container_id
+        .context("own cgroup is not a container cgroup")?;
+    let own = cgroup_mount.join(path.trim_start_matches('/'));
+    let pod = own.parent().context("own cgroup has no parent")?;
+    if !pod.is_dir() {
+        anyhow::bail!("pod cgroup {} not visible", pod.display());
+    }
+    Ok(pod.to_path_buf())
+}
+
+pub fn update(metrics: &ContainerMetrics, pod_root: &Path) -> Result<()> {
+    let mut live = LiveSeries::default();
+    for entry in std::fs::read_dir(pod_root).context("read pod cgroup directory")? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let dir_name = entry.file_name().to_string_lossy().to_string();
+        let Some(id) = kubepods::container_id_from_segment(&dir_name) else {
+            continue;
+        };
+        let short: String = id.chars().take(12).collect();
+        let labels = [short.as_str()];
+        live.insert(&labels);
+        let dir = entry.path();
+
+        if let Ok(cpu_stat) = std::fs::read_to_string(dir.join("cpu.stat")) {
+            for line in cpu_stat.lines() {
+                let mut parts = line.split_whitespace();
+                let (Some(key), Some(val)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                let Ok(val) = val.parse::<u64>() else {
+                    continue;
+                };
+                let gauge = match key {
+                    "usage_usec" => &metrics.cpu_usage_seconds,
+                    "throttled_usec" => &metrics.cpu_throttled_seconds,
+                    _ => continue,
+                };
+                gauge
+                    .with_label_values(&labels)
+                    .set(val as f64 / 1_000_000.0);
+            }
+        }
+
+        if let Some(v) = read_u64(&dir.join("memory.current")) {
+            metrics
+                .mem_current_bytes
+                .with_label_values(&labels)
+                .set(v as f64);
+        }
+        if let Some(v) = read_limit(&dir.join("memory.max")) {
+            metrics.mem_max_bytes.with_label_values(&labels).set(v);
+        }
+    }
+
+    for vec in [
+        &metrics.cpu_usage_seconds,
+        &metrics.cpu_throttled_seconds,
+        &metrics.mem_current_bytes,
+        &metrics.mem_max_bytes,
+    ] {
+        live.prune(vec);
+    }
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! Rozdíly hodnot mezi po sobě jdoucími updaty - základ odvozených metrik
+//! (ratio, rate). Klíčem je libovolný string, typicky "<cesta>:<položka>".
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Delta {
+    /// Změna hodnoty od minulého vzorku.
+    pub value: f64,
+    /// Čas uplynulý od minulého vzorku.
+    pub elapsed: Duration,
+}
+
+impl Delta {
+    /// Změna za sekundu; None pro nulový interval.
+    pub fn per_second(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        (secs > 0.0).then(|| self.value / secs)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DeltaTracker {
+    prev: HashMap<String, (Instant, f64)>,
+}
+
+impl DeltaTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uloží nový vzorek a vrátí rozdíl proti předchozímu.
+    ///
+    /// None při prvním vzorku a při resetu zdroje (hodnota klesla) -
+    /// v obou případech nemáme s čím rozumně porovnat.
+    pub fn observe(&mut self, key: &str, value: f64) -> Option<Delta> {
+        let now = Instant::now();
+        let (at, old) = self.prev.insert(key.to_string(), (now, value))?;
+        if value < old {
+            return None;
+        }
+        Some(Delta {
+            value: value - old,
+            elapsed: now.duration_since(at),
+        })
+    }
+}
@@ -0,0 +1,439 @@
+//! Exec collector (EXEC_COMMANDS): pouští nakonfigurované příkazy přes
+//! `sh -c`, jejich stdout parsuje jako Prometheus text exposition a výsledek
+//! přimíchá do registry jako vlastní Collector.
+//!
+//! Parser je záměrně minimální: `# HELP`, `# TYPE` a řádky
+//! `name{label="value",...} value [timestamp]`. Typ counter se převezme,
+//! všechno ostatní (untyped, histogram/summary sample) se exportuje jako gauge.
+//!
+//! Collector je "unchecked", registry za nás konzistenci nehlídá - neplatné
+//! jméno příkaz shodí, duplicitní série, nekonzistentní labely a kolize
+//! s vestavěnými metrikami nebo výstupem jiného příkazu se zahodí s warningem.
+//! Každá série dostane label `exec_command` se jménem příkazu.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Read,
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use prometheus::{
+    GaugeVec, Opts, Registry,
+    core::{Collector, Desc},
+    proto::{self, LabelPair, MetricFamily, MetricType},
+};
+use tracing::warn;
+
+use crate::{config::ExecCommand, metrics::ExecMetrics};
+
+/// Label se jménem příkazu na každé sérii
+const COMMAND_LABEL: &str = "exec_command";
+
+/// Poslední úspěšný výstup každého příkazu (klíč = jméno příkazu).
+#[derive(Clone)]
+pub struct ExecCollector {
+    families: Arc<Mutex<BTreeMap<String, Vec<MetricFamily>>>>,
+    /// Registry s vestavěnými metrikami - kvůli kontrole kolizí jmen
+    registry: Registry,
+}
+
+impl ExecCollector {
+    pub fn new(registry: &Registry) -> Self {
+        Self {
+            families: Arc::default(),
+            registry: registry.clone(),
+        }
+    }
+
+    /// Jméno už patří vestavěné metrice? Registry odmítne desc se stejným
+    /// jménem; prázdný vec se při gather() nevypíše, scrape ho neuvidí.
+    fn is_builtin(&self, name: &str) -> bool {
+        let Ok(probe) = GaugeVec::new(Opts::new(name, "exec name probe"), &[COMMAND_LABEL]) else {
+            return true;
+        };
+        if self.registry.register(Box::new(probe.clone())).is_err() {
+            return true;
+        }
+        let _ = self.registry.unregister(Box::new(probe));
+        false
+    }
+
+    /// Zahodí rodiny, které kolidují s vestavěnou metrikou nebo se stejně
+    /// pojmenovanou rodinou jiného příkazu s jiným typem či labely.
+    fn drop_conflicts(&self, command: &str, families: Vec<MetricFamily>) -> Vec<MetricFamily> {
+        // registry zvlášť a bez držení families - gather() drží registry a volá collect()
+        let families: Vec<MetricFamily> = families
+            .into_iter()
+            .filter(|mf| {
+                let builtin = self.is_builtin(mf.name());
+                if builtin {
+                    warn!(
+                        command,
+                        metric = mf.name(),
+                        "exec metric collides with a built-in metric, dropped"
+                    );
+                }
+                !builtin
+            })
+            .collect();
+
+        let stored = self.families.lock().expect("exec families mutex poisoned");
+        families
+            .into_iter()
+            .filter(|mf| {
+                let clash = stored
+                    .iter()
+                    .filter(|(other, _)| other.as_str() != command)
+                    .flat_map(|(_, mfs)| mfs)
+                    .find(|other| other.name() == mf.name())
+                    .is_some_and(|other| {
+                        other.get_field_type() != mf.get_field_type()
+                            || label_names(other) != label_names(mf)
+                    });
+                if clash {
+                    warn!(
+                        command,
+                        metric = mf.name(),
+                        "exec metric conflicts with another command's output, dropped"
+                    );
+                }
+                !clash
+            })
+            .collect()
+    }
+
+    /// Zahodí všechny uložené výstupy příkazů.
+    pub fn clear(&self) {
+        self.families
+            .lock()
+            .expect("exec families mutex poisoned")
+            .clear();
+    }
+}
+
+impl Collector for ExecCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        // "unchecked" collector - jména metrik dopředu neznáme
+        Vec::new()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.families
+            .lock()
+            .expect("exec families mutex poisoned")
+            .values()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Spustí příkazy jeden po druhém. Volá se z vlastní smyčky (EXEC_INTERVAL_SECS),
+/// ne z hlavního updatu - N pomalých příkazů trvá až N × EXEC_TIMEOUT_SECS.
+pub fn update(metrics: &ExecMetrics, commands: &[ExecCommand], timeout: Duration) {
+    for cmd in commands {
+        let start = Instant::now();
+        let result = run(&cmd.command, timeout)
+            .and_then(|out| parse_exposition(&cmd.name, &out))
+            .map(|families| metrics.collector.drop_conflicts(&cmd.name, families));
+        metrics
+            .duration_seconds
+            .with_label_values(&[&cmd.name])
+            .set(start.elapsed().as_secs_f64());
+
+        match result {
+            Ok(families) => {
+                metrics.success.with_label_values(&[&cmd.name]).set(1);
+                metrics
+                    .collector
+                    .families
+                    .lock()
+                    .expect("exec families mutex poisoned")
+                    .insert(cmd.name.clone(), families);
+            }
+            Err(e) => {
+                // stará data radši zahodíme, než abychom exportovali neplatné
+                metrics.success.with_label_values(&[&cmd.name]).set(0);
+                metrics
+                    .collector
+                    .families
+                    .lock()
+                    .expect("exec families mutex poisoned")
+                    .remove(&cmd.name);
+                warn!(command = %cmd.name, error = %format!("{e:#}"), "exec command failed");
+            }
+        }
+    }
+}
+
+/// Spustí příkaz a vrátí stdout; po timeoutu ho zabije.
+fn run(command: &str, timeout: Duration) -> Result<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("spawn")?;
+
+    // stdout čteme ve vlákně, jinak by plná pipe zablokovala potomka
+    let mut stdout = child.stdout.take().context("take stdout")?;
+    let reader = std::thread::spawn(move || {
+        let mut out = String::new();
+        stdout.read_to_string(&mut out).map(|_| out)
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait().context("wait")? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("timed out after {}s", timeout.as_secs_f64());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    let out = reader
+        .join()
+        .map_err(|_| anyhow::anyhow!("stdout reader panicked"))?
+        .context("read stdout")?;
+    if !status.success() {
+        anyhow::bail!("exited with {status}");
+    }
+    Ok(out)
+}
+
+/// Jména labelů první série rodiny (všechny série jedné rodiny je mají stejná).
+fn label_names(mf: &MetricFamily) -> Vec<&str> {
+    mf.get_metric()
+        .first()
+        .map(|m| m.get_label().iter().map(|lp| lp.name()).collect())
+        .unwrap_or_default()
+}
+
+/// `[a-zA-Z_:][a-zA-Z0-9_:]*`
+fn is_valid_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+}
+
+/// `[a-zA-Z_][a-zA-Z0-9_]*`, prefix `__` je rezervovaný
+fn is_valid_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    !name.starts_with("__")
+        && chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_exposition(command: &str, text: &str) -> Result<Vec<MetricFamily>> {
+    let mut types: BTreeMap<&str, MetricType> = BTreeMap::new();
+    let mut helps: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut families: BTreeMap<String, MetricFamily> = BTreeMap::new();
+    // už viděné série (jméno + labely) - druhá stejná by rozbila scrape
+    let mut seen: BTreeSet<(String, Labels)> = BTreeSet::new();
+
+    for (lineno, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('#') {
+            let mut parts = rest.trim_start().splitn(3, ' ');
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("TYPE"), Some(name), Some(t)) => {
+                    let t = match t.trim() {
+                        "counter" => MetricType::COUNTER,
+                        _ => MetricType::GAUGE,
+                    };
+                    types.insert(name, t);
+                }
+                (Some("HELP"), Some(name), help) => {
+                    helps.insert(name, help.unwrap_or(""));
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (name, mut labels, value) =
+            parse_sample(line).with_context(|| format!("line {}: '{line}'", lineno + 1))?;
+        labels.push((COMMAND_LABEL.to_string(), command.to_string()));
+        labels.sort();
+
+        if !seen.insert((name.to_string(), labels.clone())) {
+            warn!(
+                command,
+                metric = name,
+                "duplicate exec metric series, dropped"
+            );
+            continue;
+        }
+        if let Some(mf) = families.get(name) {
+            let expected = label_names(mf);
+            if !expected
+                .iter()
+                .copied()
+                .eq(labels.iter().map(|(k, _)| k.as_str()))
+            {
+                warn!(
+                    command,
+                    metric = name,
+                    "exec metric series with inconsistent labels, dropped"
+                );
+                continue;
+            }
+        }
+
+        let t = types.get(name).copied().unwrap_or(MetricType::GAUGE);
+        let mf = families.entry(name.to_string()).or_insert_with(|| {
+            let mut mf = MetricFamily::default();
+            mf.set_name(name.to_string());
+            mf.set_help(
+                helps
+                    .get(name)
+                    .copied()
+                    .unwrap_or("exec collector metric")
+                    .to_string(),
+            );
+            mf.set_field_type(t);
+            mf
+        });
+
+        let mut m = proto::Metric::default();
+        m.set_label(
+            labels
+                .into_iter()
+                .map(|(k, v)| {
+                    let mut lp = LabelPair::default();
+                    lp.set_name(k);
+                    lp.set_value(v);
+                    lp
+                })
+                .collect(),
+        );
+        if t == MetricType::COUNTER {
+            let mut c = proto::Counter::default();
+            c.set_value(value);
+            m.set_counter(c);
+        } else {
+            let mut g = proto::Gauge::default();
+            g.set_value(value);
+            m.set_gauge(g);
+        }
+        mf.mut_metric().push(m);
+    }
+
+    Ok(families.into_values().collect())
+}
+
+type Labels = Vec<(String, String)>;
+
+/// `name{a="b",c="d"} 1.5 [ts]` → (name, labely, hodnota)
+fn parse_sample(line: &str) -> Result<(&str, Labels, f64)> {
+    let name_end = line
+        .find(|c: char| c == '{' || c.is_whitespace())
+        .context("missing value")?;
+    let name = &line[..name_end];
+    if name.is_empty() {
+        anyhow::bail!("missing metric name");
+    }
+    if !is_valid_metric_name(name) {
+        anyhow::bail!("invalid metric name '{name}'");
+    }
+
+    let mut labels = Vec::new();
+    let mut rest = &line[name_end..];
+    if let Some(inner) = rest.strip_prefix('{') {
+        let mut chars = inner.char_indices();
+        let mut key = String::new();
+        let end = loop {
+            let (i, c) = chars.next().context("unterminated label set")?;
+            match c {
+                '}' => break i,
+                ',' | ' ' => {}
+                '=' => {
+                    if chars.next().map(|(_, c)| c) != Some('"') {
+                        anyhow::bail!("expected '\"' after '='");
+                    }
+                    let mut value = String::new();
+                    loop {
+                        let (_, c) = chars.next().context("unterminated label value")?;
+                        match c {
+                            '"' => break,
+                            '\\' => match chars.next().map(|(_, c)| c) {
+                                Some('n') => value.push('\n'),
+                                Some(c) => value.push(c),
+                                None => anyhow::bail!("dangling escape"),
+                            },
+                            c => value.push(c),
+                        }
+                    }
+                    if !is_valid_label_name(&key) || key == COMMAND_LABEL {
+                        anyhow::bail!("invalid label name '{key}'");
+                    }
+                    if labels.iter().any(|(k, _)| *k == key) {
+                        anyhow::bail!("duplicate label '{key}'");
+                    }
+                    labels.push((std::mem::take(&mut key), value));
+                }
+                c => key.push(c),
+            }
+        };
+        rest = &inner[end + 1..];
+    }
+
+    let value = rest.split_whitespace().next().context("missing value")?;
+    let value = match value {
+        "+Inf" => f64::INFINITY,
+        "-Inf" => f64::NEG_INFINITY,
+        v => v.parse::<f64>().context("invalid value")?,
+    };
+    Ok((name, labels, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sample_with_labels_and_escapes() {
+        let (name, labels, value) =
+            parse_sample(r#"queue_depth{queue="a\"b",shard="1"} 42 1700000000"#).unwrap();
+        assert_eq!(name, "queue_depth");
+        assert_eq!(
+            labels,
+            vec![
+                ("queue".to_string(), "a\"b".to_string()),
+                ("shard".to_string(), "1".to_string()),
+            ]
+        );
+        assert_eq!(value, 42.0);
+
+        let (name, labels, value) = parse_sample("up +Inf").unwrap();
+        assert_eq!((name, labels.len(), value), ("up", 0, f64::INFINITY));
+    }
+
+    #[test]
+    fn parse_sample_rejects_invalid_input() {
+        assert!(parse_sample("1bad 1").is_err());
+        assert!(parse_sample("bad-name 1").is_err());
+        assert!(parse_sample(r#"m{__reserved="x"} 1"#).is_err());
+        assert!(parse_sample(r#"m{exec_command="x"} 1"#).is_err());
+        assert!(parse_sample(r#"m{a="1",a="2"} 1"#).is_err());
+        assert!(parse_sample(r#"m{a="1" 1"#).is_err());
+        assert!(parse_sample("m").is_err());
+        assert!(parse_sample("m abc").is_err());
+    }
+}
@@ -0,0 +1,86 @@
+//! Rychlé vzorkování vybraných gauge (FAST_SAMPLE_INTERVAL_MS) mezi updaty.
+//!
+//! Snapshot memory.current jednou za 5-60 s krátké špičky (a OOM kill, který
+//! z nich vznikne) úplně mine. Tady se vzorkuje častěji na pozadí a při
+//! každém updatu se publikuje min/max/avg za uplynulý interval.
+
+use std::{path::Path, sync::Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::{hierarchy::Layout, metrics::FastSampleMetrics};
+
+#[derive(Debug, Clone, Copy)]
+struct Stats {
+    min: f64,
+    max: f64,
+    sum: f64,
+    count: u64,
+}
+
+impl Stats {
+    fn push(stats: &mut Option<Stats>, v: f64) {
+        match stats {
+            Some(s) => {
+                s.min = s.min.min(v);
+                s.max = s.max.max(v);
+                s.sum += v;
+                s.count += 1;
+            }
+            None => {
+                *stats = Some(Stats {
+                    min: v,
+                    max: v,
+                    sum: v,
+                    count: 1,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    mem_current: Option<Stats>,
+    procs_running: Option<Stats>,
+}
+
+static WINDOW: Lazy<Mutex<Window>> = Lazy::new(|| Mutex::new(Window::default()));
+
+/// Jeden rychlý vzorek - volá se z vlastního tasku s FAST_SAMPLE_INTERVAL_MS.
+pub fn sample(cgroup_root: &Path, proc_root: &Path) {
+    let mem = Layout::detect(cgroup_root)
+        .read("memory.current")
+        .and_then(|s| s.parse::<u64>().ok());
+
+    let running = std::fs::read_to_string(proc_root.join("stat"))
+        .ok()
+        .and_then(|s| {
+            s.lines()
+                .find_map(|l| l.strip_prefix("procs_running "))
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        });
+
+    let mut w = WINDOW.lock().expect("fastsample WINDOW mutex poisoned");
+    if let Some(v) = mem {
+        Stats::push(&mut w.mem_current, v as f64);
+    }
+    if let Some(v) = running {
+        Stats::push(&mut w.procs_running, v as f64);
+    }
+}
+
+/// Publikuje min/max/avg za interval od posledního volání a začne nový.
+pub fn publish(metrics: &FastSampleMetrics) {
+    let w = std::mem::take(&mut *WINDOW.lock().expect("fastsample WINDOW mutex poisoned"));
+
+    for (stats, vec) in [
+        (w.mem_current, &metrics.mem_current_bytes),
+        (w.procs_running, &metrics.procs_running),
+    ] {
+        let Some(s) = stats else { continue };
+        vec.with_label_values(&["min"]).set(s.min);
+        vec.with_label_values(&["max"]).set(s.max);
+        vec.with_label_values(&["avg"]).set(s.sum / s.count as f64);
+    }
+}
@@ -0,0 +1,39 @@
+//! Existence, velikost a stáří souborů z WATCH_FILES - heartbeat soubory,
+//! pid soubory a podobné signály života, které aplikace jen "touchne".
+
+use std::{path::PathBuf, time::SystemTime};
+
+use crate::metrics::WatchFileMetrics;
+
+pub fn update(metrics: &WatchFileMetrics, paths: &[PathBuf]) {
+    let now = SystemTime::now();
+
+    for path in paths {
+        let label = path.display().to_string();
+        let labels = [label.as_str()];
+
+        match std::fs::metadata(path) {
+            Ok(meta) => {
+                metrics.exists.with_label_values(&labels).set(1);
+                metrics
+                    .size_bytes
+                    .with_label_values(&labels)
+                    .set(meta.len() as f64);
+                if let Ok(mtime) = meta.modified() {
+                    // mtime v budoucnosti (posunuté hodiny) → 0, ne záporné stáří
+                    let age = now.duration_since(mtime).unwrap_or_default();
+                    metrics
+                        .age_seconds
+                        .with_label_values(&labels)
+                        .set(age.as_secs_f64());
+                }
+            }
+            Err(_) => {
+                // soubor zmizel - stáří a velikost by byly zavádějící
+                metrics.exists.with_label_values(&labels).set(0);
+                let _ = metrics.size_bytes.remove_label_values(&labels);
+                let _ = metrics.age_seconds.remove_label_values(&labels);
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Využití filesystémů pro cesty z FS_PATHS (statvfs).
+//!
+//! S FS_TARGET_MOUNT_NS se statvfs volá v mount namespace cílového procesu
+//! (ProcessTarget), takže volumy odpovídají mountům aplikačního kontejneru,
+//! ne sidecaru. setns(CLONE_NEWNS) jde jen z vlákna, které nesdílí fs
+//! atributy s ostatními - proto jednorázové pomocné vlákno s unshare(CLONE_FS),
+//! které po měření skončí a zbytek procesu namespace nikdy nezmění.
+
+use std::{
+    ffi::CString,
+    fs::File,
+    os::{
+        fd::{AsRawFd, OwnedFd},
+        unix::ffi::OsStrExt,
+    },
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::metrics::FilesystemMetrics;
+
+#[derive(Debug, Clone, Copy)]
+struct FsStat {
+    size_bytes: f64,
+    avail_bytes: f64,
+    free_bytes: f64,
+    files: f64,
+    files_free: f64,
+}
+
+fn statvfs(path: &Path) -> Result<FsStat> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).context("path contains NUL")?;
+    // SAFETY: statvfs je plain-old-data struktura, nulová hodnota je platná
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path žije do konce funkce, takže ukazatel je platný
+    // NUL-terminated řetězec po celou dobu volání; st je platný &mut
+    let rc = unsafe { libc::statvfs(c_path.as_ptr(), &mut st) };
+    if rc != 0 {
+        return Err(std::io::Error::last_os_error()).context("statvfs");
+    }
+
+    let frsize = st.f_frsize as f64;
+    Ok(FsStat {
+        size_bytes: st.f_blocks as f64 * frsize,
+        avail_bytes: st.f_bavail as f64 * frsize,
+        free_bytes: st.f_bfree as f64 * frsize,
+        files: st.f_files as f64,
+        files_free: st.f_ffree as f64,
+    })
+}
+
+/// statvfs všech cest v mount namespace procesu `pid` (v pomocném vlákně).
+fn statvfs_in_ns(proc_root: &Path, pid: i32, paths: &[PathBuf]) -> Result<Vec<Result<FsStat>>> {
+    // OwnedFd se zavře při dropu na každé cestě - i když vlákno nevznikne
+    let ns: OwnedFd = File::open(proc_root.join(pid.to_string()).join("ns/mnt"))
+        .context("open target mount namespace")?
+        .into();
+    let paths = paths.to_vec();
+
+    std::thread::Builder::new()
+        .name("fs-mntns".into())
+        .spawn(move || -> Result<Vec<Result<FsStat>>> {
+            // SAFETY: unshare(CLONE_FS) mění jen fs atributy (cwd, root, umask)
+            // tohoto vlákna, ostatní vlákna procesu je dál sdílí beze změny
+            if unsafe { libc::unshare(libc::CLONE_FS) } != 0 {
+                return Err(std::io::Error::last_os_error()).context("unshare(CLONE_FS)");
+            }
+            // SAFETY: ns je platný otevřený fd (OwnedFd) po celou dobu volání;
+            // mount namespace se přepne jen tomuto vláknu, které už fs
+            // atributy nesdílí a po měření skončí
+            let rc = unsafe { libc::setns(ns.as_raw_fd(), libc::CLONE_NEWNS) };
+            // errno přečíst dřív, než ho close() přepíše
+            let err = (rc != 0).then(std::io::Error::last_os_error);
+            // fd namespace už není potřeba - zavřít hned, i při chybě
+            drop(ns);
+            if let Some(err) = err {
+                return Err(err).context("setns(CLONE_NEWNS)");
+            }
+            Ok(paths.iter().map(|p| statvfs(p)).collect())
+        })
+        .context("spawn mount namespace helper thread")?
+        .join()
+        .map_err(|_| anyhow::anyhow!("mount namespace helper thread panicked"))?
+}
+
+/// `target_pid` = Some → měřit v mount namespace toho procesu.
+pub fn update(
+    metrics: &FilesystemMetrics,
+    proc_root: &Path,
+    paths: &[PathBuf],
+    target_pid: Option<i32>,
+) -> Result<()> {
+    let results = match target_pid {
+        Some(pid) => statvfs_in_ns(proc_root, pid, paths)?,
+        None => paths.iter().map(|p| statvfs(p)).collect(),
+    };
+
+    let mut errors = Vec::new();
+    for (path, result) in paths.iter().zip(results) {
+        let label = path.display().to_string();
+        let labels = [label.as_str()];
+        match result {
+            Ok(st) => {
+                for (vec, v) in [
+                    (&metrics.size_bytes, st.size_bytes),
+                    (&metrics.avail_bytes, st.avail_bytes),
+                    (&metrics.free_bytes, st.free_bytes),
+                    (&metrics.files, st.files),
+                    (&metrics.files_free, st.files_free),
+                ] {
+                    vec.with_label_values(&labels).set(v);
+                }
+            }
+            Err(e) => errors.push(format!("{label}: {e:#}")),
+        }
+    }
+
+    if !errors.is_empty() {
+        anyhow::bail!("{}", errors.join("; "));
+    }
+    Ok(())
+}
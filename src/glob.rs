@@ -0,0 +1,69 @@
+//! Discovery cgroup podle vzoru (CGROUP_GLOB): každá cgroupa pod CGROUP_ROOT,
+//! jejíž relativní cesta odpovídá vzoru, se exportuje jako série s labelem
+//! `path`. Funguje i mimo Kubernetes, např. `system.slice/*.service`.
+//!
+//! Label je celá relativní cesta - kardinalitu určuje vzor, proto collector
+//! patří mezi obětovatelné v paměťovém rozpočtu. Čte se jen v2 formát.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use walkdir::WalkDir;
+
+use crate::{
+    config::CgroupGlob,
+    metrics::{GlobMetrics, LiveSeries},
+    recursive::read_sample,
+};
+
+pub fn update(metrics: &GlobMetrics, root: &Path, glob: &CgroupGlob) -> Result<()> {
+    // nečitelná root dá prázdný sken - série se promažou, chybu ale ohlásíme
+    let readable = std::fs::read_dir(root)
+        .map(|_| ())
+        .context("read cgroup directory");
+
+    let mut live = LiveSeries::default();
+    let mut matches = 0;
+    for entry in WalkDir::new(root)
+        .min_depth(glob.depth)
+        .max_depth(glob.depth)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+    {
+        let Ok(rel) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let rel = rel.to_string_lossy();
+        if !glob.regex.is_match(&rel) {
+            continue;
+        }
+        matches += 1;
+
+        let sample = read_sample(entry.path());
+        let labels = [rel.as_ref()];
+        live.insert(&labels);
+        metrics
+            .cpu_usage_seconds
+            .with_label_values(&labels)
+            .set(sample.cpu_usage_seconds);
+        metrics
+            .cpu_throttled_seconds
+            .with_label_values(&labels)
+            .set(sample.cpu_throttled_seconds);
+        metrics
+            .mem_current_bytes
+            .with_label_values(&labels)
+            .set(sample.mem_current_bytes);
+    }
+    metrics.matches.set(matches);
+
+    for vec in [
+        &metrics.cpu_usage_seconds,
+        &metrics.cpu_throttled_seconds,
+        &metrics.mem_current_bytes,
+    ] {
+        live.prune(vec);
+    }
+    readable
+}
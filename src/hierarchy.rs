@@ -0,0 +1,270 @@
+//! Rozpoznání cgroup hierarchie pod CGROUP_ROOT a čtení v2 souborů
+//! i tam, kde čistá v2 není:
+//!
+//! - Unified: `cgroup.controllers` přímo v CGROUP_ROOT (čistá v2)
+//! - Hybrid:  v2 je jen v `CGROUP_ROOT/unified` (bez controllerů, jen core
+//!   soubory jako cpu.stat), controllery jsou v1 vedle (`cpu`, `memory`, ...)
+//! - Legacy:  jen v1 controllery
+//!
+//! Collector se ptá na v2 jména souborů; když v2 soubor chybí, sestaví se
+//! jeho obsah ve v2 formátu z odpovídajících v1 souborů (per soubor).
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use once_cell::sync::Lazy;
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgroupMode {
+    Unified,
+    Hybrid,
+    Legacy,
+}
+
+impl CgroupMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CgroupMode::Unified => "unified",
+            CgroupMode::Hybrid => "hybrid",
+            CgroupMode::Legacy => "legacy",
+        }
+    }
+}
+
+/// Naposledy detekovaný režim - kvůli logu při změně.
+static LAST_MODE: Lazy<Mutex<Option<CgroupMode>>> = Lazy::new(|| Mutex::new(None));
+
+/// v1 limity "bez limitu" jsou obří čísla zaokrouhlená na stránku.
+const V1_UNLIMITED: u64 = 1 << 62;
+
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pub mode: CgroupMode,
+    /// Adresář s v2 soubory (u Legacy neexistuje, čtení z něj jen selže)
+    v2: PathBuf,
+    root: PathBuf,
+}
+
+impl Layout {
+    pub fn detect(root: &Path) -> Self {
+        let (mode, v2) = if root.join("cgroup.controllers").exists() {
+            (CgroupMode::Unified, root.to_path_buf())
+        } else if root.join("unified/cgroup.controllers").exists() {
+            (CgroupMode::Hybrid, root.join("unified"))
+        } else if ["cpu", "cpuacct", "memory"]
+            .iter()
+            .any(|c| root.join(c).is_dir())
+        {
+            (CgroupMode::Legacy, root.join("unified"))
+        } else {
+            // fixtures / ručně zadaný adresář jedné cgroupy bez cgroup.controllers
+            (CgroupMode::Unified, root.to_path_buf())
+        };
+
+        let mut last = LAST_MODE
+            .lock()
+            .expect("hierarchy LAST_MODE mutex poisoned");
+        if *last != Some(mode) {
+            info!(mode = mode.as_str(), root = %root.display(), "detected cgroup hierarchy");
+            *last = Some(mode);
+        }
+
+        Self {
+            mode,
+            v2,
+            root: root.to_path_buf(),
+        }
+    }
+
+    /// Adresář v1 controlleru (`cpu`, `memory`, ...), včetně spojených mountů
+    /// typu `cpu,cpuacct`.
+    fn v1_dir(&self, controller: &str) -> Option<PathBuf> {
+        if self.mode == CgroupMode::Unified {
+            return None;
+        }
+        let candidates = match controller {
+            "cpu" => &["cpu", "cpu,cpuacct", "cpuacct,cpu"][..],
+            "cpuacct" => &["cpuacct", "cpu,cpuacct", "cpuacct,cpu"][..],
+            other => return Some(self.root.join(other)).filter(|p| p.is_dir()),
+        };
+        candidates
+            .iter()
+            .map(|c| self.root.join(c))
+            .find(|p| p.is_dir())
+    }
+
+    /// Adresář s v2 soubory (u Hybrid `unified`).
+    pub fn v2_dir(&self) -> &Path {
+        &self.v2
+    }
+
+    /// Namountované v1 controllery (prázdné u Unified). Spojené mounty
+    /// (`cpu,cpuacct`) se rozdělí, `systemd` / `name=` hierarchie nejsou
+    /// controllery.
+    pub fn v1_controllers(&self) -> Vec<String> {
+        if self.mode == CgroupMode::Unified {
+            return Vec::new();
+        }
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+        let mut out: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|name| name != "unified" && name != "systemd")
+            .flat_map(|name| {
+                name.split(',')
+                    .filter(|c| !c.starts_with("name="))
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        out.sort();
+        out.dedup();
+        out
+    }
+
+    fn read_v1(&self, controller: &str, file: &str) -> Option<String> {
+        let dir = self.v1_dir(controller)?;
+        std::fs::read_to_string(dir.join(file))
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    fn read_v1_u64(&self, controller: &str, file: &str) -> Option<u64> {
+        self.read_v1(controller, file)?.parse().ok()
+    }
+
+    /// Obsah v2 souboru (oříznutý); při absenci se zkusí v1 ekvivalent.
+    pub fn read(&self, name: &str) -> Option<String> {
+        if let Ok(s) = std::fs::read_to_string(self.v2.join(name)) {
+            let s = s.trim().to_string();
+            // hybrid: v2 cpu.stat má jen usage, throttling je ve v1
+            if name == "cpu.stat" && self.mode != CgroupMode::Unified {
+                return Some(self.merge_v1_throttling(s));
+            }
+            return Some(s);
+        }
+
+        match name {
+            "cpu.stat" => {
+                let usage = self.read_v1_u64("cpuacct", "cpuacct.usage")?;
+                let mut out = format!("usage_usec {}", usage / 1000);
+                if let Some(v) = self.read_v1_u64("cpuacct", "cpuacct.usage_user") {
+                    out.push_str(&format!("\nuser_usec {}", v / 1000));
+                }
+                if let Some(v) = self.read_v1_u64("cpuacct", "cpuacct.usage_sys") {
+                    out.push_str(&format!("\nsystem_usec {}", v / 1000));
+                }
+                Some(self.merge_v1_throttling(out))
+            }
+            "cpu.max" => {
+                let period = self.read_v1_u64("cpu", "cpu.cfs_period_us")?;
+                let quota = self.read_v1("cpu", "cpu.cfs_quota_us")?;
+                // v1 -1 = bez limitu
+                let quota = if quota.starts_with('-') {
+                    "max".to_string()
+                } else {
+                    quota
+                };
+                Some(format!("{quota} {period}"))
+            }
+            "cpu.max.burst" => self.read_v1("cpu", "cpu.cfs_burst_us"),
+            "cpu.idle" => self.read_v1("cpu", "cpu.idle"),
+            "memory.current" => self.read_v1("memory", "memory.usage_in_bytes"),
+            "memory.peak" => self.read_v1("memory", "memory.max_usage_in_bytes"),
+            "memory.max" => self
+                .read_v1_u64("memory", "memory.limit_in_bytes")
+                .map(v1_limit),
+            "memory.low" => self
+                .read_v1_u64("memory", "memory.soft_limit_in_bytes")
+                .map(v1_limit),
+            // v1 memsw = paměť + swap, swap samotný je rozdíl
+            "memory.swap.current" => {
+                let memsw = self.read_v1_u64("memory", "memory.memsw.usage_in_bytes")?;
+                let usage = self.read_v1_u64("memory", "memory.usage_in_bytes")?;
+                Some(memsw.saturating_sub(usage).to_string())
+            }
+            "memory.swap.max" => {
+                let memsw = self.read_v1_u64("memory", "memory.memsw.limit_in_bytes")?;
+                let limit = self.read_v1_u64("memory", "memory.limit_in_bytes")?;
+                Some(if memsw >= V1_UNLIMITED {
+                    "max".to_string()
+                } else {
+                    memsw.saturating_sub(limit).to_string()
+                })
+            }
+            // v1 freezer.state: THAWED | FREEZING | FROZEN
+            "cgroup.events" => self
+                .read_v1("freezer", "freezer.state")
+                .map(|s| format!("frozen {}", u8::from(s == "FROZEN"))),
+            "cgroup.freeze" => self
+                .read_v1("freezer", "freezer.state")
+                .map(|s| u8::from(s != "THAWED").to_string()),
+            // členy stačí vzít z jednoho controlleru
+            "cgroup.procs" => self
+                .read_v1("memory", "cgroup.procs")
+                .or_else(|| self.read_v1("cpu", "cgroup.procs")),
+            "cpuset.cpus.effective" => self.read_v1("cpuset", "cpuset.effective_cpus"),
+            "cpuset.mems.effective" => self.read_v1("cpuset", "cpuset.effective_mems"),
+            "memory.stat" => {
+                // v2 hodnoty jsou hierarchické = v1 total_*
+                let raw = self.read_v1("memory", "memory.stat")?;
+                let totals: Vec<String> = raw
+                    .lines()
+                    .filter_map(|l| l.strip_prefix("total_"))
+                    .map(str::to_string)
+                    .collect();
+                Some(if totals.is_empty() {
+                    raw
+                } else {
+                    totals.join("\n")
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Doplní v2 cpu.stat o throttling z v1 cpu.stat (throttled_time je v ns).
+    fn merge_v1_throttling(&self, mut stat: String) -> String {
+        if stat.contains("nr_periods") {
+            return stat;
+        }
+        let Some(v1) = self.read_v1("cpu", "cpu.stat") else {
+            return stat;
+        };
+        for line in v1.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(val)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let converted = match key {
+                "nr_periods" | "nr_throttled" | "nr_bursts" => format!("{key} {val}"),
+                "throttled_time" => match val.parse::<u64>() {
+                    Ok(ns) => format!("throttled_usec {}", ns / 1000),
+                    Err(_) => continue,
+                },
+                "burst_time" => match val.parse::<u64>() {
+                    Ok(ns) => format!("burst_usec {}", ns / 1000),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            stat.push('\n');
+            stat.push_str(&converted);
+        }
+        stat
+    }
+}
+
+fn v1_limit(v: u64) -> String {
+    if v >= V1_UNLIMITED {
+        "max".to_string()
+    } else {
+        v.to_string()
+    }
+}
@@ -3,6 +3,7 @@
 use std::{
     fs::File,
     io::{BufRead, BufReader},
+    path::Path,
 };
 
 use anyhow::{Context, Result, bail};
@@ -10,9 +11,9 @@ use anyhow::{Context, Result, bail};
 use crate::metrics::HostMetrics;
 
 /// Aktualizuje všechny host metriky (CPU + paměť).
-pub fn update(metrics: &HostMetrics) -> Result<()> {
-    update_cpu(metrics)?;
-    update_memory(metrics)?;
+pub fn update(metrics: &HostMetrics, proc_root: &Path) -> Result<()> {
+    update_cpu(metrics, proc_root)?;
+    update_memory(metrics, proc_root)?;
     Ok(())
 }
 
@@ -24,8 +25,8 @@ fn ticks_per_second() -> f64 {
 }
 
 /// Parsuje agregovaný řádek "cpu  ..." z /proc/stat a uloží ho do metrik.
-fn update_cpu(metrics: &HostMetrics) -> Result<()> {
-    let file = File::open("/proc/stat").context("open /proc/stat")?;
+fn update_cpu(metrics: &HostMetrics, proc_root: &Path) -> Result<()> {
+    let file = File::open(proc_root.join("stat")).context("open /proc/stat")?;
     let reader = BufReader::new(file);
 
     let mut cpu_line: Option<String> = None;
@@ -88,8 +89,8 @@ fn update_cpu(metrics: &HostMetrics) -> Result<()> {
 }
 
 /// Parsuje /proc/meminfo a uloží vybrané položky.
-fn update_memory(metrics: &HostMetrics) -> Result<()> {
-    let file = File::open("/proc/meminfo").context("open /proc/meminfo")?;
+fn update_memory(metrics: &HostMetrics, proc_root: &Path) -> Result<()> {
+    let file = File::open(proc_root.join("meminfo")).context("open /proc/meminfo")?;
     let reader = BufReader::new(file);
 
     let mut mem_total = None;
@@ -135,3 +136,31 @@ fn update_memory(metrics: &HostMetrics) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn update_reads_fixture_proc() {
+        let cfg = Config::for_fixtures();
+        let metrics = HostMetrics::new(&Registry::new(), &cfg).unwrap();
+
+        update(&metrics, &cfg.proc_root).unwrap();
+
+        let cpu = |mode| {
+            metrics
+                .cpu_seconds_total
+                .with_label_values(&["all", mode])
+                .get()
+        };
+        assert_eq!(cpu("user"), 18384.12);
+        assert_eq!(cpu("idle"), 482610.44);
+        assert_eq!(metrics.memory_total_bytes.get(), 16702771200.0);
+        assert_eq!(metrics.memory_available_bytes.get(), 10070130688.0);
+        assert_eq!(metrics.swap_free_bytes.get(), 2147479552.0);
+    }
+}
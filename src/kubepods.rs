@@ -0,0 +1,191 @@
+//! Rozpoznání Kubernetes pod/kontejneru z cgroup cesty (kubepods hierarchie).
+//!
+//! Kubelet zakládá cgroupy ve dvou tvarech podle cgroup driveru:
+//!
+//! - cgroupfs: `/kubepods/burstable/pod<uid>/<container-id>`
+//! - systemd:  `/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod<uid>.slice/cri-containerd-<id>.scope`
+//!
+//! Guaranteed pody nemají QoS mezivrstvu (`/kubepods/pod<uid>/...`).
+
+use std::path::Path;
+
+use tracing::{debug, info};
+
+use crate::{config::Config, metrics::Metrics};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QosClass {
+    Guaranteed,
+    Burstable,
+    BestEffort,
+}
+
+impl QosClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QosClass::Guaranteed => "Guaranteed",
+            QosClass::Burstable => "Burstable",
+            QosClass::BestEffort => "BestEffort",
+        }
+    }
+
+    /// Jak ji pojmenovává cgroup cesta (`kubepods-besteffort.slice`).
+    pub fn as_segment(&self) -> &'static str {
+        match self {
+            QosClass::Guaranteed => "guaranteed",
+            QosClass::Burstable => "burstable",
+            QosClass::BestEffort => "besteffort",
+        }
+    }
+}
+
+/// Co se z cgroup cesty dá vyčíst.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodCgroup {
+    pub qos: QosClass,
+    /// UID podu s pomlčkami (systemd driver je nahrazuje podtržítky)
+    pub pod_uid: Option<String>,
+    /// ID kontejneru bez prefixu runtime (cri-containerd-, crio-, docker-)
+    pub container_id: Option<String>,
+}
+
+/// Rozparsuje cgroup cestu; None pokud nejde o kubepods hierarchii.
+pub fn parse_cgroup_path(path: &str) -> Option<PodCgroup> {
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+
+    // kubepods může být i hlouběji (např. kind / nested cgroupy)
+    segments
+        .by_ref()
+        .find(|s| *s == "kubepods" || *s == "kubepods.slice")?;
+
+    let mut qos = QosClass::Guaranteed;
+    let mut pod_uid = None;
+    let mut container_id = None;
+
+    for seg in segments {
+        let name = seg
+            .strip_suffix(".slice")
+            .or_else(|| seg.strip_suffix(".scope"))
+            .unwrap_or(seg);
+
+        if pod_uid.is_none() {
+            match name {
+                "burstable" | "kubepods-burstable" => {
+                    qos = QosClass::Burstable;
+                    continue;
+                }
+                "besteffort" | "kubepods-besteffort" => {
+                    qos = QosClass::BestEffort;
+                    continue;
+                }
+                _ => {}
+            }
+
+            if let Some(pos) = name.rfind("pod") {
+                pod_uid = Some(name[pos + 3..].replace('_', "-"));
+                continue;
+            }
+        } else if container_id.is_none() {
+            container_id = container_id_from_segment(seg);
+        }
+    }
+
+    pod_uid.as_ref()?;
+    Some(PodCgroup {
+        qos,
+        pod_uid,
+        container_id,
+    })
+}
+
+/// ID kontejneru z jednoho segmentu cesty (`cri-containerd-<id>.scope`,
+/// `crio-<id>.scope`, `docker-<id>.scope` nebo holé `<id>` u cgroupfs).
+pub fn container_id_from_segment(seg: &str) -> Option<String> {
+    let name = seg.strip_suffix(".scope").unwrap_or(seg);
+    let id = name.rsplit_once('-').map(|(_, id)| id).unwrap_or(name);
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id.to_string())
+}
+
+/// Cgroup cesta procesu z `/proc/<pid>/cgroup` - přednostně v2 řádek (`0::`),
+/// jinak první v1 hierarchie, která vede do kubepods.
+pub fn cgroup_path_of(proc_root: &Path, pid: &str) -> Option<String> {
+    let raw = std::fs::read_to_string(proc_root.join(pid).join("cgroup")).ok()?;
+
+    let mut fallback = None;
+    for line in raw.lines() {
+        let mut parts = line.splitn(3, ':');
+        let (Some(id), Some(_), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        if id == "0" && path.contains("kubepods") {
+            return Some(path.to_string());
+        }
+        if fallback.is_none() && path.contains("kubepods") {
+            fallback = Some(path.to_string());
+        }
+    }
+    fallback
+}
+
+/// QoS podle requests/limits z env (typicky resourceFieldRef z downward API),
+/// stejná pravidla jako kubelet. None, když nejsou nastavené vůbec.
+fn qos_from_resources(cfg: &Config) -> Option<QosClass> {
+    let values = [
+        cfg.cpu_requests_mcpu,
+        cfg.cpu_limits_mcpu,
+        cfg.memory_requests_bytes,
+        cfg.memory_limits_bytes,
+    ];
+    if values.iter().all(Option::is_none) {
+        return None;
+    }
+    if values.iter().flatten().all(|&v| v == 0.0) {
+        return Some(QosClass::BestEffort);
+    }
+
+    // request bez explicitní hodnoty = limit (defaulting v API serveru)
+    let equal = |req: Option<f64>, lim: Option<f64>| match (req, lim) {
+        (_, None) => false,
+        (None, Some(l)) => l > 0.0,
+        (Some(r), Some(l)) => l > 0.0 && r == l,
+    };
+    if equal(cfg.cpu_requests_mcpu, cfg.cpu_limits_mcpu)
+        && equal(cfg.memory_requests_bytes, cfg.memory_limits_bytes)
+    {
+        Some(QosClass::Guaranteed)
+    } else {
+        Some(QosClass::Burstable)
+    }
+}
+
+/// Zjistí QoS class podu a nastaví kubernetes_qos_class_info{qos="burstable"} 1
+/// a původní k8s_qos_class_info{class="Burstable"} 1.
+/// Přednost má sledovaná cgroupa (CGROUP_ROOT v kubepods hierarchii, např.
+/// host /sys/fs/cgroup namountovaný do monitorovacího podu), pak vlastní
+/// cgroupa exporteru, nakonec requests/limits z env.
+pub fn init_qos_info(metrics: &Metrics, cfg: &Config) {
+    let from_root = parse_cgroup_path(&cfg.cgroup_root.to_string_lossy()).map(|pc| pc.qos);
+    let from_cgroup = cgroup_path_of(&cfg.proc_root, "self")
+        .and_then(|p| parse_cgroup_path(&p))
+        .map(|pc| pc.qos);
+
+    let (qos, source) = match (from_root, from_cgroup, qos_from_resources(cfg)) {
+        (Some(q), _, _) => (q, "cgroup_root"),
+        (None, Some(q), _) => (q, "cgroup"),
+        (None, None, Some(q)) => (q, "resources"),
+        (None, None, None) => {
+            debug!("QoS class not detected (no kubepods cgroup path, no resource env)");
+            return;
+        }
+    };
+
+    info!(class = qos.as_str(), source, "detected pod QoS class");
+    metrics
+        .qos_class_info
+        .with_label_values(&[qos.as_str()])
+        .set(1);
+    metrics
+        .kubernetes_qos_class_info
+        .with_label_values(&[qos.as_segment()])
+        .set(1);
+}
@@ -1,3 +1,16 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use tracing_appender::{
+    non_blocking::{NonBlocking, WorkerGuard},
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{EnvFilter, fmt::writer::BoxMakeWriter, prelude::*};
+
 #[macro_export]
 macro_rules! log_anyhow_with_source {
     ($err:expr, $($rest:tt)+) => {{
@@ -24,3 +37,170 @@ macro_rules! log_error_display {
         );
     }};
 }
+
+/// Inicializace tracingu: konzole (stdout, v režimu --once stderr) a volitelně
+/// soubor z LOG_FILE s rotací. Na bare-metalu mimo Kubernetes stderr nikdo
+/// nesbírá.
+///
+/// - LOG_FILE_ROTATION: minutely | hourly | daily (default) | weekly | never
+/// - LOG_FILE_MAX_BYTES: rotace podle velikosti (má přednost před časem)
+/// - LOG_FILE_MAX_FILES: kolik starých souborů ponechat (default 7)
+///
+/// Vrácený guard musí žít do konce procesu, jinak se ztratí nezapsané řádky.
+pub fn init(once: bool) -> anyhow::Result<Option<WorkerGuard>> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    // v režimu --once patří stdout jen metrikám
+    let console_writer = if once {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    let console = tracing_subscriber::fmt::layer().with_writer(console_writer);
+
+    let (file_layer, guard) = match file_writer()? {
+        Some((writer, guard)) => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(writer),
+            ),
+            Some(guard),
+        ),
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(console)
+        .with(file_layer)
+        .init();
+
+    Ok(guard)
+}
+
+fn file_writer() -> anyhow::Result<Option<(NonBlocking, WorkerGuard)>> {
+    let Some(path) = std::env::var("LOG_FILE")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+    else {
+        return Ok(None);
+    };
+
+    let max_files: usize = match std::env::var("LOG_FILE_MAX_FILES") {
+        Ok(v) => v.trim().parse().context("LOG_FILE_MAX_FILES parse error")?,
+        Err(_) => 7,
+    };
+    let max_bytes: Option<u64> = std::env::var("LOG_FILE_MAX_BYTES")
+        .ok()
+        .map(|v| v.trim().parse().context("LOG_FILE_MAX_BYTES parse error"))
+        .transpose()?;
+
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("create log directory {}", dir.display()))?;
+
+    let (writer, guard) = if let Some(max_bytes) = max_bytes {
+        tracing_appender::non_blocking(SizeRotatingFile::open(path, max_bytes, max_files)?)
+    } else {
+        let rotation = match std::env::var("LOG_FILE_ROTATION")
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "daily" => Rotation::DAILY,
+            "minutely" => Rotation::MINUTELY,
+            "hourly" => Rotation::HOURLY,
+            "weekly" => Rotation::WEEKLY,
+            "never" => Rotation::NEVER,
+            other => anyhow::bail!(
+                "LOG_FILE_ROTATION must be minutely, hourly, daily, weekly or never, got {other:?}"
+            ),
+        };
+        let file_name = path
+            .file_name()
+            .context("LOG_FILE must name a file")?
+            .to_string_lossy()
+            .into_owned();
+        let appender = RollingFileAppender::builder()
+            .rotation(rotation)
+            .filename_prefix(file_name)
+            .max_log_files(max_files.max(1))
+            .build(&dir)
+            .context("create rolling log file")?;
+        tracing_appender::non_blocking(appender)
+    };
+
+    Ok(Some((writer, guard)))
+}
+
+/// Soubor rotovaný podle velikosti: app.log → app.log.1 → … → app.log.N.
+struct SizeRotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl SizeRotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> anyhow::Result<Self> {
+        let file = Self::open_append(&path)
+            .with_context(|| format!("open log file {}", path.display()))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    fn open_append(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            // žádné staré soubory → jen zkrátit
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated(self.max_files));
+            for n in (1..self.max_files).rev() {
+                let _ = std::fs::rename(self.rotated(n), self.rotated(n + 1));
+            }
+            std::fs::rename(&self.path, self.rotated(1))?;
+            self.file = Self::open_append(&self.path)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
@@ -1,63 +1,233 @@
+mod blockdev;
+mod budget;
+mod cache;
+mod certs;
 mod cgroup;
+mod changes;
+mod cli;
 mod config;
+mod container_cgroup;
+mod containers;
+mod delta;
 mod downward;
+mod exec;
+mod fastsample;
+mod files;
+mod filesystem;
+mod glob;
+mod hierarchy;
 mod host;
+mod kubepods;
 mod logging;
 mod metrics;
+mod monotonic;
 mod net;
+mod nodepods;
+mod oomwatch;
+mod peaks;
+mod probes;
 mod procfs;
+mod procgroups;
+mod psitrigger;
+mod reclaim;
+mod recursive;
+#[cfg(feature = "runtime-metrics")]
+mod runtime;
+mod security;
+mod siblings;
+mod state;
+mod steal;
+mod stub;
+mod systemd;
+mod taskstats;
+mod tc;
 mod tcp;
+mod thresholds;
+mod tree;
+mod version;
 
-use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    convert::Infallible,
+    io::Write,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use http_body_util::Full;
-use hyper::body::{Bytes, Incoming};
+use hyper::body::{Body, Bytes, Incoming};
 use hyper::server::conn::http1;
 use hyper::service::service_fn;
 use hyper::{Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
+use once_cell::sync::Lazy;
 use prometheus::{Encoder, TextEncoder};
 use tokio::net::TcpListener;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::Notify;
 use tracing::{debug, info, warn};
-use tracing_subscriber::EnvFilter;
 
 use crate::{
-    cgroup as cgroup_mod, config::Config, downward as downward_mod, host as host_mod,
-    metrics::Metrics, net as net_mod, procfs as procfs_mod, tcp as tcp_mod,
+    budget::Sheddable,
+    cgroup as cgroup_mod,
+    cli::Cli,
+    config::{Config, ProcessTarget},
+    downward as downward_mod, host as host_mod,
+    metrics::Metrics,
+    net as net_mod, procfs as procfs_mod, state as state_mod, tcp as tcp_mod,
 };
 
 struct AppState {
     cfg: Config,
     metrics: Metrics,
+    /// Syntetické metriky místo skutečných collectorů (--dev-stub / ne-Linux)
+    dev_stub: bool,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // tracing/logging init
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let cli = Cli::from_args()?;
+
+    if cli.version {
+        print!("{}", version::long_version());
+        return Ok(());
+    }
+
+    let _log_guard = logging::init(cli.once)?;
+
+    info!(
+        version = version::VERSION,
+        git_commit = version::GIT_COMMIT,
+        build_date = version::BUILD_DATE,
+        features = version::features(),
+        "cgroup-runtime-exporter"
+    );
+
+    let mut cfg = Config::from_env()?;
+
+    // mimo Linux by všechny collectory jen padaly → automaticky stub
+    let dev_stub = cli.dev_stub || cfg!(not(target_os = "linux"));
+    if dev_stub {
+        warn!(
+            "dev stub mode - serving synthetic metric values, nothing is read from /proc or /sys"
+        );
+    }
 
-    let cfg = Config::from_env()?;
+    // Kontejner bez cgroup namespace vidí celý host - default root přesměrovat
+    // na vlastní cgroupu, jinak by metriky byly za celý node
+    if cfg.cgroup_ns_detect
+        && !dev_stub
+        && let Some(dir) = cgroup_mod::own_container_dir(&cfg.proc_root, &cfg.cgroup_root)
+    {
+        info!(
+            mount = %cfg.cgroup_root.display(),
+            cgroup = %dir.display(),
+            "host cgroup hierarchy visible (no cgroup namespace), using own container cgroup"
+        );
+        for root in &mut cfg.cgroup_roots {
+            if root.path == cfg.cgroup_root {
+                root.path.clone_from(&dir);
+            }
+        }
+        if let Some(ProcessTarget::Cgroup(ref mut target)) = cfg.process_target
+            && *target == cfg.cgroup_root
+        {
+            target.clone_from(&dir);
+        }
+        cfg.cgroup_root = dir;
+    }
 
     let metrics = Metrics::new(&cfg)?;
-    let state = Arc::new(AppState { cfg, metrics });
+
+    let state = Arc::new(AppState {
+        cfg,
+        metrics,
+        dev_stub,
+    });
 
     // DownwardAPI je nepovinné - pokud není DIR, nic se neděje
-    if let Some(ref dir) = state.cfg.downward_dir {
-        if let Err(e) = downward_mod::init_downward_info(&state.metrics, dir) {
-            log_anyhow_with_source!(e, "init downward api info failed");
+    if let Some(ref dir) = state.cfg.downward_dir
+        && let Err(e) = downward_mod::init_downward_info(&state.metrics, dir)
+    {
+        log_anyhow_with_source!(e, "init downward api info failed");
+    }
+
+    // QoS class podu - statická po celou dobu běhu
+    if !state.dev_stub {
+        kubepods::init_qos_info(&state.metrics, &state.cfg);
+    }
+
+    // Self-check oprávnění - jen informace + warningy, start neblokuje
+    if !state.dev_stub {
+        security::init_security_info(&state.metrics, &state.cfg);
+    }
+
+    if let (Some(budget), Some(bytes)) =
+        (&state.metrics.memory_budget, state.cfg.memory_budget_bytes)
+    {
+        budget::init(budget, &state.metrics, bytes);
+    }
+
+    // saver po SIGTERM uloží stav a ohlásí konec - main pak normálně vrátí,
+    // ať se dropne i guard logování a dopíše LOG_FILE
+    let shutdown = Arc::new(Notify::new());
+
+    // Perzistentní stav (peaky, offsety counterů) - načíst dřív, než poběží první update
+    if let Some(ref path) = state.cfg.state_file {
+        if let Err(e) = state_mod::load(path) {
+            log_anyhow_with_source!(e, path = %path.display(), "loading state file failed");
+        }
+        if !cli.once {
+            spawn_state_saver(Arc::clone(&state), path.clone(), Arc::clone(&shutdown));
         }
     }
 
+    // --once: jeden sběr, výpis exposition formátu na stdout a konec
+    if cli.once {
+        if let Err(e) = update_metrics(&state) {
+            log_anyhow_with_source!(e, "updating metrics failed");
+        }
+        update_exec(&state);
+        let (buffer, _) = encode_metrics(&state.metrics);
+        std::io::stdout()
+            .write_all(&buffer)
+            .context("write metrics to stdout")?;
+        return Ok(());
+    }
+
+    // Rychlé vzorkování mezi updaty (FAST_SAMPLE_INTERVAL_MS)
+    if let Some(ms) = state.cfg.fast_sample_interval_ms
+        && !state.dev_stub
+    {
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(ms));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                fastsample::sample(&state.cfg.cgroup_root, &state.cfg.proc_root);
+            }
+        });
+    }
+
     // Background update loop - cache metrik
     {
         let state = Arc::clone(&state);
         tokio::spawn(async move {
             let interval = Duration::from_secs(state.cfg.update_interval_secs);
             loop {
-                if let Err(e) = update_metrics(&state) {
-                    log_anyhow_with_source!(e, "updating metrics failed");
+                // collectory jsou blokující (fs, probe s timeoutem) - mimo async
+                // workery, jinak by na 1 CPU zablokovaly i obsluhu /metrics
+                let st = Arc::clone(&state);
+                match tokio::task::spawn_blocking(move || update_metrics(&st)).await {
+                    Ok(Err(e)) => log_anyhow_with_source!(e, "updating metrics failed"),
+                    Err(e) => log_error_display!(e, "metrics update task panicked"),
+                    Ok(Ok(())) => {}
                 }
                 debug!(
                     sleep_secs = interval.as_secs(),
@@ -69,6 +239,23 @@ async fn main() -> Result<()> {
         });
     }
 
+    // EXEC_COMMANDS ve vlastní smyčce s EXEC_INTERVAL_SECS - zaseknutý
+    // skript nesmí zdržet cgroup a process metriky
+    if state.metrics.exec.is_some() && !state.dev_stub {
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(state.cfg.exec_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let st = Arc::clone(&state);
+                if let Err(e) = tokio::task::spawn_blocking(move || update_exec(&st)).await {
+                    log_error_display!(e, "exec update task panicked");
+                }
+            }
+        });
+    }
+
     let addr: SocketAddr = state.cfg.listen_addr;
     info!(
         listen_addr = %addr,
@@ -79,56 +266,430 @@ async fn main() -> Result<()> {
     // hyper 1.x už nemá "Server::bind"; použijeme TcpListener + http1::Builder
     let listener = TcpListener::bind(addr).await?;
     loop {
-        let (stream, _) = listener.accept().await?;
-        let io = TokioIo::new(stream);
-        let state_clone = Arc::clone(&state);
+        tokio::select! {
+            res = listener.accept() => {
+                let (stream, _) = res?;
+                tokio::spawn(serve_connection(stream, Arc::clone(&state)));
+            }
+            _ = shutdown.notified() => return Ok(()),
+        }
+    }
+}
 
-        tokio::spawn(async move {
-            let service = service_fn(move |req: Request<Incoming>| {
-                let state = Arc::clone(&state_clone);
-                async move { handle_request(req, state).await }
-            });
+/// Obslouží jedno HTTP/1 spojení podle HttpConfig: keep-alive, pipeline
+/// flush, idle timeout a limit requestů na spojení. Idle timeout i limit
+/// hyper sám neumí - spojení se při nich ukončí přes graceful_shutdown,
+/// rozpracovaná odpověď se ještě dopíše.
+async fn serve_connection(stream: tokio::net::TcpStream, state: Arc<AppState>) {
+    let http = state.cfg.http.clone();
+    let last_activity = Arc::new(Mutex::new(Instant::now()));
+    // service ohlásí dosažení HTTP_MAX_REQUESTS_PER_CONNECTION
+    let limit_reached = Arc::new(Notify::new());
+
+    let service = {
+        let requests = AtomicU64::new(0);
+        let last_activity = Arc::clone(&last_activity);
+        let limit_reached = Arc::clone(&limit_reached);
+        service_fn(move |req: Request<Incoming>| {
+            let state = Arc::clone(&state);
+            let last_activity = Arc::clone(&last_activity);
+            *last_activity.lock().expect("last_activity mutex poisoned") = Instant::now();
 
-            if let Err(e) = http1::Builder::new().serve_connection(io, service).await {
-                log_error_display!(e, "serving connection failed");
+            let served = requests.fetch_add(1, Ordering::Relaxed) + 1;
+            if http
+                .max_requests_per_connection
+                .is_some_and(|max| served >= max)
+            {
+                limit_reached.notify_one();
             }
-        });
+
+            async move {
+                let response = handle_request(req, state).await;
+                *last_activity.lock().expect("last_activity mutex poisoned") = Instant::now();
+                response
+            }
+        })
+    };
+
+    let conn = http1::Builder::new()
+        .keep_alive(http.keep_alive)
+        .pipeline_flush(http.pipeline_flush)
+        .serve_connection(TokioIo::new(stream), service);
+    tokio::pin!(conn);
+
+    let mut shutting_down = false;
+    loop {
+        // Kdy nejdřív může vypršet idle timeout (od poslední aktivity)
+        let idle_deadline = http
+            .idle_timeout
+            .map(|timeout| *last_activity.lock().expect("last_activity mutex poisoned") + timeout);
+
+        tokio::select! {
+            res = conn.as_mut() => {
+                if let Err(e) = res {
+                    log_error_display!(e, "serving connection failed");
+                }
+                return;
+            }
+            _ = limit_reached.notified(), if !shutting_down => {
+                debug!("connection reached HTTP_MAX_REQUESTS_PER_CONNECTION, closing");
+                conn.as_mut().graceful_shutdown();
+                shutting_down = true;
+            }
+            _ = async {
+                match idle_deadline {
+                    Some(at) => tokio::time::sleep_until(at.into()).await,
+                    None => std::future::pending().await,
+                }
+            }, if !shutting_down => {
+                let idle = last_activity.lock().expect("last_activity mutex poisoned").elapsed();
+                if http.idle_timeout.is_some_and(|timeout| idle >= timeout) {
+                    debug!(idle_secs = idle.as_secs(), "closing idle connection");
+                    conn.as_mut().graceful_shutdown();
+                    shutting_down = true;
+                }
+            }
+        }
     }
 }
 
+/// Periodicky ukládá stav do STATE_FILE; na SIGTERM uloží naposledy
+/// a přes `shutdown` ukončí main.
+fn spawn_state_saver(state: Arc<AppState>, path: PathBuf, shutdown: Arc<Notify>) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs(state.cfg.state_save_interval_secs));
+        interval.tick().await; // první tick je okamžitý - nic k uložení
+
+        let mut sigterm = match signal(SignalKind::terminate()) {
+            Ok(s) => Some(s),
+            Err(e) => {
+                log_error_display!(e, "installing SIGTERM handler failed");
+                None
+            }
+        };
+
+        loop {
+            let terminating = tokio::select! {
+                _ = interval.tick() => false,
+                Some(_) = async { sigterm.as_mut()?.recv().await } => true,
+            };
+
+            if let Err(e) = state_mod::save(&path) {
+                log_anyhow_with_source!(e, path = %path.display(), "saving state file failed");
+            }
+
+            if terminating {
+                info!("SIGTERM received, state saved, exiting");
+                // notify_one si permit zapamatuje, i kdyby accept smyčka zrovna nečekala
+                shutdown.notify_one();
+                return;
+            }
+        }
+    });
+}
+
 fn update_metrics(state: &AppState) -> Result<()> {
-    // Cgroup metrics
-    if let Err(e) = cgroup_mod::update(&state.metrics.cgroup, &state.cfg.cgroup_root) {
-        log_anyhow_with_source!(e, "updating cgroup metrics failed");
+    // Runtime exporteru samotného - nezávisí na stub režimu
+    #[cfg(feature = "runtime-metrics")]
+    runtime::update(&state.metrics.runtime);
+
+    // Paměťový rozpočet - případně vypne collector ještě před sběrem
+    if let (Some(budget), Some(bytes)) =
+        (&state.metrics.memory_budget, state.cfg.memory_budget_bytes)
+        && let Err(e) = budget::enforce(budget, &state.metrics, bytes)
+    {
+        log_anyhow_with_source!(e, "checking exporter memory budget failed");
     }
 
-    // Process metrics - nově umí Single PID, list PIDů i regexp
-    if let Some(ref target) = state.cfg.process_target {
-        if let Err(e) = procfs_mod::update_for_target(&state.metrics.process, target) {
-            log_anyhow_with_source!(e, "updating proc metrics failed");
+    if state.dev_stub {
+        stub::update(&state.metrics);
+    } else {
+        collect(state);
+    }
+
+    if let Some(ref fast) = state.metrics.fast_sample {
+        fastsample::publish(fast);
+    }
+
+    // Odvozené vyhodnocení nad tím, co collectory právě naplnily
+    steal::update(&state.metrics);
+    thresholds::evaluate(&state.metrics, &state.cfg);
+
+    // snapshot pro GET /delta - až úplně na konci, ať obsahuje i odvozené metriky
+    changes::record(&state.metrics);
+    cache::invalidate();
+
+    Ok(())
+}
+
+/// Poslední cgroupa cílového procesu (CGROUP_FOLLOW_TARGET) nebo systemd
+/// unity (CGROUP_SYSTEMD_UNIT) či kontejneru (CGROUP_CONTAINER_ID) - kvůli
+/// logu při změně.
+static LAST_TARGET_CGROUP: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+/// Cgroupa, ze které se sbírají cgroup metriky: s CGROUP_FOLLOW_TARGET
+/// cgroupa prvního matchnutého procesu, s CGROUP_SYSTEMD_UNIT cgroupa unity,
+/// s CGROUP_CONTAINER_ID cgroupa kontejneru, jinak (i když se ji nepodaří
+/// zjistit) CGROUP_ROOT.
+/// Vlastní metriky z příkazů EXEC_COMMANDS.
+fn update_exec(state: &AppState) {
+    if let Some(ref exec) = state.metrics.exec
+        && !budget::is_shed(Sheddable::Exec)
+    {
+        exec::update(exec, &state.cfg.exec_commands, state.cfg.exec_timeout);
+    }
+}
+
+fn effective_cgroup_root(state: &AppState) -> PathBuf {
+    let cfg = &state.cfg;
+    let mount = cfg.sys_root.join("fs/cgroup");
+    let (resolved, source) = match (
+        &cfg.process_target,
+        &cfg.cgroup_systemd_unit,
+        &cfg.cgroup_container_id,
+    ) {
+        (Some(target), _, _) if cfg.cgroup_follow_target => {
+            let dir = procfs_mod::resolve_target(&cfg.proc_root, target)
+                .ok()
+                .and_then(|matches| {
+                    matches
+                        .iter()
+                        .find_map(|m| cgroup_mod::dir_of_pid(&cfg.proc_root, &mount, m.pid))
+                });
+            (dir, "target process")
+        }
+        (_, Some(unit), _) => (systemd::resolve_unit(&mount, unit), "systemd unit"),
+        (_, _, Some(id)) => (container_cgroup::resolve(&mount, id), "container"),
+        _ => return cfg.cgroup_root.clone(),
+    };
+
+    let mut last = LAST_TARGET_CGROUP
+        .lock()
+        .expect("LAST_TARGET_CGROUP mutex poisoned");
+    if *last != resolved {
+        match resolved {
+            Some(ref dir) => info!(cgroup = %dir.display(), source, "following cgroup"),
+            None => warn!(
+                fallback = %cfg.cgroup_root.display(),
+                source,
+                "cgroup to follow not found, using CGROUP_ROOT"
+            ),
+        }
+        last.clone_from(&resolved);
+    }
+
+    resolved.unwrap_or_else(|| cfg.cgroup_root.clone())
+}
+
+/// Spustí všechny collectory; chyby jednotlivých collectorů jen loguje.
+fn collect(state: &AppState) {
+    // Cgroup metrics - per root z CGROUP_ROOTS; sledování cgroupy cíle se
+    // týká jen root shodné s CGROUP_ROOT
+    for (root, metrics) in state.cfg.cgroup_roots.iter().zip(&state.metrics.cgroups) {
+        let dir = if root.path == state.cfg.cgroup_root {
+            effective_cgroup_root(state)
+        } else {
+            root.path.clone()
+        };
+        if let Err(e) = cgroup_mod::update(metrics, &dir) {
+            log_anyhow_with_source!(e, root = %root.name, "updating cgroup metrics failed");
+        }
+        if let Some(ref oom) = state.metrics.oom {
+            oomwatch::update(oom, &root.name, &dir);
         }
+        if let Some(ref psi) = state.metrics.psi_triggers {
+            psitrigger::update(psi, &state.cfg.psi_triggers, &root.name, &dir);
+        }
+        if let (Some(reclaim_metrics), Some(probe)) =
+            (&state.metrics.reclaim, &state.cfg.memory_reclaim_probe)
+            && let Err(e) = reclaim::update(reclaim_metrics, probe, &root.name, &dir)
+        {
+            log_anyhow_with_source!(e, root = %root.name, "memory reclaim probe failed");
+        }
+        if let (Some(recursive_metrics), Some(mode)) =
+            (&state.metrics.recursive, state.cfg.cgroup_recursive)
+            && !budget::is_shed(Sheddable::Recursive)
+            && let Err(e) = recursive::update(
+                recursive_metrics,
+                &root.name,
+                &dir,
+                mode,
+                state.cfg.cgroup_recursive_max_depth,
+            )
+        {
+            log_anyhow_with_source!(e, root = %root.name, "updating recursive cgroup metrics failed");
+        }
+    }
+
+    // Cgroupy podle CGROUP_GLOB
+    if let (Some(glob_metrics), Some(glob)) = (&state.metrics.glob, &state.cfg.cgroup_glob)
+        && !budget::is_shed(Sheddable::Glob)
+        && let Err(e) = glob::update(glob_metrics, &state.cfg.cgroup_root, glob)
+    {
+        log_anyhow_with_source!(e, pattern = %glob.pattern, "updating cgroup glob metrics failed");
+    }
+
+    // Per-kontejnerové metriky z potomků pod cgroupy
+    if let Some(ref containers) = state.metrics.containers
+        && !budget::is_shed(Sheddable::Containers)
+    {
+        let pod_root = if state.cfg.pod_cgroup_discover {
+            containers::own_pod_root(&state.cfg.sys_root.join("fs/cgroup"))
+        } else {
+            Ok(state.cfg.cgroup_root.clone())
+        };
+        if let Err(e) = pod_root.and_then(|root| containers::update(containers, &root)) {
+            log_anyhow_with_source!(e, "updating container metrics failed");
+        }
+    }
+
+    // Process metrics - nově umí Single PID, list PIDů i regexp
+    if let Some(ref target) = state.cfg.process_target
+        && let Err(e) = procfs_mod::update_for_target(
+            &state.metrics.process,
+            state
+                .metrics
+                .per_pid
+                .as_ref()
+                .filter(|_| !budget::is_shed(Sheddable::PerPid)),
+            state
+                .metrics
+                .taskstats
+                .as_ref()
+                .filter(|_| !budget::is_shed(Sheddable::PerPid)),
+            &state.cfg.proc_root,
+            target,
+            state.cfg.follow_children,
+        )
+    {
+        log_anyhow_with_source!(e, "updating proc metrics failed");
+    }
+
+    // Pojmenované skupiny procesů (TARGET_GROUPS)
+    if !state.metrics.process_groups.is_empty()
+        && let Err(e) = procgroups::update(
+            &state.metrics.process_groups,
+            &state.cfg.proc_root,
+            &state.cfg.target_groups,
+        )
+    {
+        log_anyhow_with_source!(e, "updating process group metrics failed");
+    }
+
+    // Node režim - procesy všech podů na nodu
+    if let Some(ref pods) = state.metrics.pod_processes
+        && !budget::is_shed(Sheddable::PodProcesses)
+        && let Err(e) = nodepods::update(pods, &state.cfg.proc_root, &state.cfg.pods_log_dir)
+    {
+        log_anyhow_with_source!(e, "updating per-pod process metrics failed");
+    }
+
+    // Sourozenecké kontejnery ve sdíleném PID namespace
+    if let Some(ref siblings) = state.metrics.siblings
+        && !budget::is_shed(Sheddable::Siblings)
+        && let Err(e) = siblings::update(
+            siblings,
+            &state.cfg.proc_root,
+            &state.cfg.containers_log_dir,
+        )
+    {
+        log_anyhow_with_source!(e, "updating sibling container metrics failed");
     }
 
     // Host (node) metrics - /proc/stat + /proc/meminfo
-    if let Err(e) = host_mod::update(&state.metrics.host) {
+    if let Err(e) = host_mod::update(&state.metrics.host, &state.cfg.proc_root) {
         log_anyhow_with_source!(e, "updating host metrics failed");
     }
 
-    // TCP stack metrics - /proc/net/tcp{,6}
-    if let Err(e) = tcp_mod::update(&state.metrics.tcp) {
+    // TCP stack metrics - /proc/net/tcp{,6}; po vypnutí per-peer počty nesbírat
+    let peers: &[_] = if budget::is_shed(Sheddable::PeerTcp) {
+        &[]
+    } else {
+        &state.cfg.peer_cidrs
+    };
+    if let Err(e) = tcp_mod::update(&state.metrics.tcp, &state.cfg.proc_root, peers) {
         log_anyhow_with_source!(e, "updating tcp metrics failed");
     }
 
-    // Network metrics (per-interface throughput)
-    if let Err(e) = net_mod::update(&state.metrics.net, &state.cfg.net_interface) {
+    // Expirace certifikátů ze secretů
+    if let Some(ref expiry) = state.metrics.cert_expiry
+        && let Err(e) = certs::update(expiry, &state.cfg.cert_paths)
+    {
+        log_anyhow_with_source!(e, "updating certificate metrics failed");
+    }
+
+    // Využití volumů - volitelně v mount namespace cílového procesu
+    if let Some(ref fs) = state.metrics.filesystem {
+        let target_pid = match state.cfg.process_target {
+            Some(ref target) if state.cfg.fs_target_mount_ns => {
+                procfs_mod::resolve_target(&state.cfg.proc_root, target)
+                    .ok()
+                    .and_then(|m| m.first().map(|m| m.pid))
+            }
+            _ => None,
+        };
+        if state.cfg.fs_target_mount_ns && target_pid.is_none() {
+            warn!(
+                "FS_TARGET_MOUNT_NS is set but no target process was found, skipping filesystem metrics"
+            );
+        } else if let Err(e) =
+            filesystem::update(fs, &state.cfg.proc_root, &state.cfg.fs_paths, target_pid)
+        {
+            log_anyhow_with_source!(e, "updating filesystem metrics failed");
+        }
+    }
+
+    // Heartbeat / pid soubory z WATCH_FILES
+    if let Some(ref watch) = state.metrics.watch_files {
+        files::update(watch, &state.cfg.watch_files);
+    }
+
+    // Lokální HTTP/TCP probe
+    if let Some(ref probes) = state.metrics.probes {
+        probes::update(
+            probes,
+            &state.cfg.probe_http,
+            &state.cfg.probe_tcp,
+            &state.cfg.probe_dns,
+            state.cfg.probe_timeout,
+        );
+    }
+
+    // Saturace blokových zařízení volumů
+    if let Some(ref block) = state.metrics.block_devices
+        && let Err(e) = blockdev::update(block, &state.cfg.sys_root, &state.cfg.block_devices)
+    {
+        log_anyhow_with_source!(e, "updating block device metrics failed");
+    }
+
+    // tc qdisc/class statistiky (shaping, dropy)
+    if let Some(ref tc) = state.metrics.tc
+        && let Err(e) = tc::update(tc, &state.cfg.tc_interfaces)
+    {
+        log_anyhow_with_source!(e, "updating tc metrics failed");
+    }
+
+    // Network metrics (per-interface throughput), z netns cílového procesu
+    let net_pid = state
+        .cfg
+        .process_target
+        .as_ref()
+        .and_then(|target| procfs_mod::resolve_target(&state.cfg.proc_root, target).ok())
+        .and_then(|matches| matches.first().map(|m| m.pid));
+    if let Err(e) = net_mod::update(
+        &state.metrics.net,
+        &state.cfg.sys_root,
+        &state.cfg.proc_root,
+        net_pid,
+        &state.cfg.net_interface,
+    ) {
         log_anyhow_with_source!(
             e,
             iface = %state.cfg.net_interface,
             "updating net metrics failed"
         );
     }
-
-    Ok(())
 }
 
 async fn handle_request(
@@ -136,30 +697,170 @@ async fn handle_request(
     state: Arc<AppState>,
 ) -> Result<Response<Full<Bytes>>, Infallible> {
     let path = req.uri().path();
+    let http = &state.metrics.http;
+    http.in_flight.inc();
+    let start = Instant::now();
 
-    let resp = match path {
-        "/metrics" => metrics_response(&state),
-        "/healthz" => healthz_response(),
-        _ => not_found_response(),
+    // label path jen ze známých cest, ať skenery nenafouknou kardinalitu
+    let (path_label, resp) = match path {
+        "/metrics" => ("/metrics", metrics_response(&state)),
+        "/healthz" => ("/healthz", healthz_response()),
+        "/targets" => (
+            "/targets",
+            blocking_response(&state, targets_response).await,
+        ),
+        "/debug/cgroup" => ("/debug/cgroup", debug_cgroup_response(&state)),
+        "/delta" => ("/delta", delta_response()),
+        "/tree" => ("/tree", blocking_response(&state, tree_response).await),
+        _ => ("other", not_found_response()),
     };
 
+    http.in_flight.dec();
+    http.request_duration_seconds
+        .with_label_values(&[path_label])
+        .observe(start.elapsed().as_secs_f64());
+    http.requests_total
+        .with_label_values(&[path_label, resp.status().as_str()])
+        .inc();
+    if let Some(size) = resp.body().size_hint().exact() {
+        http.response_size_bytes
+            .with_label_values(&[path_label])
+            .observe(size as f64);
+    }
+
     Ok(resp)
 }
 
-fn metrics_response(state: &AppState) -> Response<Full<Bytes>> {
-    debug!("scrape requested");
+/// Spustí handler, který prochází /proc, na blocking poolu - na 1 CPU by
+/// jinak zdržel async workery a s nimi i /metrics.
+async fn blocking_response(
+    state: &Arc<AppState>,
+    handler: fn(&AppState) -> Response<Full<Bytes>>,
+) -> Response<Full<Bytes>> {
+    let st = Arc::clone(state);
+    match tokio::task::spawn_blocking(move || handler(&st)).await {
+        Ok(resp) => resp,
+        Err(e) => {
+            log_error_display!(e, "request handler task panicked");
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header("Content-Type", "text/plain; charset=utf-8")
+                .body(Full::new(Bytes::from_static(b"internal error\n")))
+                .unwrap()
+        }
+    }
+}
+
+/// Zakóduje aktuální stav registry do textového exposition formátu.
+/// Vrací buffer a Content-Type.
+fn encode_metrics(metrics: &Metrics) -> (Vec<u8>, String) {
     let encoder = TextEncoder::new();
-    let metric_families = state.metrics.registry.gather();
+    let metric_families = metrics.registry.gather();
 
     let mut buffer = Vec::new();
     if let Err(e) = encoder.encode(&metric_families, &mut buffer) {
         log_error_display!(e, "could not encode metrics");
     }
 
+    (buffer, encoder.format_type().to_string())
+}
+
+fn metrics_response(state: &AppState) -> Response<Full<Bytes>> {
+    debug!("scrape requested");
+
+    // METRICS_CACHE_SECONDS: kódovat jen po změně dat, klientům max-age
+    let Some(max_age) = state.cfg.metrics_cache_seconds else {
+        let (buffer, content_type) = encode_metrics(&state.metrics);
+        return Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", content_type)
+            .body(Full::new(Bytes::from(buffer)))
+            .unwrap();
+    };
+
+    let (body, content_type) = cache::get_or_encode(|| encode_metrics(&state.metrics));
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", content_type)
+        .header("Cache-Control", format!("max-age={max_age}"))
+        .body(Full::new(body))
+        .unwrap()
+}
+
+/// GET /targets - které PIDy aktuálně matchuje ProcessTarget a proč.
+fn targets_response(state: &AppState) -> Response<Full<Bytes>> {
+    debug!("targets requested");
+    let Some(ref target) = state.cfg.process_target else {
+        return json_response(&serde_json::json!({ "target": null, "processes": [] }));
+    };
+
+    match procfs_mod::describe_targets(&state.cfg.proc_root, target, state.cfg.follow_children) {
+        Ok(processes) => json_response(&serde_json::json!({
+            "target": target.describe(),
+            "processes": processes,
+        })),
+        Err(e) => {
+            log_anyhow_with_source!(e, "resolving process targets failed");
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header("Content-Type", "text/plain; charset=utf-8")
+                .body(Full::new(Bytes::from(format!("{e:#}\n"))))
+                .unwrap()
+        }
+    }
+}
+
+/// GET /debug/cgroup - co přesně kernel v cgroup souborech právě reportuje.
+fn debug_cgroup_response(state: &AppState) -> Response<Full<Bytes>> {
+    debug!("debug cgroup requested");
+    let root = &effective_cgroup_root(state);
+    json_response(&serde_json::json!({
+        "root": root.display().to_string(),
+        "files": cgroup_mod::read_raw(root),
+    }))
+}
+
+/// GET /delta - o kolik se každá metrika změnila za poslední cyklus.
+fn delta_response() -> Response<Full<Bytes>> {
+    debug!("delta requested");
+    json_response(&serde_json::json!(changes::last()))
+}
+
+/// GET /tree - strom procesů pod cílovými PIDy (jako `ps --forest`).
+fn tree_response(state: &AppState) -> Response<Full<Bytes>> {
+    debug!("process tree requested");
+    let Some(ref target) = state.cfg.process_target else {
+        return json_response(&serde_json::json!({ "target": null, "processes": [] }));
+    };
+
+    let result = procfs_mod::resolve_target(&state.cfg.proc_root, target).and_then(|matches| {
+        let roots: Vec<i32> = matches.iter().map(|m| m.pid).collect();
+        tree::build(&state.cfg.proc_root, &roots)
+    });
+
+    match result {
+        Ok((interval_seconds, processes)) => json_response(&serde_json::json!({
+            "target": target.describe(),
+            "interval_seconds": interval_seconds,
+            "processes": processes,
+        })),
+        Err(e) => {
+            log_anyhow_with_source!(e, "building process tree failed");
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header("Content-Type", "text/plain; charset=utf-8")
+                .body(Full::new(Bytes::from(format!("{e:#}\n"))))
+                .unwrap()
+        }
+    }
+}
+
+fn json_response(value: &serde_json::Value) -> Response<Full<Bytes>> {
+    let body = serde_json::to_vec_pretty(value).unwrap_or_default();
     Response::builder()
         .status(StatusCode::OK)
-        .header("Content-Type", encoder.format_type())
-        .body(Full::new(Bytes::from(buffer)))
+        .header("Content-Type", "application/json")
+        .body(Full::new(Bytes::from(body)))
         .unwrap()
 }
 
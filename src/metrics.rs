@@ -1,9 +1,24 @@
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
 use anyhow::{Context, Result};
-use prometheus::{Gauge, GaugeVec, IntGauge, IntGaugeVec, Opts, Registry};
-
-use crate::config::Config;
+use prometheus::{
+    Gauge, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    Opts, Registry,
+    core::{
+        Atomic, AtomicF64, AtomicI64, Collector, GenericGauge, GenericGaugeVec, MetricVec,
+        MetricVecBuilder,
+    },
+    proto::{Metric, MetricType},
+};
+
+use crate::{
+    config::{Config, RecursiveMode},
+    exec::ExecCollector,
+};
 
 #[allow(dead_code)]
 pub struct ResourceMetrics {
@@ -13,31 +28,254 @@ pub struct ResourceMetrics {
     pub memory_limits_bytes: Gauge,
 }
 
+/// Gauge s maximem za klouzavé okno (PEAK_WINDOWS), např. *_peak_5m.
+pub struct WindowGauge {
+    pub label: String,
+    pub window: Duration,
+    pub gauge: Gauge,
+}
+
+/// Label hodnoty sérií, které collector v tomto cyklu nastavil.
+///
+/// Série, které zmizely (proces skončil, cgroupa zanikla), se mažou až po
+/// naplnění přes `prune`. Reset vektoru před čtením /proc by nechal
+/// scrape, který trefí update uprostřed, úplně bez sérií - Prometheus pak
+/// zapíše staleness a rozbije rate().
+#[derive(Default)]
+pub struct LiveSeries(HashSet<Vec<String>>);
+
+impl LiveSeries {
+    pub fn insert(&mut self, labels: &[&str]) {
+        self.0
+            .insert(labels.iter().map(|l| l.to_string()).collect());
+    }
+
+    /// Smaže z vektoru série, které v tomto cyklu nikdo nenastavil.
+    pub fn prune<T: MetricVecBuilder>(&self, vec: &MetricVec<T>) {
+        for values in series_label_values(vec) {
+            if !self.0.contains(&values) {
+                let values: Vec<&str> = values.iter().map(String::as_str).collect();
+                let _ = vec.remove_label_values(&values);
+            }
+        }
+    }
+}
+
+/// Hodnoty variabilních labelů všech sérií vektoru (v pořadí deklarace).
+fn series_label_values<T: MetricVecBuilder>(vec: &MetricVec<T>) -> Vec<Vec<String>> {
+    let labels = vec
+        .desc()
+        .first()
+        .map(|d| d.variable_labels.clone())
+        .unwrap_or_default();
+    vec.collect()
+        .iter()
+        .flat_map(|family| family.get_metric())
+        .map(|metric| {
+            let pairs: HashMap<&str, &str> = metric
+                .get_label()
+                .iter()
+                .map(|lp| (lp.name(), lp.value()))
+                .collect();
+            labels
+                .iter()
+                .map(|l| pairs.get(l.as_str()).copied().unwrap_or("").to_string())
+                .collect()
+        })
+        .collect()
+}
+
+/// Vektor, jehož první label je navázaný na jednu cgroup root (label root,
+/// CGROUP_ROOTS) nebo skupinu procesů (label group, TARGET_GROUPS) -
+/// volající předává jen zbylé labely. Nenavázaný se chová jako obyčejný vec.
+#[derive(Clone)]
+pub struct RootVec<P: Atomic> {
+    vec: GenericGaugeVec<P>,
+    root: Option<String>,
+    /// Jména variabilních labelů vektoru (navázaný první)
+    labels: Vec<String>,
+}
+
+pub type RootGaugeVec = RootVec<AtomicF64>;
+pub type RootIntGaugeVec = RootVec<AtomicI64>;
+
+impl<P: Atomic> RootVec<P> {
+    pub fn new(vec: &GenericGaugeVec<P>, root: &str) -> Self {
+        Self::bound(vec, Some(root))
+    }
+
+    pub fn bound(vec: &GenericGaugeVec<P>, root: Option<&str>) -> Self {
+        let labels = vec
+            .desc()
+            .first()
+            .map(|d| d.variable_labels.clone())
+            .unwrap_or_default();
+        Self {
+            vec: vec.clone(),
+            root: root.map(str::to_string),
+            labels,
+        }
+    }
+
+    pub fn with_label_values(&self, labels: &[&str]) -> GenericGauge<P> {
+        let mut values = Vec::with_capacity(labels.len() + 1);
+        values.extend(self.root.as_deref());
+        values.extend_from_slice(labels);
+        self.vec.with_label_values(&values)
+    }
+
+    /// Smaže jen série této root; ostatní roots zůstanou.
+    pub fn reset(&self) {
+        let (Some(root), Some(root_label)) = (&self.root, self.labels.first()) else {
+            self.vec.reset();
+            return;
+        };
+        for family in self.vec.collect() {
+            for metric in family.get_metric() {
+                let pairs: HashMap<&str, &str> = metric
+                    .get_label()
+                    .iter()
+                    .map(|lp| (lp.name(), lp.value()))
+                    .collect();
+                if pairs.get(root_label.as_str()) != Some(&root.as_str()) {
+                    continue;
+                }
+                let values: Vec<&str> = self
+                    .labels
+                    .iter()
+                    .map(|l| pairs.get(l.as_str()).copied().unwrap_or(""))
+                    .collect();
+                let _ = self.vec.remove_label_values(&values);
+            }
+        }
+    }
+
+    /// Jako `LiveSeries::prune`, ale jen nad sériemi této root; `live`
+    /// obsahuje labely bez navázaného prvního.
+    pub fn prune(&self, live: &LiveSeries) {
+        let Some(ref root) = self.root else {
+            live.prune(&self.vec);
+            return;
+        };
+        for values in series_label_values(&self.vec) {
+            if values.first() != Some(root) || live.0.contains(&values[1..]) {
+                continue;
+            }
+            let values: Vec<&str> = values.iter().map(String::as_str).collect();
+            let _ = self.vec.remove_label_values(&values);
+        }
+    }
+}
+
 pub struct CgroupMetrics {
+    /// Jméno root z CGROUP_ROOTS - hodnota labelu root u všech sérií
+    pub root: String,
+    /// Ze které cgroupy se právě čte, labely path + mode (hierarchie)
+    pub info: RootIntGaugeVec,
     pub cpu_usage_seconds: Gauge,
     pub cpu_user_seconds: Gauge,
     pub cpu_system_seconds: Gauge,
     pub cpu_nr_periods: IntGauge,
     pub cpu_nr_throttled: IntGauge,
     pub cpu_throttled_seconds: Gauge,
+    /// CFS burst (5.14+): kolikrát a jak dlouho cgroupa čerpala cpu.max.burst
+    pub cpu_nr_bursts: IntGauge,
+    pub cpu_burst_seconds: Gauge,
+    pub cpu_max_burst_seconds: Gauge,
+    /// Core scheduling: čas, kdy sourozenecký SMT thread musel stát (forceidle)
+    pub cpu_core_sched_force_idle_seconds: Gauge,
+    /// cpu.max.burst / perioda z cpu.max - burst přepočtený na jádra
+    pub cpu_burst_cores: Gauge,
+    pub cpu_throttled_ratio: Gauge,
+    pub cpu_saturation_ratio: Gauge,
     pub cpu_limit_cores: Gauge,
+    pub cpu_utilization_ratio: Gauge,
+    /// cpu.uclamp.{min,max} v procentech, label bound; jen když soubory existují
+    pub cpu_uclamp_percent: RootGaugeVec,
+    pub cpu_idle: IntGauge,
+    /// Počet efektivně dostupných CPU / NUMA nodů (cpuset.*.effective)
+    pub cpuset_cpus: IntGauge,
+    pub cpuset_mems: IntGauge,
+    /// Seznamy CPU a nodů tak, jak jsou v cpuset.*.effective, labely cpus + mems
+    pub cpuset_info: RootIntGaugeVec,
+    /// PSI z {cpu,memory,io}.pressure: labely resource, kind (some|full), window
+    pub pressure_avg_percent: RootGaugeVec,
+    /// PSI total stall, labely resource, kind
+    pub pressure_stall_seconds: RootGaugeVec,
+    /// Odhad host steal time připadající na cgroupu (akumulovaný)
+    pub cpu_steal_impact_seconds: Gauge,
+    /// Jen s DERIVED_RATES
+    pub cpu_usage_cores: Option<Gauge>,
+    pub cpu_usage_cores_peaks: Vec<WindowGauge>,
 
     pub mem_current_bytes: Gauge,
+    pub mem_current_peaks: Vec<WindowGauge>,
     pub mem_peak_bytes: Gauge,
     pub mem_max_bytes: Gauge,
     pub mem_high_bytes: Gauge,
     pub mem_low_bytes: Gauge,
-    pub mem_events_total: IntGaugeVec,
+    pub mem_min_bytes: Gauge,
+    pub mem_working_set_bytes: Gauge,
+    pub mem_headroom_bytes: Gauge,
+    pub mem_events_total: RootIntGaugeVec,
+    /// memory.events.local - jen události této cgroupy, bez potomků
+    pub mem_events_local_total: RootIntGaugeVec,
+    pub mem_swap_current_bytes: Gauge,
+    pub mem_swap_max_bytes: Gauge,
+    pub mem_swap_high_bytes: Gauge,
+    /// memory.swap.events (high, max, fail), label type
+    pub mem_swap_events_total: RootIntGaugeVec,
+    /// zswap (komprimovaná paměť), jen když kernel soubory má
+    pub mem_zswap_current_bytes: Gauge,
+    pub mem_zswap_max_bytes: Gauge,
+    /// Všechny položky memory.stat (anon, file, slab_*, pgfault, ...), label stat
+    pub mem_stat: RootGaugeVec,
+    /// Thrashing signály z memory.stat s vlastními jmény; refault/activate
+    /// s labelem type (anon|file)
+    pub mem_workingset_refault_total: RootGaugeVec,
+    pub mem_workingset_activate_total: RootGaugeVec,
+    pub mem_pgscan_total: Gauge,
+    pub mem_pgsteal_total: Gauge,
+
+    // misc controller (SEV/SEV-ES ASIDs, TDX klíče, ...), label resource
+    pub misc_current: RootGaugeVec,
+    pub misc_max: RootGaugeVec,
+
+    /// cgroup.events frozen (skutečně zmrazeno) a cgroup.freeze (požadavek)
+    pub frozen: IntGauge,
+    pub freeze_requested: IntGauge,
+
+    /// Dostupné controllery, labely controller + scope (self = cgroup.controllers
+    /// nebo v1 mount, subtree = cgroup.subtree_control) + hierarchy (v1|v2)
+    pub controller_enabled: RootIntGaugeVec,
+
+    // cgroup.stat - nr_dying_descendants roste při leaku umírajících cgroup
+    pub descendants: IntGauge,
+    pub dying_descendants: IntGauge,
+
+    // io.stat per zařízení, label device (maj:min)
+    pub io_read_bytes: RootGaugeVec,
+    pub io_write_bytes: RootGaugeVec,
+    pub io_reads: RootGaugeVec,
+    pub io_writes: RootGaugeVec,
+    pub io_discard_bytes: RootGaugeVec,
 }
 
 pub struct ProcessMetrics {
+    /// Jméno skupiny z TARGET_GROUPS (None = TARGET_PID*) - odděluje stav
+    /// offsetů, delt a historie mezi sadami
+    pub scope: Option<String>,
     pub cpu_user_seconds: Gauge,
     pub cpu_system_seconds: Gauge,
     pub start_time_seconds: Gauge,
 
     pub mem_rss_bytes: Gauge,
+    pub mem_rss_peaks: Vec<WindowGauge>,
     pub mem_vms_bytes: Gauge,
     pub mem_swap_bytes: Gauge,
+    /// Max VmHWM / VmPeak přes členy skupiny
+    pub mem_rss_hwm_bytes: Gauge,
+    pub mem_vms_peak_bytes: Gauge,
 
     // IO z /proc/<pid>/io
     pub io_rchar_bytes_total: Gauge,
@@ -48,7 +286,247 @@ pub struct ProcessMetrics {
     pub io_write_bytes_total: Gauge,
     pub io_cancelled_write_bytes_total: Gauge,
 
+    /// minflt / majflt (pole 10 a 12 /proc/<pid>/stat)
+    pub minor_page_faults_total: Gauge,
+    pub major_page_faults_total: Gauge,
+
+    /// voluntary / nonvoluntary_ctxt_switches z /proc/<pid>/status
+    pub voluntary_ctxt_switches_total: Gauge,
+    pub nonvoluntary_ctxt_switches_total: Gauge,
+
     pub uptime_seconds: Gauge, // <- NOVÉ
+    /// Zmizelé nebo znovu spuštěné PIDy skupiny (změněný start time)
+    pub restarts_total: IntCounter,
+    /// Počet PIDů, které do agregace v posledním updatu přispěly
+    pub target_matched: IntGauge,
+    /// Součet Threads ze /proc/<pid>/status přes skupinu
+    pub threads: Gauge,
+    /// Nejvyšší oom_score ve skupině (kandidát na OOM kill) a jeho oom_score_adj
+    pub oom_score: Gauge,
+    pub oom_score_adj: Gauge,
+    /// Počet procesů skupiny per stav (pole 3 /proc/<pid>/stat), label state
+    pub states: RootIntGaugeVec,
+    /// TCP porty, na kterých cílové procesy poslouchají, labely port + proto
+    pub listening_ports: RootIntGaugeVec,
+    /// Součet otevřených fd; limit = nejnižší soft NOFILE ve skupině,
+    /// utilization = nejhorší poměr jednoho procesu
+    pub open_fds: Gauge,
+    pub max_fds: Gauge,
+    pub fds_utilization_ratio: Gauge,
+    /// Limity z /proc/<pid>/limits (nejpřísnější ve skupině), labely
+    /// resource + type (soft|hard)
+    pub resource_limits: RootGaugeVec,
+
+    /// Podíl updatů v okně AVAILABILITY_WINDOW, kdy existoval aspoň jeden proces
+    pub availability: WindowGauge,
+
+    /// Jen s DERIVED_RATES
+    pub cpu_usage_cores: Option<Gauge>,
+    /// Jen s DERIVED_RATES
+    pub io_rates: Option<ProcessIoRateMetrics>,
+    /// Jen s PROCESS_THREAD_CPU, label thread
+    pub thread_cpu_seconds: Option<RootGaugeVec>,
+    /// Jen s PROCESS_SMAPS
+    pub smaps: Option<SmapsMetrics>,
+    /// Jen s PROCESS_STALLS
+    pub stalls: Option<StallMetrics>,
+}
+
+/// Vlákna skupiny v uninterruptible sleep (D). Doba zaseknutí se měří od
+/// prvního updatu, kdy bylo vlákno vidět v D, bez přerušení.
+pub struct StallMetrics {
+    pub uninterruptible_tasks: IntGauge,
+    pub longest_stall_seconds: Gauge,
+    /// Nejdéle zaseklé vlákno (labely pid, tid, comm, wchan), hodnota 1
+    pub stalled_task_info: RootIntGaugeVec,
+}
+
+/// IO rychlosti skupiny z rozdílů monotónních totalů mezi updaty.
+pub struct ProcessIoRateMetrics {
+    pub read_bytes_per_second: Gauge,
+    pub write_bytes_per_second: Gauge,
+}
+
+/// Paměť ze /proc/<pid>/smaps_rollup sečtená přes skupinu. Na rozdíl od
+/// součtu RSS se sdílené stránky nepočítají vícekrát.
+pub struct SmapsMetrics {
+    pub pss_bytes: Gauge,
+    pub pss_anon_bytes: Gauge,
+    /// USS = Private_Clean + Private_Dirty
+    pub uss_bytes: Gauge,
+    pub private_clean_bytes: Gauge,
+    pub private_dirty_bytes: Gauge,
+    /// Shared_Clean + Shared_Dirty
+    pub shared_bytes: Gauge,
+}
+
+/// Min/max/avg rychlých vzorků za update interval (FAST_SAMPLE_INTERVAL_MS).
+/// Label stat="min|max|avg".
+pub struct FastSampleMetrics {
+    pub mem_current_bytes: GaugeVec,
+    pub procs_running: GaugeVec,
+}
+
+/// Per-kontejnerové metriky v POD_CGROUP_MODE, label container (zkrácené ID).
+pub struct ContainerMetrics {
+    pub cpu_usage_seconds: GaugeVec,
+    pub cpu_throttled_seconds: GaugeVec,
+    pub mem_current_bytes: GaugeVec,
+    pub mem_max_bytes: GaugeVec,
+}
+
+/// Potomci cgroupy (CGROUP_RECURSIVE). Per-child: labely root + cgroup,
+/// aggregate: jen root (součet přes listy).
+pub struct RecursiveMetrics {
+    pub cpu_usage_seconds: GaugeVec,
+    pub cpu_throttled_seconds: GaugeVec,
+    pub mem_current_bytes: GaugeVec,
+    /// Počet prohledaných potomků
+    pub cgroups: IntGaugeVec,
+    /// Kolik z nich je zmrazených (cgroup.events frozen)
+    pub frozen_children: IntGaugeVec,
+}
+
+/// Cgroupy odpovídající CGROUP_GLOB, label path (relativně ke CGROUP_ROOT).
+pub struct GlobMetrics {
+    pub cpu_usage_seconds: GaugeVec,
+    pub cpu_throttled_seconds: GaugeVec,
+    pub mem_current_bytes: GaugeVec,
+    pub matches: IntGauge,
+}
+
+/// Skutečné OOM countery z inotify watche memory.events (OOM_WATCH).
+#[derive(Clone)]
+pub struct OomMetrics {
+    /// Labely root + type (oom, oom_kill)
+    pub events_total: IntCounterVec,
+    pub last_oom_kill_timestamp_seconds: GaugeVec,
+}
+
+/// Překročení PSI triggerů (PSI_TRIGGERS), labely root, resource, kind,
+/// threshold.
+pub struct PsiTriggerMetrics {
+    pub events_total: IntCounterVec,
+}
+
+/// Výsledek reclaim sondy (MEMORY_RECLAIM_PROBE_BYTES), label root.
+pub struct ReclaimMetrics {
+    pub requested_bytes: IntGauge,
+    pub reclaimed_bytes: GaugeVec,
+    /// 1 = kernel uvolnil celé požadované množství, 0 = EAGAIN
+    pub complete: IntGaugeVec,
+    pub last_run_timestamp_seconds: GaugeVec,
+}
+
+/// Série per proces cílové skupiny (PROCESS_METRICS_MODE=per_pid), labely
+/// pid + comm. Ukončené procesy se každý update zahodí.
+pub struct PidMetrics {
+    pub cpu_user_seconds: GaugeVec,
+    pub cpu_system_seconds: GaugeVec,
+    pub start_time_seconds: GaugeVec,
+    pub mem_rss_bytes: GaugeVec,
+    pub mem_vms_bytes: GaugeVec,
+    pub mem_swap_bytes: GaugeVec,
+    pub io_read_bytes_total: GaugeVec,
+    pub io_write_bytes_total: GaugeVec,
+    /// Popis procesu (labely pid, comm, exe, uid, cmdline), hodnota 1
+    pub info: IntGaugeVec,
+}
+
+/// Delay accounting per cílový proces z taskstats netlinku (TASKSTATS),
+/// labely pid, comm, type = cpu | blkio | swapin.
+pub struct TaskstatsMetrics {
+    pub delay_seconds_total: GaugeVec,
+    pub delays_total: GaugeVec,
+}
+
+/// Agregace procesů per pod v NODE_MODE, labely namespace, pod, pod_uid.
+pub struct PodProcessMetrics {
+    pub processes: IntGaugeVec,
+    pub cpu_seconds: GaugeVec,
+    pub rss_bytes: GaugeVec,
+}
+
+/// Agregace procesů per kontejner ve sdíleném PID namespace
+/// (SIBLING_CONTAINERS), label container.
+pub struct SiblingMetrics {
+    pub processes: IntGaugeVec,
+    pub cpu_seconds: GaugeVec,
+    pub rss_bytes: GaugeVec,
+}
+
+/// Výsledky PROBE_HTTP / PROBE_TCP / PROBE_DNS, labely type + target.
+pub struct ProbeMetrics {
+    pub success: IntGaugeVec,
+    pub duration_seconds: GaugeVec,
+    /// Jen HTTP, label target; 0 když request selhal
+    pub http_status_code: IntGaugeVec,
+}
+
+/// Stav příkazů EXEC_COMMANDS (label command) + jejich vlastní metriky.
+pub struct ExecMetrics {
+    pub success: IntGaugeVec,
+    pub duration_seconds: GaugeVec,
+    pub collector: ExecCollector,
+}
+
+/// tc qdisc/class statistiky (TC_INTERFACES), labely interface, object
+/// (qdisc|class), kind, handle, parent.
+pub struct TcMetrics {
+    pub sent_bytes: GaugeVec,
+    pub sent_packets: GaugeVec,
+    pub drops: GaugeVec,
+    pub overlimits: GaugeVec,
+    pub requeues: GaugeVec,
+    pub backlog_bytes: GaugeVec,
+    pub backlog_packets: GaugeVec,
+}
+
+/// Obsluha HTTP exporterem samotným - odliší "Prometheus přestal scrapovat"
+/// od "exporter přestal odpovídat". Label path je jen ze známých cest.
+pub struct HttpMetrics {
+    /// labely path + status
+    pub requests_total: IntCounterVec,
+    pub in_flight: IntGauge,
+    /// label path
+    pub request_duration_seconds: HistogramVec,
+    /// label path
+    pub response_size_bytes: HistogramVec,
+}
+
+/// Paměťový rozpočet exporteru (SELF_MEMORY_BUDGET_BYTES).
+pub struct MemoryBudgetMetrics {
+    pub rss_bytes: IntGauge,
+    pub budget_bytes: IntGauge,
+    /// 1 = collector byl kvůli rozpočtu vypnut, label collector
+    pub collector_shed: IntGaugeVec,
+}
+
+/// Využití filesystémů z FS_PATHS (statvfs), label path.
+pub struct FilesystemMetrics {
+    pub size_bytes: GaugeVec,
+    pub avail_bytes: GaugeVec,
+    pub free_bytes: GaugeVec,
+    pub files: GaugeVec,
+    pub files_free: GaugeVec,
+}
+
+/// Saturace blokových zařízení z BLOCK_DEVICES, label device.
+pub struct BlockDeviceMetrics {
+    pub in_flight: GaugeVec,
+    /// labely device + direction (read|write)
+    pub in_flight_by_direction: GaugeVec,
+    pub io_time_seconds: GaugeVec,
+    pub time_in_queue_seconds: GaugeVec,
+    pub utilization_ratio: GaugeVec,
+    pub avg_queue_depth: GaugeVec,
+}
+
+/// Sledované soubory z WATCH_FILES, label path.
+pub struct WatchFileMetrics {
+    pub exists: IntGaugeVec,
+    pub size_bytes: GaugeVec,
+    pub age_seconds: GaugeVec,
 }
 
 /// Síťové metriky pro jeden interface (NET_INTERFACE).
@@ -61,6 +539,17 @@ pub struct NetMetrics {
     pub tx_errors_total: Gauge,
     pub rx_dropped_total: Gauge,
     pub tx_dropped_total: Gauge,
+
+    /// Jen s DERIVED_RATES
+    pub rates: Option<NetRateMetrics>,
+}
+
+/// Odvozené rate metriky pro NET_INTERFACE (DERIVED_RATES).
+pub struct NetRateMetrics {
+    pub rx_bytes_per_second: Gauge,
+    pub tx_bytes_per_second: Gauge,
+    pub rx_packets_per_second: Gauge,
+    pub tx_packets_per_second: Gauge,
 }
 #[allow(dead_code)]
 pub struct HostMetrics {
@@ -85,11 +574,32 @@ pub struct HostMetrics {
 #[allow(dead_code)]
 pub struct TcpMetrics {
     pub connections: IntGaugeVec,
+    /// Jen s PEER_CIDRS: labely peer (jméno služby) + state
+    pub peer_connections: Option<IntGaugeVec>,
+}
+
+/// Metriky tokio runtime samotného exporteru (feature `runtime-metrics`).
+#[cfg(feature = "runtime-metrics")]
+pub struct RuntimeMetrics {
+    pub workers: IntGauge,
+    pub alive_tasks: IntGauge,
+    pub global_queue_depth: IntGauge,
+    /// Labels: worker="0".."N"
+    pub worker_busy_seconds_total: GaugeVec,
+    pub worker_park_total: IntGaugeVec,
+
+    #[cfg(tokio_unstable)]
+    pub blocking_threads: IntGauge,
+    #[cfg(tokio_unstable)]
+    pub blocking_queue_depth: IntGauge,
+    #[cfg(tokio_unstable)]
+    pub worker_mean_poll_seconds: GaugeVec,
 }
 
 pub struct Metrics {
     pub registry: Registry,
-    pub cgroup: CgroupMetrics,
+    /// Jedna sada per root z CGROUP_ROOTS (sdílené vektory, label root)
+    pub cgroups: Vec<CgroupMetrics>,
     pub process: ProcessMetrics,
     pub net: NetMetrics,
     #[allow(dead_code)]
@@ -98,8 +608,60 @@ pub struct Metrics {
     pub tcp: TcpMetrics,
     /// DownwardAPI info: field + value, vždy 1 sample
     pub downward_info: IntGaugeVec,
+    /// THRESHOLDS: 1 = pravidlo je v breachi, label name
+    pub threshold_breached: IntGaugeVec,
+    /// QoS class podu (z cgroup cesty nebo requests/limits), vždy 1 sample
+    pub qos_class_info: IntGaugeVec,
+    /// Totéž pod jménem a labelem `qos` v malých písmenech jako v cgroup cestě
+    pub kubernetes_qos_class_info: IntGaugeVec,
+    /// Self-check při startu (root, capabilities, read-only mounty), vždy 1 sample
+    pub security_info: IntGaugeVec,
+    /// Vlastní HTTP server (requesty, latence, velikost odpovědí)
+    pub http: HttpMetrics,
+    /// Jen s FAST_SAMPLE_INTERVAL_MS
+    pub fast_sample: Option<FastSampleMetrics>,
+    /// Jen s CERT_PATHS: notAfter certifikátu, labely path + subject
+    pub cert_expiry: Option<GaugeVec>,
+    /// Jen s POD_CGROUP_MODE / POD_CGROUP_DISCOVER
+    pub containers: Option<ContainerMetrics>,
+    /// Jen s CGROUP_RECURSIVE
+    pub recursive: Option<RecursiveMetrics>,
+    /// Jen s PSI_TRIGGERS
+    pub psi_triggers: Option<PsiTriggerMetrics>,
+    /// Jen s MEMORY_RECLAIM_PROBE_BYTES
+    pub reclaim: Option<ReclaimMetrics>,
+    /// Jen s OOM_WATCH
+    pub oom: Option<OomMetrics>,
+    /// Jen s CGROUP_GLOB
+    pub glob: Option<GlobMetrics>,
+    /// Jen s PROCESS_METRICS_MODE=per_pid
+    pub per_pid: Option<PidMetrics>,
+    /// Jen s TASKSTATS
+    pub taskstats: Option<TaskstatsMetrics>,
+    /// Jedna sada per skupina z TARGET_GROUPS (sdílené vektory, label group)
+    pub process_groups: Vec<ProcessMetrics>,
+    /// Jen s NODE_MODE
+    pub pod_processes: Option<PodProcessMetrics>,
+    /// Jen se SIBLING_CONTAINERS
+    pub siblings: Option<SiblingMetrics>,
+    /// Jen s PROBE_HTTP / PROBE_TCP / PROBE_DNS
+    pub probes: Option<ProbeMetrics>,
+    /// Jen s EXEC_COMMANDS
+    pub exec: Option<ExecMetrics>,
+    /// Jen s FS_PATHS
+    pub filesystem: Option<FilesystemMetrics>,
+    /// Jen s BLOCK_DEVICES
+    pub block_devices: Option<BlockDeviceMetrics>,
+    /// Jen s TC_INTERFACES
+    pub tc: Option<TcMetrics>,
+    /// Jen se SELF_MEMORY_BUDGET_BYTES
+    pub memory_budget: Option<MemoryBudgetMetrics>,
+    /// Jen s WATCH_FILES
+    pub watch_files: Option<WatchFileMetrics>,
     #[allow(dead_code)]
     pub resources: Option<ResourceMetrics>, // může být None, když env chybí
+    #[cfg(feature = "runtime-metrics")]
+    pub runtime: RuntimeMetrics,
 }
 
 fn gauge_with_const_label(
@@ -148,29 +710,220 @@ impl Metrics {
     pub fn new(cfg: &Config) -> Result<Self> {
         let registry = Registry::new_custom(None, None)?;
 
-        let cgroup = CgroupMetrics::new(&registry, cfg)?;
+        let cgroups = CgroupMetrics::new_for_roots(&registry, cfg)?;
         let process = ProcessMetrics::new(&registry, cfg)?;
         let net = NetMetrics::new(&registry, cfg)?;
         let host = HostMetrics::new(&registry, cfg)?;
         let tcp = TcpMetrics::new(&registry, cfg)?;
         let downward_info = downward_info_metric(&registry, cfg)?;
+
+        let threshold_breached = int_gauge_vec(
+            &registry,
+            cfg,
+            "exporter_threshold_breached",
+            "Whether the THRESHOLDS rule is currently breached (1) or not (0)",
+            &["name"],
+        )?;
+        for rule in &cfg.thresholds {
+            threshold_breached.with_label_values(&[&rule.name]).set(0);
+        }
+
+        let qos_class_info = int_gauge_vec(
+            &registry,
+            cfg,
+            "k8s_qos_class_info",
+            "Kubernetes QoS class of the pod derived from the kubepods cgroup path or from CPU/MEMORY requests and limits",
+            &["class"],
+        )?;
+        let kubernetes_qos_class_info = int_gauge_vec(
+            &registry,
+            cfg,
+            "kubernetes_qos_class_info",
+            "Kubernetes QoS class of the pod (lowercase, as in the kubepods cgroup path)",
+            &["qos"],
+        )?;
+
+        let security_info = int_gauge_vec(
+            &registry,
+            cfg,
+            "exporter_security_info",
+            "Security posture of the exporter detected at startup: effective uid 0, effective capabilities, read-only /proc and /sys",
+            &[
+                "run_as_root",
+                "capabilities",
+                "proc_read_only",
+                "sys_read_only",
+            ],
+        )?;
+
+        let http = HttpMetrics::new(&registry, cfg)?;
+
+        let fast_sample = if cfg.fast_sample_interval_ms.is_some() {
+            Some(FastSampleMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let cert_expiry = if cfg.cert_paths.is_empty() {
+            None
+        } else {
+            Some(gauge_vec_with_const_label(
+                &registry,
+                cfg,
+                "tls_certificate_expiry_timestamp_seconds",
+                "Expiry (notAfter) of PEM certificates from CERT_PATHS as unix timestamp",
+                &["path", "subject"],
+                None,
+            )?)
+        };
+        let containers = if cfg.pod_cgroup_mode || cfg.pod_cgroup_discover {
+            Some(ContainerMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let recursive = match cfg.cgroup_recursive {
+            Some(mode) => Some(RecursiveMetrics::new(&registry, cfg, mode)?),
+            None => None,
+        };
+        let psi_triggers = if cfg.psi_triggers.is_empty() {
+            None
+        } else {
+            Some(PsiTriggerMetrics::new(&registry, cfg)?)
+        };
+        let reclaim = if cfg.memory_reclaim_probe.is_some() {
+            Some(ReclaimMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let oom = if cfg.oom_watch {
+            Some(OomMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let glob = if cfg.cgroup_glob.is_some() {
+            Some(GlobMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let per_pid = if cfg.process_per_pid {
+            Some(PidMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let taskstats = if cfg.taskstats {
+            Some(TaskstatsMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let process_groups = ProcessMetrics::new_for_groups(&registry, cfg)?;
+        let pod_processes = if cfg.node_mode {
+            Some(PodProcessMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let siblings = if cfg.sibling_containers {
+            Some(SiblingMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let probes =
+            if cfg.probe_http.is_empty() && cfg.probe_tcp.is_empty() && cfg.probe_dns.is_empty() {
+                None
+            } else {
+                Some(ProbeMetrics::new(&registry, cfg)?)
+            };
+        let exec = if cfg.exec_commands.is_empty() {
+            None
+        } else {
+            Some(ExecMetrics::new(&registry, cfg)?)
+        };
+        let filesystem = if cfg.fs_paths.is_empty() {
+            None
+        } else {
+            Some(FilesystemMetrics::new(&registry, cfg)?)
+        };
+        let block_devices = if cfg.block_devices.is_empty() {
+            None
+        } else {
+            Some(BlockDeviceMetrics::new(&registry, cfg)?)
+        };
+        let tc = if cfg.tc_interfaces.is_empty() {
+            None
+        } else {
+            Some(TcMetrics::new(&registry, cfg)?)
+        };
+        let memory_budget = if cfg.memory_budget_bytes.is_some() {
+            Some(MemoryBudgetMetrics::new(&registry, cfg)?)
+        } else {
+            None
+        };
+        let watch_files = if cfg.watch_files.is_empty() {
+            None
+        } else {
+            Some(WatchFileMetrics::new(&registry, cfg)?)
+        };
         let resources = ResourceMetrics::new(&registry, cfg)?; // Option<…>
+        #[cfg(feature = "runtime-metrics")]
+        let runtime = RuntimeMetrics::new(&registry, cfg)?;
 
         Ok(Self {
             registry,
-            cgroup,
+            cgroups,
             process,
             net,
             host,
             tcp,
             downward_info,
+            threshold_breached,
+            qos_class_info,
+            kubernetes_qos_class_info,
+            security_info,
+            http,
+            fast_sample,
+            cert_expiry,
+            containers,
+            recursive,
+            glob,
+            oom,
+            reclaim,
+            psi_triggers,
+            pod_processes,
+            per_pid,
+            taskstats,
+            process_groups,
+            siblings,
+            probes,
+            exec,
+            filesystem,
+            block_devices,
+            tc,
+            memory_budget,
+            watch_files,
             resources,
+            #[cfg(feature = "runtime-metrics")]
+            runtime,
         })
     }
 }
 
 impl CgroupMetrics {
-    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+    /// Zaregistruje vektory s labelem `root` a pro každou root z CGROUP_ROOTS
+    /// vrátí sadu na ni navázaných metrik.
+    pub fn new_for_roots(registry: &Registry, cfg: &Config) -> Result<Vec<Self>> {
+        let gauge = |registry: &Registry, cfg: &Config, name: &str, help: &str| {
+            gauge_vec_with_const_label(registry, cfg, name, help, &["root"], None)
+        };
+        let int_gauge = |registry: &Registry, cfg: &Config, name: &str, help: &str| {
+            int_gauge_vec(registry, cfg, name, help, &["root"])
+        };
+
+        let info = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_info",
+            "Cgroup directory the cgroup metrics are read from and the detected hierarchy (unified, hybrid, legacy)",
+            &["root", "path", "mode"],
+        )?;
+
         let cpu_usage_seconds = gauge(
             registry,
             cfg,
@@ -213,6 +966,55 @@ impl CgroupMetrics {
             "Total time duration the cgroup has been throttled (throttled_usec / 1e6)",
         )?;
 
+        let cpu_nr_bursts = int_gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_nr_bursts_total",
+            "Number of periods in which the cgroup used its CFS burst (nr_bursts from cpu.stat)",
+        )?;
+
+        let cpu_burst_seconds = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_burst_seconds",
+            "Total CPU time consumed above the quota from the CFS burst (burst_usec / 1e6)",
+        )?;
+
+        let cpu_max_burst_seconds = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_max_burst_seconds",
+            "Configured CFS burst per period in seconds (cpu.max.burst / 1e6)",
+        )?;
+
+        let cpu_core_sched_force_idle_seconds = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_core_sched_force_idle_seconds",
+            "Time SMT siblings were forced idle because of core scheduling (core_sched.force_idle_usec / 1e6)",
+        )?;
+
+        let cpu_burst_cores = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_burst_cores",
+            "Configured CFS burst in cores (cpu.max.burst / cpu.max period), extra headroom on top of cgroup_cpu_limit_cores",
+        )?;
+
+        let cpu_throttled_ratio = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_throttled_ratio",
+            "Fraction of enforcement periods throttled since the previous update (delta nr_throttled / delta nr_periods)",
+        )?;
+
+        let cpu_saturation_ratio = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_saturation_ratio",
+            "CPU saturation 0-1: max(cpu.pressure some avg10 / 100, cgroup_cpu_throttled_ratio); throttled ratio alone if PSI is unavailable",
+        )?;
+
         let cpu_limit_cores = gauge(
             registry,
             cfg,
@@ -220,249 +1022,1634 @@ impl CgroupMetrics {
             "Effective CPU limit in cores derived from cpu.max (quota/period), +Inf if unlimited",
         )?;
 
-        let mem_current_bytes = gauge(
+        let cpu_utilization_ratio = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_utilization_ratio",
+            "CPU usage since the previous update divided by the cpu.max limit (1 = whole limit used), 0 if unlimited",
+        )?;
+
+        let cpu_uclamp_percent = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_cpu_uclamp_percent",
+            "Utilization clamp from cpu.uclamp.min / cpu.uclamp.max in percent (max = 100), only when present",
+            &["root", "bound"],
+            None,
+        )?;
+
+        let cpu_idle = int_gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_idle",
+            "Whether the cgroup is SCHED_IDLE (cpu.idle = 1); 0 also when the file is missing",
+        )?;
+
+        let cpuset_cpus = int_gauge(
+            registry,
+            cfg,
+            "cgroup_cpuset_cpus",
+            "Number of CPUs effectively available to the cgroup (cpuset.cpus.effective)",
+        )?;
+
+        let cpuset_mems = int_gauge(
+            registry,
+            cfg,
+            "cgroup_cpuset_mems",
+            "Number of memory nodes effectively available to the cgroup (cpuset.mems.effective)",
+        )?;
+
+        let cpuset_info = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_cpuset_info",
+            "Effective cpuset of the cgroup as CPU and memory node lists (e.g. cpus=\"2-3\" when pinned by the static CPU manager policy)",
+            &["root", "cpus", "mems"],
+        )?;
+
+        let pressure_avg_percent = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_pressure_avg_percent",
+            "PSI share of time tasks of the cgroup were stalled on the resource, averaged over the window (avg10/avg60/avg300)",
+            &["root", "resource", "kind", "window"],
+            None,
+        )?;
+
+        let pressure_stall_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_pressure_stall_seconds_total",
+            "PSI total time tasks of the cgroup were stalled on the resource (total from {cpu,memory,io}.pressure)",
+            &["root", "resource", "kind"],
+            None,
+        )?;
+
+        let cpu_steal_impact_seconds = gauge(
+            registry,
+            cfg,
+            "cgroup_cpu_steal_impact_seconds",
+            "Estimated hypervisor steal time attributable to the cgroup: host steal (/proc/stat) times the cgroup's share of busy host CPU time, accumulated",
+        )?;
+
+        let cpu_usage_cores = if cfg.derived_rates {
+            Some(gauge(
+                registry,
+                cfg,
+                "cgroup_cpu_usage_cores",
+                "CPU usage of current cgroup in cores averaged over the last update interval",
+            )?)
+        } else {
+            None
+        };
+
+        let cpu_usage_cores_peaks = peak_gauge_vecs(
+            registry,
+            cfg,
+            "cgroup_cpu_usage_cores",
+            "Maximum per-interval CPU usage in cores of current cgroup over a rolling window",
+        )?;
+
+        let mem_current_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_current_bytes",
+            "Current memory usage in bytes (memory.current)",
+        )?;
+
+        let mem_current_peaks = peak_gauge_vecs(
+            registry,
+            cfg,
+            "cgroup_memory_current_bytes",
+            "Maximum sampled memory.current in bytes over a rolling window",
+        )?;
+
+        let mem_peak_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_peak_bytes",
+            "Peak memory usage in bytes (memory.peak)",
+        )?;
+
+        let mem_max_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_max_bytes",
+            "Memory limit in bytes (memory.max or +Inf)",
+        )?;
+
+        let mem_high_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_high_bytes",
+            "High memory threshold in bytes (memory.high)",
+        )?;
+
+        let mem_low_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_low_bytes",
+            "Low memory threshold in bytes (memory.low)",
+        )?;
+
+        let mem_min_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_min_bytes",
+            "Hard memory protection in bytes (memory.min)",
+        )?;
+
+        let mem_working_set_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_working_set_bytes",
+            "Working set in bytes as computed by the kubelet: memory.current minus inactive_file from memory.stat",
+        )?;
+
+        let mem_headroom_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_headroom_bytes",
+            "Bytes left before the memory limit: memory.max minus working set (kubelet eviction math), +Inf if unlimited",
+        )?;
+
+        let mem_events_total = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_memory_events_total",
+            "Cumulative memory events from memory.events",
+            &["root", "type"],
+        )?;
+
+        let mem_events_local_total = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_memory_events_local_total",
+            "Cumulative memory events from memory.events.local (this cgroup only, children excluded)",
+            &["root", "type"],
+        )?;
+
+        let mem_swap_current_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_swap_current_bytes",
+            "Swap used by the cgroup in bytes (memory.swap.current)",
+        )?;
+
+        let mem_swap_max_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_swap_max_bytes",
+            "Swap limit in bytes (memory.swap.max or +Inf)",
+        )?;
+
+        let mem_swap_high_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_swap_high_bytes",
+            "Swap throttling threshold in bytes (memory.swap.high or +Inf)",
+        )?;
+
+        let mem_swap_events_total = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_memory_swap_events_total",
+            "Cumulative swap events from memory.swap.events (high, max, fail)",
+            &["root", "type"],
+        )?;
+
+        let mem_zswap_current_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_zswap_current_bytes",
+            "Memory consumed by the zswap compression backend in bytes (memory.zswap.current)",
+        )?;
+
+        let mem_zswap_max_bytes = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_zswap_max_bytes",
+            "zswap limit in bytes (memory.zswap.max or +Inf)",
+        )?;
+
+        let mem_stat = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_memory_stat",
+            "Entries of memory.stat: sizes in bytes (anon, file, kernel, slab_*, sock, shmem, file_mapped, ...) and event counts (pgfault, pgmajfault, workingset_*, ...)",
+            &["root", "stat"],
+            None,
+        )?;
+
+        let frozen = int_gauge(
+            registry,
+            cfg,
+            "cgroup_frozen",
+            "Whether the cgroup is frozen (frozen from cgroup.events, v1 freezer.state FROZEN)",
+        )?;
+
+        let freeze_requested = int_gauge(
+            registry,
+            cfg,
+            "cgroup_freeze_requested",
+            "Whether freezing of the cgroup was requested (cgroup.freeze); 1 with cgroup_frozen 0 means freezing is in progress",
+        )?;
+
+        let controller_enabled = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_controller_enabled",
+            "Controllers available to the cgroup (scope=self: cgroup.controllers or mounted v1 hierarchy) and enabled for its children (scope=subtree: cgroup.subtree_control)",
+            &["root", "controller", "scope", "hierarchy"],
+        )?;
+
+        let descendants = int_gauge(
+            registry,
+            cfg,
+            "cgroup_descendants",
+            "Number of live descendant cgroups (nr_descendants from cgroup.stat)",
+        )?;
+
+        let dying_descendants = int_gauge(
+            registry,
+            cfg,
+            "cgroup_dying_descendants",
+            "Number of removed descendant cgroups still held by the kernel (nr_dying_descendants from cgroup.stat)",
+        )?;
+
+        let mem_workingset_refault_total = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_memory_workingset_refault_total",
+            "Refaults of previously evicted pages (workingset_refault_anon/file from memory.stat); growth means the working set does not fit",
+            &["root", "type"],
+            None,
+        )?;
+
+        let mem_workingset_activate_total = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_memory_workingset_activate_total",
+            "Refaulted pages that were immediately activated (workingset_activate_anon/file from memory.stat)",
+            &["root", "type"],
+            None,
+        )?;
+
+        let mem_pgscan_total = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_pgscan_total",
+            "Pages scanned by reclaim in the cgroup (pgscan from memory.stat)",
+        )?;
+
+        let mem_pgsteal_total = gauge(
+            registry,
+            cfg,
+            "cgroup_memory_pgsteal_total",
+            "Pages reclaimed from the cgroup (pgsteal from memory.stat)",
+        )?;
+
+        let misc_current = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_misc_current",
+            "Current usage of the misc controller resource (misc.current)",
+            &["root", "resource"],
+            None,
+        )?;
+
+        let misc_max = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_misc_max",
+            "Limit of the misc controller resource (misc.max), +Inf if unlimited",
+            &["root", "resource"],
+            None,
+        )?;
+
+        let io_vec = |name: &str, help: &str| {
+            gauge_vec_with_const_label(registry, cfg, name, help, &["root", "device"], None)
+        };
+        let io_read_bytes = io_vec(
+            "cgroup_io_read_bytes_total",
+            "Bytes read by the cgroup from the block device (io.stat rbytes)",
+        )?;
+        let io_write_bytes = io_vec(
+            "cgroup_io_write_bytes_total",
+            "Bytes written by the cgroup to the block device (io.stat wbytes)",
+        )?;
+        let io_reads = io_vec(
+            "cgroup_io_reads_total",
+            "Read I/O operations of the cgroup on the block device (io.stat rios)",
+        )?;
+        let io_writes = io_vec(
+            "cgroup_io_writes_total",
+            "Write I/O operations of the cgroup on the block device (io.stat wios)",
+        )?;
+        let io_discard_bytes = io_vec(
+            "cgroup_io_discard_bytes_total",
+            "Bytes discarded by the cgroup on the block device (io.stat dbytes)",
+        )?;
+
+        Ok(cfg
+            .cgroup_roots
+            .iter()
+            .map(|r| r.name.as_str())
+            .map(|root| Self {
+                root: root.to_string(),
+                info: RootVec::new(&info, root),
+                cpu_usage_seconds: cpu_usage_seconds.with_label_values(&[root]),
+                cpu_user_seconds: cpu_user_seconds.with_label_values(&[root]),
+                cpu_system_seconds: cpu_system_seconds.with_label_values(&[root]),
+                cpu_nr_periods: cpu_nr_periods.with_label_values(&[root]),
+                cpu_nr_throttled: cpu_nr_throttled.with_label_values(&[root]),
+                cpu_nr_bursts: cpu_nr_bursts.with_label_values(&[root]),
+                cpu_burst_seconds: cpu_burst_seconds.with_label_values(&[root]),
+                cpu_max_burst_seconds: cpu_max_burst_seconds.with_label_values(&[root]),
+                cpu_burst_cores: cpu_burst_cores.with_label_values(&[root]),
+                cpu_core_sched_force_idle_seconds: cpu_core_sched_force_idle_seconds
+                    .with_label_values(&[root]),
+                cpu_throttled_seconds: cpu_throttled_seconds.with_label_values(&[root]),
+                cpu_throttled_ratio: cpu_throttled_ratio.with_label_values(&[root]),
+                cpu_saturation_ratio: cpu_saturation_ratio.with_label_values(&[root]),
+                cpu_limit_cores: cpu_limit_cores.with_label_values(&[root]),
+                cpu_utilization_ratio: cpu_utilization_ratio.with_label_values(&[root]),
+                cpu_uclamp_percent: RootVec::new(&cpu_uclamp_percent, root),
+                cpu_idle: cpu_idle.with_label_values(&[root]),
+                cpuset_cpus: cpuset_cpus.with_label_values(&[root]),
+                cpuset_mems: cpuset_mems.with_label_values(&[root]),
+                cpuset_info: RootVec::new(&cpuset_info, root),
+                pressure_avg_percent: RootVec::new(&pressure_avg_percent, root),
+                pressure_stall_seconds: RootVec::new(&pressure_stall_seconds, root),
+                cpu_steal_impact_seconds: cpu_steal_impact_seconds.with_label_values(&[root]),
+                cpu_usage_cores: cpu_usage_cores
+                    .as_ref()
+                    .map(|v| v.with_label_values(&[root])),
+                cpu_usage_cores_peaks: bind_peaks(&cpu_usage_cores_peaks, root),
+                mem_current_bytes: mem_current_bytes.with_label_values(&[root]),
+                mem_current_peaks: bind_peaks(&mem_current_peaks, root),
+                mem_peak_bytes: mem_peak_bytes.with_label_values(&[root]),
+                mem_max_bytes: mem_max_bytes.with_label_values(&[root]),
+                mem_high_bytes: mem_high_bytes.with_label_values(&[root]),
+                mem_low_bytes: mem_low_bytes.with_label_values(&[root]),
+                mem_min_bytes: mem_min_bytes.with_label_values(&[root]),
+                mem_working_set_bytes: mem_working_set_bytes.with_label_values(&[root]),
+                mem_headroom_bytes: mem_headroom_bytes.with_label_values(&[root]),
+                mem_events_total: RootVec::new(&mem_events_total, root),
+                mem_events_local_total: RootVec::new(&mem_events_local_total, root),
+                mem_swap_current_bytes: mem_swap_current_bytes.with_label_values(&[root]),
+                mem_swap_max_bytes: mem_swap_max_bytes.with_label_values(&[root]),
+                mem_swap_high_bytes: mem_swap_high_bytes.with_label_values(&[root]),
+                mem_swap_events_total: RootVec::new(&mem_swap_events_total, root),
+                mem_zswap_current_bytes: mem_zswap_current_bytes.with_label_values(&[root]),
+                mem_zswap_max_bytes: mem_zswap_max_bytes.with_label_values(&[root]),
+                mem_stat: RootVec::new(&mem_stat, root),
+                mem_workingset_refault_total: RootVec::new(&mem_workingset_refault_total, root),
+                mem_workingset_activate_total: RootVec::new(&mem_workingset_activate_total, root),
+                mem_pgscan_total: mem_pgscan_total.with_label_values(&[root]),
+                mem_pgsteal_total: mem_pgsteal_total.with_label_values(&[root]),
+                frozen: frozen.with_label_values(&[root]),
+                freeze_requested: freeze_requested.with_label_values(&[root]),
+                controller_enabled: RootVec::new(&controller_enabled, root),
+                descendants: descendants.with_label_values(&[root]),
+                dying_descendants: dying_descendants.with_label_values(&[root]),
+                misc_current: RootVec::new(&misc_current, root),
+                misc_max: RootVec::new(&misc_max, root),
+                io_read_bytes: RootVec::new(&io_read_bytes, root),
+                io_write_bytes: RootVec::new(&io_write_bytes, root),
+                io_reads: RootVec::new(&io_reads, root),
+                io_writes: RootVec::new(&io_writes, root),
+                io_discard_bytes: RootVec::new(&io_discard_bytes, root),
+            })
+            .collect())
+    }
+}
+
+/// Vektory ProcessMetrics: pro TARGET_PID* bez labelu navíc (prefix
+/// process), pro TARGET_GROUPS sdílené přes skupiny s labelem group (prefix
+/// process_group). Registrují se při prvním použití, další skupina je jen
+/// naváže.
+struct ProcessVecs<'a> {
+    registry: &'a Registry,
+    cfg: &'a Config,
+    prefix: &'static str,
+    /// Labely před vlastními labely metriky ([] nebo ["group"])
+    extra: &'static [&'static str],
+    gauges: RefCell<HashMap<String, GaugeVec>>,
+    int_gauges: RefCell<HashMap<String, IntGaugeVec>>,
+    counters: RefCell<HashMap<String, IntCounterVec>>,
+}
+
+fn cached<V: Clone>(
+    cache: &RefCell<HashMap<String, V>>,
+    name: String,
+    make: impl FnOnce(&str) -> Result<V>,
+) -> Result<V> {
+    if let Some(v) = cache.borrow().get(&name) {
+        return Ok(v.clone());
+    }
+    let v = make(&name)?;
+    cache.borrow_mut().insert(name, v.clone());
+    Ok(v)
+}
+
+impl<'a> ProcessVecs<'a> {
+    fn new(
+        registry: &'a Registry,
+        cfg: &'a Config,
+        prefix: &'static str,
+        extra: &'static [&'static str],
+    ) -> Self {
+        Self {
+            registry,
+            cfg,
+            prefix,
+            extra,
+            gauges: RefCell::default(),
+            int_gauges: RefCell::default(),
+            counters: RefCell::default(),
+        }
+    }
+
+    fn labels<'l>(&self, labels: &[&'l str]) -> Vec<&'l str> {
+        self.extra.iter().chain(labels).copied().collect()
+    }
+
+    fn gauge_vec(&self, name: &str, help: &str, labels: &[&str]) -> Result<GaugeVec> {
+        let labels = self.labels(labels);
+        cached(&self.gauges, format!("{}_{name}", self.prefix), |full| {
+            gauge_vec_with_const_label(self.registry, self.cfg, full, help, &labels, None)
+        })
+    }
+
+    fn int_gauge_vec(&self, name: &str, help: &str, labels: &[&str]) -> Result<IntGaugeVec> {
+        let labels = self.labels(labels);
+        cached(
+            &self.int_gauges,
+            format!("{}_{name}", self.prefix),
+            |full| int_gauge_vec(self.registry, self.cfg, full, help, &labels),
+        )
+    }
+
+    fn int_counter_vec(&self, name: &str, help: &str) -> Result<IntCounterVec> {
+        let labels = self.labels(&[]);
+        cached(&self.counters, format!("{}_{name}", self.prefix), |full| {
+            int_counter_vec(self.registry, self.cfg, full, help, &labels)
+        })
+    }
+}
+
+impl ProcessMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        Self::build(&ProcessVecs::new(registry, cfg, "process", &[]), &[])
+    }
+
+    /// Jedna sada per skupina z TARGET_GROUPS (sdílené vektory, label group).
+    pub fn new_for_groups(registry: &Registry, cfg: &Config) -> Result<Vec<Self>> {
+        let vecs = ProcessVecs::new(registry, cfg, "process_group", &["group"]);
+        cfg.target_groups
+            .iter()
+            .map(|group| Self::build(&vecs, &[group.name.as_str()]))
+            .collect()
+    }
+
+    /// `key` = hodnoty labelů z `vecs.extra` (prázdné, nebo jméno skupiny).
+    fn build(vecs: &ProcessVecs, key: &[&str]) -> Result<Self> {
+        let cfg = vecs.cfg;
+        let g = |name: &str, help: &str| -> Result<Gauge> {
+            Ok(vecs.gauge_vec(name, help, &[])?.with_label_values(key))
+        };
+        let ig = |name: &str, help: &str| -> Result<IntGauge> {
+            Ok(vecs.int_gauge_vec(name, help, &[])?.with_label_values(key))
+        };
+        let ic = |name: &str, help: &str| -> Result<IntCounter> {
+            Ok(vecs.int_counter_vec(name, help)?.with_label_values(key))
+        };
+        let gv = |name: &str, help: &str, labels: &[&str]| -> Result<RootGaugeVec> {
+            Ok(RootVec::bound(
+                &vecs.gauge_vec(name, help, labels)?,
+                key.first().copied(),
+            ))
+        };
+        let igv = |name: &str, help: &str, labels: &[&str]| -> Result<RootIntGaugeVec> {
+            Ok(RootVec::bound(
+                &vecs.int_gauge_vec(name, help, labels)?,
+                key.first().copied(),
+            ))
+        };
+        let peaks = |name: &str, help: &str| -> Result<Vec<WindowGauge>> {
+            cfg.peak_windows
+                .iter()
+                .map(|(label, window)| {
+                    Ok(WindowGauge {
+                        label: label.clone(),
+                        window: *window,
+                        gauge: g(
+                            &format!("{name}_peak_{label}"),
+                            &format!("{help} ({label})"),
+                        )?,
+                    })
+                })
+                .collect()
+        };
+
+        let cpu_user_seconds = g(
+            "cpu_user_seconds",
+            "User CPU time for observed process (/proc/<pid>/stat)",
+        )?;
+
+        let cpu_system_seconds = g("cpu_system_seconds", "System CPU time for observed process")?;
+
+        let start_time_seconds = g(
+            "start_time_seconds",
+            "Start time of observed process since epoch seconds",
+        )?;
+
+        let mem_rss_bytes = g("memory_rss_bytes", "Resident set size of observed process")?;
+
+        let mem_rss_peaks = peaks(
+            "memory_rss_bytes",
+            "Maximum sampled resident set size of observed process over a rolling window",
+        )?;
+
+        let mem_vms_bytes = g(
+            "memory_vms_bytes",
+            "Virtual memory size of observed process",
+        )?;
+
+        let mem_swap_bytes = g("memory_swap_bytes", "Swap usage of observed process")?;
+
+        let mem_rss_hwm_bytes = g(
+            "memory_rss_hwm_bytes",
+            "Peak resident set size (VmHWM) of observed process, maximum across the target group",
+        )?;
+
+        let mem_vms_peak_bytes = g(
+            "memory_vms_peak_bytes",
+            "Peak virtual memory size (VmPeak) of observed process, maximum across the target group",
+        )?;
+
+        let io_rchar_bytes_total = g(
+            "io_rchar_bytes_total",
+            "Characters read (rchar) from /proc/<pid>/io",
+        )?;
+
+        let io_wchar_bytes_total = g(
+            "io_wchar_bytes_total",
+            "Characters written (wchar) from /proc/<pid>/io",
+        )?;
+
+        let io_syscr_total = g(
+            "io_syscr_total",
+            "Number of read syscalls (syscr) from /proc/<pid>/io",
+        )?;
+
+        let io_syscw_total = g(
+            "io_syscw_total",
+            "Number of write syscalls (syscw) from /proc/<pid>/io",
+        )?;
+
+        let io_read_bytes_total = g(
+            "io_read_bytes_total",
+            "Bytes read from storage (read_bytes) from /proc/<pid>/io",
+        )?;
+
+        let io_write_bytes_total = g(
+            "io_write_bytes_total",
+            "Bytes written to storage (write_bytes) from /proc/<pid>/io",
+        )?;
+
+        let io_cancelled_write_bytes_total = g(
+            "io_cancelled_write_bytes_total",
+            "Bytes of cancelled write IO (cancelled_write_bytes) from /proc/<pid>/io",
+        )?;
+
+        let minor_page_faults_total = g(
+            "minor_page_faults_total",
+            "Minor page faults (minflt, field 10 of /proc/<pid>/stat)",
+        )?;
+
+        let major_page_faults_total = g(
+            "major_page_faults_total",
+            "Major page faults requiring disk IO (majflt, field 12 of /proc/<pid>/stat), a sign of memory-limit thrashing",
+        )?;
+
+        let voluntary_ctxt_switches_total = g(
+            "voluntary_ctxt_switches_total",
+            "Voluntary context switches (voluntary_ctxt_switches) from /proc/<pid>/status",
+        )?;
+
+        let nonvoluntary_ctxt_switches_total = g(
+            "nonvoluntary_ctxt_switches_total",
+            "Involuntary context switches (nonvoluntary_ctxt_switches) from /proc/<pid>/status, the per-process counterpart of CPU throttling",
+        )?;
+
+        let uptime_seconds = g(
+            "uptime_seconds",
+            "Time in seconds the observed process has been running",
+        )?;
+
+        let restarts_total = ic(
+            "restarts_total",
+            "Target processes that exited or were restarted (PID left the target set or its start time changed)",
+        )?;
+
+        let target_matched = ig(
+            "target_matched",
+            "Number of processes currently matched by the process target and contributing to the process_* aggregates",
+        )?;
+
+        let threads = g(
+            "threads",
+            "Number of threads of observed process (Threads in /proc/<pid>/status), summed across the target group",
+        )?;
+
+        let oom_score = g(
+            "oom_score",
+            "Highest oom_score (/proc/<pid>/oom_score) in the target group - the process the kernel would kill first",
+        )?;
+
+        let oom_score_adj = g(
+            "oom_score_adj",
+            "oom_score_adj of the target process with the highest oom_score",
+        )?;
+
+        let states = igv(
+            "states",
+            "Number of target processes in each scheduler state (R running, S sleeping, D disk sleep, Z zombie, T stopped, I idle) from /proc/<pid>/stat",
+            &["state"],
+        )?;
+
+        let listening_ports = igv(
+            "listening_port_info",
+            "TCP port in LISTEN state owned by a target process (socket inodes from /proc/<pid>/fd matched against /proc/<pid>/net/tcp{,6})",
+            &["port", "proto"],
+        )?;
+
+        let open_fds = g(
+            "open_fds",
+            "Number of open file descriptors (/proc/<pid>/fd), summed across the target group",
+        )?;
+
+        let max_fds = g(
+            "max_fds",
+            "Soft NOFILE limit (/proc/<pid>/limits), lowest across the target group",
+        )?;
+
+        let fds_utilization_ratio = g(
+            "fds_utilization_ratio",
+            "Open file descriptors divided by the soft NOFILE limit, highest single process in the target group",
+        )?;
+
+        let resource_limits = gv(
+            "resource_limit",
+            "Resource limit from /proc/<pid>/limits (open_files, address_space, locked_memory, processes), lowest across the target group; +Inf = unlimited",
+            &["resource", "type"],
+        )?;
+
+        let availability = WindowGauge {
+            label: "availability".to_string(),
+            window: cfg.availability_window,
+            gauge: g(
+                "availability_ratio",
+                "Fraction of collection intervals within AVAILABILITY_WINDOW (default 1h) in which at least one target process existed",
+            )?,
+        };
+
+        let cpu_usage_cores = if cfg.derived_rates {
+            Some(g(
+                "cpu_usage_cores",
+                "CPU usage (user + system) of observed process in cores averaged over the last update interval",
+            )?)
+        } else {
+            None
+        };
+
+        let io_rates = if cfg.derived_rates {
+            Some(ProcessIoRateMetrics {
+                read_bytes_per_second: g(
+                    "io_read_bytes_per_second",
+                    "Bytes read from storage per second by observed process over the last update interval",
+                )?,
+                write_bytes_per_second: g(
+                    "io_write_bytes_per_second",
+                    "Bytes written to storage per second by observed process over the last update interval",
+                )?,
+            })
+        } else {
+            None
+        };
+
+        let thread_cpu_seconds = if cfg.process_thread_cpu {
+            Some(gv(
+                "thread_cpu_seconds_total",
+                "CPU time (user + system) of observed process threads by thread name (/proc/<pid>/task/*/stat), trailing numbers stripped",
+                &["thread"],
+            )?)
+        } else {
+            None
+        };
+
+        let smaps = if cfg.process_smaps {
+            Some(SmapsMetrics {
+                pss_bytes: g(
+                    "memory_pss_bytes",
+                    "Proportional set size (Pss in /proc/<pid>/smaps_rollup), summed across the target group",
+                )?,
+                pss_anon_bytes: g(
+                    "memory_pss_anon_bytes",
+                    "Anonymous part of the proportional set size (Pss_Anon)",
+                )?,
+                uss_bytes: g(
+                    "memory_uss_bytes",
+                    "Unique set size (Private_Clean + Private_Dirty), memory freed if the processes exit",
+                )?,
+                private_clean_bytes: g(
+                    "memory_private_clean_bytes",
+                    "Private clean pages (Private_Clean in smaps_rollup)",
+                )?,
+                private_dirty_bytes: g(
+                    "memory_private_dirty_bytes",
+                    "Private dirty pages (Private_Dirty in smaps_rollup)",
+                )?,
+                shared_bytes: g(
+                    "memory_shared_bytes",
+                    "Shared pages (Shared_Clean + Shared_Dirty in smaps_rollup), counted once per process",
+                )?,
+            })
+        } else {
+            None
+        };
+
+        let stalls = if cfg.process_stalls {
+            Some(StallMetrics {
+                uninterruptible_tasks: ig(
+                    "uninterruptible_tasks",
+                    "Number of target process threads in uninterruptible sleep (state D in /proc/<pid>/task/*/stat)",
+                )?,
+                longest_stall_seconds: g(
+                    "longest_stall_seconds",
+                    "How long the longest-stalled thread has been continuously observed in uninterruptible sleep",
+                )?,
+                stalled_task_info: igv(
+                    "stalled_task_info",
+                    "Longest-stalled thread in uninterruptible sleep with its kernel wait channel (/proc/<pid>/task/<tid>/wchan), always 1",
+                    &["pid", "tid", "comm", "wchan"],
+                )?,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            scope: key.first().map(|k| k.to_string()),
+            cpu_user_seconds,
+            cpu_system_seconds,
+            start_time_seconds,
+            mem_rss_bytes,
+            mem_rss_peaks,
+            mem_vms_bytes,
+            mem_swap_bytes,
+            mem_rss_hwm_bytes,
+            mem_vms_peak_bytes,
+            io_rchar_bytes_total,
+            io_wchar_bytes_total,
+            io_syscr_total,
+            io_syscw_total,
+            io_read_bytes_total,
+            io_write_bytes_total,
+            io_cancelled_write_bytes_total,
+            minor_page_faults_total,
+            major_page_faults_total,
+            voluntary_ctxt_switches_total,
+            nonvoluntary_ctxt_switches_total,
+            uptime_seconds, // <- přidat
+            restarts_total,
+            target_matched,
+            threads,
+            oom_score,
+            oom_score_adj,
+            states,
+            listening_ports,
+            open_fds,
+            max_fds,
+            fds_utilization_ratio,
+            resource_limits,
+            availability,
+            cpu_usage_cores,
+            io_rates,
+            thread_cpu_seconds,
+            smaps,
+            stalls,
+        })
+    }
+}
+
+impl NetMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let rx_bytes_total = gauge(
+            registry,
+            cfg,
+            "pod_network_receive_bytes_total",
+            "Network bytes received on NET_INTERFACE as seen from container (/sys/class/net/<iface>/statistics/rx_bytes, or /proc/<pid>/net/dev when the target process is in another network namespace)",
+        )?;
+        let tx_bytes_total = gauge(
+            registry,
+            cfg,
+            "pod_network_transmit_bytes_total",
+            "Network bytes transmitted on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_bytes)",
+        )?;
+
+        let rx_packets_total = gauge(
+            registry,
+            cfg,
+            "pod_network_receive_packets_total",
+            "Network packets received on NET_INTERFACE (/sys/class/net/<iface>/statistics/rx_packets)",
+        )?;
+        let tx_packets_total = gauge(
+            registry,
+            cfg,
+            "pod_network_transmit_packets_total",
+            "Network packets transmitted on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_packets)",
+        )?;
+
+        let rx_errors_total = gauge(
+            registry,
+            cfg,
+            "pod_network_receive_errors_total",
+            "Receive errors on NET_INTERFACE (/sys/class/net/<iface>/statistics/rx_errors)",
+        )?;
+        let tx_errors_total = gauge(
+            registry,
+            cfg,
+            "pod_network_transmit_errors_total",
+            "Transmit errors on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_errors)",
+        )?;
+
+        let rx_dropped_total = gauge(
+            registry,
+            cfg,
+            "pod_network_receive_dropped_total",
+            "Dropped receive packets on NET_INTERFACE (/sys/class/net/<iface>/statistics/rx_dropped)",
+        )?;
+        let tx_dropped_total = gauge(
+            registry,
+            cfg,
+            "pod_network_transmit_dropped_total",
+            "Dropped transmit packets on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_dropped)",
+        )?;
+
+        let rates = if cfg.derived_rates {
+            Some(NetRateMetrics::new(registry, cfg)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rx_bytes_total,
+            tx_bytes_total,
+            rx_packets_total,
+            tx_packets_total,
+            rx_errors_total,
+            tx_errors_total,
+            rx_dropped_total,
+            tx_dropped_total,
+            rates,
+        })
+    }
+}
+
+impl FastSampleMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let mem_current_bytes = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_memory_current_bytes_interval",
+            "Min/max/avg of memory.current sampled every FAST_SAMPLE_INTERVAL_MS over the last update interval",
+            &["stat"],
+            None,
+        )?;
+
+        let procs_running = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "host_procs_running_interval",
+            "Min/max/avg of runnable tasks (procs_running in /proc/stat) sampled every FAST_SAMPLE_INTERVAL_MS over the last update interval",
+            &["stat"],
+            None,
+        )?;
+
+        Ok(Self {
+            mem_current_bytes,
+            procs_running,
+        })
+    }
+}
+
+impl RecursiveMetrics {
+    pub fn new(registry: &Registry, cfg: &Config, mode: RecursiveMode) -> Result<Self> {
+        let (prefix, what, labels): (_, _, &[&str]) = match mode {
+            RecursiveMode::PerChild => ("cgroup_child", "the child cgroup", &["root", "cgroup"]),
+            RecursiveMode::Aggregate => {
+                ("cgroup_subtree", "leaf child cgroups (summed)", &["root"])
+            }
+        };
+
+        let cpu_usage_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            &format!("{prefix}_cpu_usage_seconds"),
+            &format!("Total CPU time consumed by {what} (usage_usec / 1e6)"),
+            labels,
+            None,
+        )?;
+
+        let cpu_throttled_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            &format!("{prefix}_cpu_throttled_seconds"),
+            &format!("Total time {what} was throttled (throttled_usec / 1e6)"),
+            labels,
+            None,
+        )?;
+
+        let mem_current_bytes = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            &format!("{prefix}_memory_current_bytes"),
+            &format!("Current memory usage of {what} (memory.current)"),
+            labels,
+            None,
+        )?;
+
+        let cgroups = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_recursive_children",
+            "Number of child cgroups found under the root within CGROUP_RECURSIVE_MAX_DEPTH",
+            &["root"],
+        )?;
+
+        let frozen_children = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_recursive_frozen_children",
+            "Number of child cgroups within CGROUP_RECURSIVE_MAX_DEPTH that are frozen (cgroup.events frozen 1)",
+            &["root"],
+        )?;
+
+        Ok(Self {
+            cpu_usage_seconds,
+            cpu_throttled_seconds,
+            mem_current_bytes,
+            cgroups,
+            frozen_children,
+        })
+    }
+}
+
+impl PsiTriggerMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let events_total = int_counter_vec(
+            registry,
+            cfg,
+            "cgroup_pressure_trigger_events_total",
+            "Number of times a PSI trigger from PSI_TRIGGERS fired (stall above threshold within the window)",
+            &["root", "resource", "kind", "threshold"],
+        )?;
+        Ok(Self { events_total })
+    }
+}
+
+impl ReclaimMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let requested_bytes = int_gauge(
+            registry,
+            cfg,
+            "cgroup_memory_reclaim_probe_requested_bytes",
+            "Bytes the reclaim probe asks the kernel to reclaim via memory.reclaim (MEMORY_RECLAIM_PROBE_BYTES)",
+        )?;
+
+        let reclaimed_bytes = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_memory_reclaim_probe_reclaimed_bytes",
+            "Drop of memory.current caused by the last reclaim probe - an estimate of cold memory",
+            &["root"],
+            None,
+        )?;
+
+        let complete = int_gauge_vec(
+            registry,
+            cfg,
+            "cgroup_memory_reclaim_probe_complete",
+            "Whether the last reclaim probe reclaimed the full requested amount (0 = kernel returned EAGAIN)",
+            &["root"],
+        )?;
+
+        let last_run_timestamp_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_memory_reclaim_probe_last_run_timestamp_seconds",
+            "Unix time of the last reclaim probe",
+            &["root"],
+            None,
+        )?;
+
+        Ok(Self {
+            requested_bytes,
+            reclaimed_bytes,
+            complete,
+            last_run_timestamp_seconds,
+        })
+    }
+}
+
+impl OomMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let events_total = int_counter_vec(
+            registry,
+            cfg,
+            "cgroup_oom_events_total",
+            "OOM events (oom, oom_kill) counted from memory.events changes reported by inotify; survives cgroup recreation",
+            &["root", "type"],
+        )?;
+
+        let last_oom_kill_timestamp_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_last_oom_kill_timestamp_seconds",
+            "Unix time when the exporter last observed an oom_kill increase in memory.events",
+            &["root"],
+            None,
+        )?;
+
+        Ok(Self {
+            events_total,
+            last_oom_kill_timestamp_seconds,
+        })
+    }
+}
+
+impl GlobMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let cpu_usage_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_glob_cpu_usage_seconds",
+            "Total CPU time consumed by a cgroup matching CGROUP_GLOB (usage_usec / 1e6)",
+            &["path"],
+            None,
+        )?;
+
+        let cpu_throttled_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "cgroup_glob_cpu_throttled_seconds",
+            "Total time a cgroup matching CGROUP_GLOB was throttled (throttled_usec / 1e6)",
+            &["path"],
+            None,
+        )?;
+
+        let mem_current_bytes = gauge_vec_with_const_label(
             registry,
             cfg,
-            "cgroup_memory_current_bytes",
-            "Current memory usage in bytes (memory.current)",
+            "cgroup_glob_memory_current_bytes",
+            "Current memory usage of a cgroup matching CGROUP_GLOB (memory.current)",
+            &["path"],
+            None,
         )?;
 
-        let mem_peak_bytes = gauge(
+        let matches = int_gauge(
             registry,
             cfg,
-            "cgroup_memory_peak_bytes",
-            "Peak memory usage in bytes (memory.peak)",
+            "cgroup_glob_matches",
+            "Number of cgroups currently matching CGROUP_GLOB",
         )?;
 
-        let mem_max_bytes = gauge(
+        Ok(Self {
+            cpu_usage_seconds,
+            cpu_throttled_seconds,
+            mem_current_bytes,
+            matches,
+        })
+    }
+}
+
+impl ContainerMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let cpu_usage_seconds = gauge_vec_with_const_label(
             registry,
             cfg,
-            "cgroup_memory_max_bytes",
-            "Memory limit in bytes (memory.max or +Inf)",
+            "container_cpu_usage_seconds",
+            "Total CPU time consumed by the container cgroup under the pod cgroup (usage_usec / 1e6)",
+            &["container"],
+            None,
         )?;
 
-        let mem_high_bytes = gauge(
+        let cpu_throttled_seconds = gauge_vec_with_const_label(
             registry,
             cfg,
-            "cgroup_memory_high_bytes",
-            "High memory threshold in bytes (memory.high)",
+            "container_cpu_throttled_seconds",
+            "Total time the container cgroup was throttled (throttled_usec / 1e6)",
+            &["container"],
+            None,
         )?;
 
-        let mem_low_bytes = gauge(
+        let mem_current_bytes = gauge_vec_with_const_label(
             registry,
             cfg,
-            "cgroup_memory_low_bytes",
-            "Low memory threshold in bytes (memory.low)",
+            "container_memory_current_bytes",
+            "Current memory usage of the container cgroup (memory.current)",
+            &["container"],
+            None,
         )?;
 
-        let mem_events_total = int_gauge_vec(
+        let mem_max_bytes = gauge_vec_with_const_label(
             registry,
             cfg,
-            "cgroup_memory_events_total",
-            "Cumulative memory events from memory.events",
-            &["type"],
+            "container_memory_max_bytes",
+            "Memory limit of the container cgroup (memory.max), +Inf if unlimited",
+            &["container"],
+            None,
         )?;
 
         Ok(Self {
             cpu_usage_seconds,
-            cpu_user_seconds,
-            cpu_system_seconds,
-            cpu_nr_periods,
-            cpu_nr_throttled,
             cpu_throttled_seconds,
-            cpu_limit_cores,
             mem_current_bytes,
-            mem_peak_bytes,
             mem_max_bytes,
-            mem_high_bytes,
-            mem_low_bytes,
-            mem_events_total,
         })
     }
 }
 
-impl ProcessMetrics {
+impl PidMetrics {
     pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
-        let cpu_user_seconds = gauge(
-            registry,
-            cfg,
-            "process_cpu_user_seconds",
-            "User CPU time for observed process (/proc/<pid>/stat)",
-        )?;
+        let labels = &["pid", "comm"];
+        let vec = |name: &str, help: &str| {
+            gauge_vec_with_const_label(registry, cfg, name, help, labels, None)
+        };
 
-        let cpu_system_seconds = gauge(
+        Ok(Self {
+            cpu_user_seconds: vec(
+                "process_pid_cpu_user_seconds",
+                "User CPU time of a single target process (/proc/<pid>/stat)",
+            )?,
+            cpu_system_seconds: vec(
+                "process_pid_cpu_system_seconds",
+                "System CPU time of a single target process",
+            )?,
+            start_time_seconds: vec(
+                "process_pid_start_time_seconds",
+                "Start time of a single target process since epoch seconds",
+            )?,
+            mem_rss_bytes: vec(
+                "process_pid_memory_rss_bytes",
+                "Resident set size (VmRSS) of a single target process",
+            )?,
+            mem_vms_bytes: vec(
+                "process_pid_memory_vms_bytes",
+                "Virtual memory size (VmSize) of a single target process",
+            )?,
+            mem_swap_bytes: vec(
+                "process_pid_memory_swap_bytes",
+                "Swap usage (VmSwap) of a single target process",
+            )?,
+            io_read_bytes_total: vec(
+                "process_pid_io_read_bytes_total",
+                "Bytes read from storage (read_bytes) by a single target process",
+            )?,
+            io_write_bytes_total: vec(
+                "process_pid_io_write_bytes_total",
+                "Bytes written to storage (write_bytes) by a single target process",
+            )?,
+            info: int_gauge_vec(
+                registry,
+                cfg,
+                "process_info",
+                "Target process description (executable, real uid, cmdline truncated to 128 characters), always 1",
+                &["pid", "comm", "exe", "uid", "cmdline"],
+            )?,
+        })
+    }
+}
+
+impl TaskstatsMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let labels = &["pid", "comm", "type"];
+
+        let delay_seconds_total = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_cpu_system_seconds",
-            "System CPU time for observed process",
+            "process_pid_delay_seconds_total",
+            "Time the target process spent waiting for CPU, block IO or swap-in (taskstats delay accounting)",
+            labels,
+            None,
         )?;
 
-        let start_time_seconds = gauge(
+        let delays_total = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_start_time_seconds",
-            "Start time of observed process since epoch seconds",
+            "process_pid_delays_total",
+            "Number of delays of the target process waiting for CPU, block IO or swap-in (taskstats)",
+            labels,
+            None,
         )?;
 
-        let mem_rss_bytes = gauge(
+        Ok(Self {
+            delay_seconds_total,
+            delays_total,
+        })
+    }
+}
+
+impl PodProcessMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let labels = &["namespace", "pod", "pod_uid"];
+
+        let processes = int_gauge_vec(
             registry,
             cfg,
-            "process_memory_rss_bytes",
-            "Resident set size of observed process",
+            "pod_processes",
+            "Number of processes on the node belonging to the pod (resolved via /proc/<pid>/cgroup)",
+            labels,
         )?;
 
-        let mem_vms_bytes = gauge(
+        let cpu_seconds = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_memory_vms_bytes",
-            "Virtual memory size of observed process",
+            "pod_process_cpu_seconds_total",
+            "CPU time (user + system) of the pod's processes, kept monotonic when processes exit",
+            labels,
+            None,
         )?;
 
-        let mem_swap_bytes = gauge(
+        let rss_bytes = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_memory_swap_bytes",
-            "Swap usage of observed process",
+            "pod_process_memory_rss_bytes",
+            "Sum of VmRSS of the pod's processes",
+            labels,
+            None,
         )?;
 
-        let io_rchar_bytes_total = gauge(
+        Ok(Self {
+            processes,
+            cpu_seconds,
+            rss_bytes,
+        })
+    }
+}
+
+impl SiblingMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let processes = int_gauge_vec(
             registry,
             cfg,
-            "process_io_rchar_bytes_total",
-            "Characters read (rchar) from /proc/<pid>/io",
+            "container_processes",
+            "Number of visible processes (shared PID namespace) belonging to the container",
+            &["container"],
         )?;
 
-        let io_wchar_bytes_total = gauge(
+        let cpu_seconds = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_io_wchar_bytes_total",
-            "Characters written (wchar) from /proc/<pid>/io",
+            "container_process_cpu_seconds_total",
+            "CPU time (user + system) of the container's processes, kept monotonic when processes exit",
+            &["container"],
+            None,
         )?;
 
-        let io_syscr_total = gauge(
+        let rss_bytes = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_io_syscr_total",
-            "Number of read syscalls (syscr) from /proc/<pid>/io",
+            "container_process_memory_rss_bytes",
+            "Sum of VmRSS of the container's processes",
+            &["container"],
+            None,
         )?;
 
-        let io_syscw_total = gauge(
+        Ok(Self {
+            processes,
+            cpu_seconds,
+            rss_bytes,
+        })
+    }
+}
+
+impl ProbeMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let success = int_gauge_vec(
             registry,
             cfg,
-            "process_io_syscw_total",
-            "Number of write syscalls (syscw) from /proc/<pid>/io",
+            "probe_success",
+            "Whether the last PROBE_HTTP (2xx/3xx) / PROBE_TCP (connect) / PROBE_DNS (at least one address) probe succeeded",
+            &["type", "target"],
         )?;
 
-        let io_read_bytes_total = gauge(
+        let duration_seconds = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_io_read_bytes_total",
-            "Bytes read from storage (read_bytes) from /proc/<pid>/io",
+            "probe_duration_seconds",
+            "Duration of the last probe including connect, up to PROBE_TIMEOUT_MS",
+            &["type", "target"],
+            None,
         )?;
 
-        let io_write_bytes_total = gauge(
+        let http_status_code = int_gauge_vec(
             registry,
             cfg,
-            "process_io_write_bytes_total",
-            "Bytes written to storage (write_bytes) from /proc/<pid>/io",
+            "probe_http_status_code",
+            "HTTP status code of the last PROBE_HTTP probe, 0 if the request failed",
+            &["target"],
         )?;
 
-        let io_cancelled_write_bytes_total = gauge(
+        Ok(Self {
+            success,
+            duration_seconds,
+            http_status_code,
+        })
+    }
+}
+
+impl ExecMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let success = int_gauge_vec(
             registry,
             cfg,
-            "process_io_cancelled_write_bytes_total",
-            "Bytes of cancelled write IO (cancelled_write_bytes) from /proc/<pid>/io",
+            "exec_command_success",
+            "Whether the last run of the EXEC_COMMANDS command succeeded and its output parsed",
+            &["command"],
         )?;
 
-        let uptime_seconds = gauge(
+        let duration_seconds = gauge_vec_with_const_label(
             registry,
             cfg,
-            "process_uptime_seconds",
-            "Time in seconds the observed process has been running",
+            "exec_command_duration_seconds",
+            "Duration of the last run of the EXEC_COMMANDS command",
+            &["command"],
+            None,
         )?;
 
+        let collector = ExecCollector::new(registry);
+        registry
+            .register(Box::new(collector.clone()))
+            .context("register exec collector")?;
+
         Ok(Self {
-            cpu_user_seconds,
-            cpu_system_seconds,
-            start_time_seconds,
-            mem_rss_bytes,
-            mem_vms_bytes,
-            mem_swap_bytes,
-            io_rchar_bytes_total,
-            io_wchar_bytes_total,
-            io_syscr_total,
-            io_syscw_total,
-            io_read_bytes_total,
-            io_write_bytes_total,
-            io_cancelled_write_bytes_total,
-            uptime_seconds, // <- přidat
+            success,
+            duration_seconds,
+            collector,
         })
     }
 }
 
-impl NetMetrics {
+impl BlockDeviceMetrics {
     pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
-        let rx_bytes_total = gauge(
-            registry,
-            cfg,
-            "pod_network_receive_bytes_total",
-            "Network bytes received on NET_INTERFACE as seen from container (/sys/class/net/<iface>/statistics/rx_bytes)",
-        )?;
-        let tx_bytes_total = gauge(
+        let vec = |name: &str, help: &str| {
+            gauge_vec_with_const_label(registry, cfg, name, help, &["device"], None)
+        };
+
+        Ok(Self {
+            in_flight: vec(
+                "block_device_in_flight_requests",
+                "I/O requests issued to the block device but not yet completed (/sys/block/<dev>/stat)",
+            )?,
+            in_flight_by_direction: gauge_vec_with_const_label(
+                registry,
+                cfg,
+                "block_device_in_flight_requests_by_direction",
+                "In-flight I/O requests of the block device by direction (/sys/block/<dev>/inflight)",
+                &["device", "direction"],
+                None,
+            )?,
+            io_time_seconds: vec(
+                "block_device_io_time_seconds_total",
+                "Time the block device had I/O in progress",
+            )?,
+            time_in_queue_seconds: vec(
+                "block_device_time_in_queue_seconds_total",
+                "Weighted time requests spent queued or in progress on the block device",
+            )?,
+            utilization_ratio: vec(
+                "block_device_utilization_ratio",
+                "Share of time the block device was busy since the previous update (0-1)",
+            )?,
+            avg_queue_depth: vec(
+                "block_device_avg_queue_depth",
+                "Average number of requests queued or in progress since the previous update",
+            )?,
+        })
+    }
+}
+
+impl TcMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let vec = |name: &str, help: &str| {
+            gauge_vec_with_const_label(
+                registry,
+                cfg,
+                name,
+                help,
+                &["interface", "object", "kind", "handle", "parent"],
+                None,
+            )
+        };
+
+        Ok(Self {
+            sent_bytes: vec(
+                "tc_sent_bytes_total",
+                "Bytes sent through the tc qdisc or class",
+            )?,
+            sent_packets: vec(
+                "tc_sent_packets_total",
+                "Packets sent through the tc qdisc or class",
+            )?,
+            drops: vec(
+                "tc_drops_total",
+                "Packets dropped by the tc qdisc or class (e.g. tbf shaping of the CNI bandwidth plugin)",
+            )?,
+            overlimits: vec(
+                "tc_overlimits_total",
+                "Times the tc qdisc or class was over its limit",
+            )?,
+            requeues: vec(
+                "tc_requeues_total",
+                "Packets requeued by the tc qdisc or class",
+            )?,
+            backlog_bytes: vec(
+                "tc_backlog_bytes",
+                "Bytes currently queued in the tc qdisc or class",
+            )?,
+            backlog_packets: vec(
+                "tc_backlog_packets",
+                "Packets currently queued in the tc qdisc or class",
+            )?,
+        })
+    }
+}
+
+impl HttpMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        Ok(Self {
+            requests_total: int_counter_vec(
+                registry,
+                cfg,
+                "exporter_http_requests_total",
+                "HTTP requests served by the exporter by path and status code",
+                &["path", "status"],
+            )?,
+            in_flight: int_gauge(
+                registry,
+                cfg,
+                "exporter_http_requests_in_flight",
+                "HTTP requests currently being served by the exporter",
+            )?,
+            request_duration_seconds: histogram_vec(
+                registry,
+                cfg,
+                "exporter_http_request_duration_seconds",
+                "Time the exporter took to build the HTTP response, by path",
+                &["path"],
+                vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5],
+            )?,
+            response_size_bytes: histogram_vec(
+                registry,
+                cfg,
+                "exporter_http_response_size_bytes",
+                "Size of HTTP response bodies served by the exporter, by path",
+                &["path"],
+                prometheus::exponential_buckets(256.0, 4.0, 8)?,
+            )?,
+        })
+    }
+}
+
+impl MemoryBudgetMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        Ok(Self {
+            rss_bytes: int_gauge(
+                registry,
+                cfg,
+                "exporter_memory_rss_bytes",
+                "Resident set size of the exporter process itself",
+            )?,
+            budget_bytes: int_gauge(
+                registry,
+                cfg,
+                "exporter_memory_budget_bytes",
+                "Configured SELF_MEMORY_BUDGET_BYTES of the exporter",
+            )?,
+            collector_shed: int_gauge_vec(
+                registry,
+                cfg,
+                "exporter_collector_shed",
+                "Whether the optional collector was disabled (1) because the exporter exceeded its memory budget",
+                &["collector"],
+            )?,
+        })
+    }
+}
+
+impl FilesystemMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let vec = |name: &str, help: &str| {
+            gauge_vec_with_const_label(registry, cfg, name, help, &["path"], None)
+        };
+
+        Ok(Self {
+            size_bytes: vec(
+                "filesystem_size_bytes",
+                "Total size of the filesystem mounted at the FS_PATHS path (statvfs)",
+            )?,
+            avail_bytes: vec(
+                "filesystem_avail_bytes",
+                "Bytes available to unprivileged users on the filesystem (statvfs f_bavail)",
+            )?,
+            free_bytes: vec(
+                "filesystem_free_bytes",
+                "Free bytes on the filesystem including reserved blocks (statvfs f_bfree)",
+            )?,
+            files: vec(
+                "filesystem_files",
+                "Total inodes on the filesystem (statvfs f_files)",
+            )?,
+            files_free: vec(
+                "filesystem_files_free",
+                "Free inodes on the filesystem (statvfs f_ffree)",
+            )?,
+        })
+    }
+}
+
+impl WatchFileMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let exists = int_gauge_vec(
             registry,
             cfg,
-            "pod_network_transmit_bytes_total",
-            "Network bytes transmitted on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_bytes)",
+            "watched_file_exists",
+            "Whether the file from WATCH_FILES exists (1) or not (0)",
+            &["path"],
         )?;
 
-        let rx_packets_total = gauge(
+        let size_bytes = gauge_vec_with_const_label(
             registry,
             cfg,
-            "pod_network_receive_packets_total",
-            "Network packets received on NET_INTERFACE (/sys/class/net/<iface>/statistics/rx_packets)",
+            "watched_file_size_bytes",
+            "Size in bytes of the file from WATCH_FILES",
+            &["path"],
+            None,
         )?;
-        let tx_packets_total = gauge(
+
+        let age_seconds = gauge_vec_with_const_label(
             registry,
             cfg,
-            "pod_network_transmit_packets_total",
-            "Network packets transmitted on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_packets)",
+            "watched_file_age_seconds",
+            "Seconds since the last modification (mtime) of the file from WATCH_FILES",
+            &["path"],
+            None,
         )?;
 
-        let rx_errors_total = gauge(
+        Ok(Self {
+            exists,
+            size_bytes,
+            age_seconds,
+        })
+    }
+}
+
+impl NetRateMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let rx_bytes_per_second = gauge(
             registry,
             cfg,
-            "pod_network_receive_errors_total",
-            "Receive errors on NET_INTERFACE (/sys/class/net/<iface>/statistics/rx_errors)",
+            "pod_network_receive_bytes_per_second",
+            "Bytes received per second on NET_INTERFACE over the last update interval",
         )?;
-        let tx_errors_total = gauge(
+        let tx_bytes_per_second = gauge(
             registry,
             cfg,
-            "pod_network_transmit_errors_total",
-            "Transmit errors on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_errors)",
+            "pod_network_transmit_bytes_per_second",
+            "Bytes transmitted per second on NET_INTERFACE over the last update interval",
         )?;
-
-        let rx_dropped_total = gauge(
+        let rx_packets_per_second = gauge(
             registry,
             cfg,
-            "pod_network_receive_dropped_total",
-            "Dropped receive packets on NET_INTERFACE (/sys/class/net/<iface>/statistics/rx_dropped)",
+            "pod_network_receive_packets_per_second",
+            "Packets received per second on NET_INTERFACE over the last update interval",
         )?;
-        let tx_dropped_total = gauge(
+        let tx_packets_per_second = gauge(
             registry,
             cfg,
-            "pod_network_transmit_dropped_total",
-            "Dropped transmit packets on NET_INTERFACE (/sys/class/net/<iface>/statistics/tx_dropped)",
+            "pod_network_transmit_packets_per_second",
+            "Packets transmitted per second on NET_INTERFACE over the last update interval",
         )?;
 
         Ok(Self {
-            rx_bytes_total,
-            tx_bytes_total,
-            rx_packets_total,
-            tx_packets_total,
-            rx_errors_total,
-            tx_errors_total,
-            rx_dropped_total,
-            tx_dropped_total,
+            rx_bytes_per_second,
+            tx_bytes_per_second,
+            rx_packets_per_second,
+            tx_packets_per_second,
         })
     }
 }
@@ -622,7 +2809,105 @@ impl TcpMetrics {
             &["state", "ip_version"],
         )?;
 
-        Ok(Self { connections })
+        let peer_connections = if cfg.peer_cidrs.is_empty() {
+            None
+        } else {
+            Some(int_gauge_vec(
+                registry,
+                cfg,
+                "pod_tcp_peer_connections",
+                "Number of TCP connections for this pod by state to the remote service defined in PEER_CIDRS",
+                &["peer", "state"],
+            )?)
+        };
+
+        Ok(Self {
+            connections,
+            peer_connections,
+        })
+    }
+}
+
+#[cfg(feature = "runtime-metrics")]
+impl RuntimeMetrics {
+    pub fn new(registry: &Registry, cfg: &Config) -> Result<Self> {
+        let workers = int_gauge(
+            registry,
+            cfg,
+            "exporter_runtime_workers",
+            "Number of worker threads of the exporter's tokio runtime",
+        )?;
+
+        let alive_tasks = int_gauge(
+            registry,
+            cfg,
+            "exporter_runtime_alive_tasks",
+            "Number of alive tasks in the exporter's tokio runtime",
+        )?;
+
+        let global_queue_depth = int_gauge(
+            registry,
+            cfg,
+            "exporter_runtime_global_queue_depth",
+            "Number of tasks waiting in the tokio runtime's global (injection) queue",
+        )?;
+
+        let worker_busy_seconds_total = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "exporter_runtime_worker_busy_seconds_total",
+            "Total time the tokio worker thread has been busy (seconds)",
+            &["worker"],
+            None,
+        )?;
+
+        let worker_park_total = int_gauge_vec(
+            registry,
+            cfg,
+            "exporter_runtime_worker_park_total",
+            "Number of times the tokio worker thread parked",
+            &["worker"],
+        )?;
+
+        #[cfg(tokio_unstable)]
+        let blocking_threads = int_gauge(
+            registry,
+            cfg,
+            "exporter_runtime_blocking_threads",
+            "Number of threads in the tokio blocking pool",
+        )?;
+
+        #[cfg(tokio_unstable)]
+        let blocking_queue_depth = int_gauge(
+            registry,
+            cfg,
+            "exporter_runtime_blocking_queue_depth",
+            "Number of tasks waiting for a thread in the tokio blocking pool",
+        )?;
+
+        #[cfg(tokio_unstable)]
+        let worker_mean_poll_seconds = gauge_vec_with_const_label(
+            registry,
+            cfg,
+            "exporter_runtime_worker_mean_poll_seconds",
+            "Exponentially weighted mean task poll duration per tokio worker (seconds)",
+            &["worker"],
+            None,
+        )?;
+
+        Ok(Self {
+            workers,
+            alive_tasks,
+            global_queue_depth,
+            worker_busy_seconds_total,
+            worker_park_total,
+            #[cfg(tokio_unstable)]
+            blocking_threads,
+            #[cfg(tokio_unstable)]
+            blocking_queue_depth,
+            #[cfg(tokio_unstable)]
+            worker_mean_poll_seconds,
+        })
     }
 }
 
@@ -646,6 +2931,16 @@ fn downward_info_metric(registry: &Registry, cfg: &Config) -> Result<IntGaugeVec
 
 // ---- helpers na tvorbu metrik ----
 
+/// Číselná hodnota jednoho vzorku z gather() (jen gauge/counter/untyped).
+pub fn sample_value(m: &Metric, t: MetricType) -> Option<f64> {
+    match t {
+        MetricType::GAUGE => Some(m.get_gauge().value()),
+        MetricType::COUNTER => Some(m.get_counter().value()),
+        MetricType::UNTYPED => Some(m.untyped.value()),
+        _ => None,
+    }
+}
+
 fn make_opts(
     name: &str,
     help: &str,
@@ -653,10 +2948,10 @@ fn make_opts(
     const_labels: HashMap<String, String>,
 ) -> Opts {
     let mut opts = Opts::new(name, help);
-    if let Some(ns) = namespace {
-        if !ns.is_empty() {
-            opts = opts.namespace(ns);
-        }
+    if let Some(ns) = namespace
+        && !ns.is_empty()
+    {
+        opts = opts.namespace(ns);
     }
     if !const_labels.is_empty() {
         opts = opts.const_labels(const_labels);
@@ -678,6 +2973,40 @@ fn gauge(registry: &Registry, cfg: &Config, name: &str, help: &str) -> Result<Ga
     Ok(g)
 }
 
+/// Pro každé okno z PEAK_WINDOWS zaregistruje vec `<name>_peak_<okno>` s labelem
+/// root (CgroupMetrics).
+fn peak_gauge_vecs(
+    registry: &Registry,
+    cfg: &Config,
+    name: &str,
+    help: &str,
+) -> Result<Vec<(String, Duration, GaugeVec)>> {
+    cfg.peak_windows
+        .iter()
+        .map(|(label, window)| {
+            let v = gauge_vec_with_const_label(
+                registry,
+                cfg,
+                &format!("{name}_peak_{label}"),
+                &format!("{help} ({label})"),
+                &["root"],
+                None,
+            )?;
+            Ok((label.clone(), *window, v))
+        })
+        .collect()
+}
+
+fn bind_peaks(vecs: &[(String, Duration, GaugeVec)], root: &str) -> Vec<WindowGauge> {
+    vecs.iter()
+        .map(|(label, window, v)| WindowGauge {
+            label: label.clone(),
+            window: *window,
+            gauge: v.with_label_values(&[root]),
+        })
+        .collect()
+}
+
 fn int_gauge(registry: &Registry, cfg: &Config, name: &str, help: &str) -> Result<IntGauge> {
     let opts = make_opts(
         name,
@@ -692,6 +3021,48 @@ fn int_gauge(registry: &Registry, cfg: &Config, name: &str, help: &str) -> Resul
     Ok(g)
 }
 
+fn int_counter_vec(
+    registry: &Registry,
+    cfg: &Config,
+    name: &str,
+    help: &str,
+    labels: &[&str],
+) -> Result<IntCounterVec> {
+    let opts = make_opts(
+        name,
+        help,
+        cfg.metrics_prefix.clone(),
+        cfg.static_labels.clone(),
+    );
+    let v = IntCounterVec::new(opts, labels).context(format!("create int counter vec {}", name))?;
+    registry
+        .register(Box::new(v.clone()))
+        .context(format!("register int counter vec {}", name))?;
+    Ok(v)
+}
+
+fn histogram_vec(
+    registry: &Registry,
+    cfg: &Config,
+    name: &str,
+    help: &str,
+    labels: &[&str],
+    buckets: Vec<f64>,
+) -> Result<HistogramVec> {
+    let opts = make_opts(
+        name,
+        help,
+        cfg.metrics_prefix.clone(),
+        cfg.static_labels.clone(),
+    );
+    let v = HistogramVec::new(HistogramOpts::from(opts).buckets(buckets), labels)
+        .context(format!("create histogram vec {}", name))?;
+    registry
+        .register(Box::new(v.clone()))
+        .context(format!("register histogram vec {}", name))?;
+    Ok(v)
+}
+
 fn int_gauge_vec(
     registry: &Registry,
     cfg: &Config,
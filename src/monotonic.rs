@@ -0,0 +1,52 @@
+//! Monotónní countery přes restart zdroje (restart procesu, nově vytvořená
+//! cgroupa). Když syrová hodnota klesne, pokles se přičte do offsetu, takže
+//! exportovaný counter zůstane na poslední hodnotě a dál jen roste.
+//!
+//! Offset = velikost poklesu, ne celá předchozí hodnota: u agregací přes
+//! skupinu PIDů znamená pokles typicky, že jeden z procesů skončil, a ostatní
+//! pořád přispívají - přičtení celé hodnoty by je započítalo dvakrát.
+//! Se STATE_FILE se stav ukládá a po restartu exporteru obnoví.
+
+use std::collections::HashMap;
+
+use tracing::info;
+
+#[derive(Debug, Default)]
+pub struct CounterOffsets {
+    /// Klíč → (poslední syrová hodnota, offset)
+    state: HashMap<String, (f64, f64)>,
+}
+
+impl CounterOffsets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Vrátí syrovou hodnotu + nasčítaný offset; při poklesu offset zvětší.
+    pub fn adjust(&mut self, key: &str, raw: f64) -> f64 {
+        let (last, offset) = self.state.entry(key.to_string()).or_insert((raw, 0.0));
+        if raw < *last {
+            *offset += *last - raw;
+            info!(
+                counter = key,
+                previous = *last,
+                current = raw,
+                "counter source reset detected, carrying total forward"
+            );
+        }
+        *last = raw;
+        raw + *offset
+    }
+
+    /// Kopie stavu pro STATE_FILE: klíč → (poslední syrová hodnota, offset).
+    pub fn snapshot(&self) -> HashMap<String, (f64, f64)> {
+        self.state.clone()
+    }
+
+    /// Obnoví stav ze STATE_FILE; klíče už viděné v tomhle běhu mají přednost.
+    pub fn restore(&mut self, saved: HashMap<String, (f64, f64)>) {
+        for (key, value) in saved {
+            self.state.entry(key).or_insert(value);
+        }
+    }
+}
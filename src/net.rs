@@ -1,50 +1,181 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::Path, sync::Mutex};
 
 use anyhow::Result;
+use once_cell::sync::Lazy;
+use prometheus::Gauge;
 
-use crate::metrics::NetMetrics;
+use crate::{delta::DeltaTracker, metrics::NetMetrics, monotonic::CounterOffsets};
 
-fn read_u64_lossy(path: &PathBuf) -> Option<u64> {
+static DELTAS: Lazy<Mutex<DeltaTracker>> = Lazy::new(|| Mutex::new(DeltaTracker::new()));
+
+/// Offsety counterů - znovu vytvořený interface začíná od nuly.
+pub static OFFSETS: Lazy<Mutex<CounterOffsets>> = Lazy::new(|| Mutex::new(CounterOffsets::new()));
+
+/// Sledované countery - jména souborů ve statistics/ a jejich sloupec v /proc/net/dev
+const COUNTERS: [(&str, usize); 8] = [
+    ("rx_bytes", 0),
+    ("rx_packets", 1),
+    ("rx_errors", 2),
+    ("rx_dropped", 3),
+    ("tx_bytes", 8),
+    ("tx_packets", 9),
+    ("tx_errors", 10),
+    ("tx_dropped", 11),
+];
+
+fn read_u64_lossy(path: &Path) -> Option<u64> {
     let s = std::fs::read_to_string(path).ok()?;
     s.trim().parse::<u64>().ok()
 }
 
-pub fn update(metrics: &NetMetrics, iface: &str) -> Result<()> {
+fn update_rate(
+    deltas: &mut DeltaTracker,
+    stats: &HashMap<&str, u64>,
+    iface: &str,
+    file: &str,
+    gauge: &Gauge,
+) {
+    let Some(&v) = stats.get(file) else {
+        return;
+    };
+    if let Some(rate) = deltas
+        .observe(&format!("{iface}:{file}"), v as f64)
+        .and_then(|d| d.per_second())
+    {
+        gauge.set(rate);
+    }
+}
+
+/// Cílový proces žije v jiném net namespace než exporter (hostPID + pod).
+/// Nečitelný ns link (chybí oprávnění) bereme jako stejný namespace.
+fn foreign_netns(proc_root: &Path, pid: i32) -> bool {
+    let netns = |dir: &str| std::fs::read_link(proc_root.join(dir).join("ns/net")).ok();
+    match (netns(&pid.to_string()), netns("self")) {
+        (Some(target), Some(own)) => target != own,
+        _ => false,
+    }
+}
+
+/// Countery interface z `/proc/<pid>/net/dev` (pohled z netns procesu).
+fn read_net_dev(path: &Path, iface: &str) -> Option<HashMap<&'static str, u64>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let fields: Vec<u64> = content.lines().find_map(|line| {
+        let (name, rest) = line.split_once(':')?;
+        (name.trim() == iface).then(|| {
+            rest.split_whitespace()
+                .filter_map(|v| v.parse().ok())
+                .collect()
+        })
+    })?;
+    Some(
+        COUNTERS
+            .iter()
+            .filter_map(|&(file, col)| Some((file, *fields.get(col)?)))
+            .collect(),
+    )
+}
+
+fn read_sys_stats(sys_root: &Path, iface: &str) -> Option<HashMap<&'static str, u64>> {
+    let base = sys_root.join(format!("class/net/{}/statistics", iface));
+    if !base.exists() {
+        return None;
+    }
+    Some(
+        COUNTERS
+            .iter()
+            .filter_map(|&(file, _)| Some((file, read_u64_lossy(&base.join(file))?)))
+            .collect(),
+    )
+}
+
+/// `target_pid` = první proces z TARGET_PID*; když je v jiném net namespace,
+/// čte se jeho /proc/<pid>/net/dev místo /sys/class/net exporteru.
+pub fn update(
+    metrics: &NetMetrics,
+    sys_root: &Path,
+    proc_root: &Path,
+    target_pid: Option<i32>,
+    iface: &str,
+) -> Result<()> {
     if iface.is_empty() {
         // monitoring vypnutý
         return Ok(());
     }
 
-    let base = PathBuf::from(format!("/sys/class/net/{}/statistics", iface));
-    if !base.exists() {
-        // interface v tomhle net namespace neexistuje - ticho po pěšině
+    let stats = match target_pid.filter(|&pid| foreign_netns(proc_root, pid)) {
+        Some(pid) => read_net_dev(&proc_root.join(pid.to_string()).join("net/dev"), iface),
+        None => read_sys_stats(sys_root, iface),
+    };
+    let Some(stats) = stats else {
+        // interface v tom net namespace neexistuje - ticho po pěšině
         return Ok(());
-    }
+    };
 
-    if let Some(v) = read_u64_lossy(&base.join("rx_bytes")) {
-        metrics.rx_bytes_total.set(v as f64);
+    {
+        let mut offsets = OFFSETS.lock().expect("net OFFSETS mutex poisoned");
+        for (file, gauge) in [
+            ("rx_bytes", &metrics.rx_bytes_total),
+            ("tx_bytes", &metrics.tx_bytes_total),
+            ("rx_packets", &metrics.rx_packets_total),
+            ("tx_packets", &metrics.tx_packets_total),
+            ("rx_errors", &metrics.rx_errors_total),
+            ("tx_errors", &metrics.tx_errors_total),
+            ("rx_dropped", &metrics.rx_dropped_total),
+            ("tx_dropped", &metrics.tx_dropped_total),
+        ] {
+            if let Some(&v) = stats.get(file) {
+                gauge.set(offsets.adjust(&format!("{iface}:{file}"), v as f64));
+            }
+        }
     }
-    if let Some(v) = read_u64_lossy(&base.join("tx_bytes")) {
-        metrics.tx_bytes_total.set(v as f64);
-    }
-    if let Some(v) = read_u64_lossy(&base.join("rx_packets")) {
-        metrics.rx_packets_total.set(v as f64);
-    }
-    if let Some(v) = read_u64_lossy(&base.join("tx_packets")) {
-        metrics.tx_packets_total.set(v as f64);
-    }
-    if let Some(v) = read_u64_lossy(&base.join("rx_errors")) {
-        metrics.rx_errors_total.set(v as f64);
-    }
-    if let Some(v) = read_u64_lossy(&base.join("tx_errors")) {
-        metrics.tx_errors_total.set(v as f64);
-    }
-    if let Some(v) = read_u64_lossy(&base.join("rx_dropped")) {
-        metrics.rx_dropped_total.set(v as f64);
-    }
-    if let Some(v) = read_u64_lossy(&base.join("tx_dropped")) {
-        metrics.tx_dropped_total.set(v as f64);
+
+    if let Some(ref rates) = metrics.rates {
+        let mut deltas = DELTAS.lock().expect("net DELTAS mutex poisoned");
+        for (file, gauge) in [
+            ("rx_bytes", &rates.rx_bytes_per_second),
+            ("tx_bytes", &rates.tx_bytes_per_second),
+            ("rx_packets", &rates.rx_packets_per_second),
+            ("tx_packets", &rates.tx_packets_per_second),
+        ] {
+            update_rate(&mut deltas, &stats, iface, file, gauge);
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn update_reads_sysfs_statistics() {
+        let cfg = Config::for_fixtures();
+        let metrics = NetMetrics::new(&Registry::new(), &cfg).unwrap();
+
+        update(
+            &metrics,
+            &cfg.sys_root,
+            &cfg.proc_root,
+            None,
+            &cfg.net_interface,
+        )
+        .unwrap();
+
+        assert_eq!(metrics.rx_bytes_total.get(), 128390012.0);
+        assert_eq!(metrics.tx_packets_total.get(), 180112.0);
+        assert_eq!(metrics.rx_dropped_total.get(), 12.0);
+    }
+
+    #[test]
+    fn read_net_dev_parses_interface_row() {
+        let net_dev = Config::for_fixtures().proc_root.join("net/dev");
+        let stats = read_net_dev(&net_dev, "eth0").unwrap();
+        assert_eq!(stats["rx_bytes"], 9182736);
+        assert_eq!(stats["tx_bytes"], 3456789);
+        assert!(read_net_dev(&net_dev, "wlan0").is_none());
+    }
+}
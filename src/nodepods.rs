@@ -0,0 +1,108 @@
+//! Node režim (NODE_MODE): exporter vidí hostitelský /proc (hostPID),
+//! projde všechny procesy, přes `/proc/<pid>/cgroup` je přiřadí podům
+//! a exportuje agregace per pod.
+//!
+//! Jméno a namespace podu se v cgroup cestě nevyskytují, jen UID. Bere se
+//! proto z adresářů kubeletu v PODS_LOG_DIR (`<namespace>_<pod>_<uid>`),
+//! stačí ho namountovat read-only. Bez něj zůstane `pod`/`namespace` prázdné.
+
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+
+use crate::{
+    kubepods,
+    metrics::{LiveSeries, PodProcessMetrics},
+    monotonic::CounterOffsets,
+    procfs::{self, GroupAggregate},
+};
+
+/// Offsety CPU counteru per pod - ukončený proces nesmí counter snížit.
+pub static OFFSETS: Lazy<Mutex<CounterOffsets>> = Lazy::new(|| Mutex::new(CounterOffsets::new()));
+
+/// UID podu → (namespace, jméno) z `<namespace>_<pod>_<uid>` adresářů.
+pub fn pod_names(pods_log_dir: &Path) -> HashMap<String, (String, String)> {
+    let Ok(entries) = std::fs::read_dir(pods_log_dir) else {
+        return HashMap::new();
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            // jména podů ani namespaců '_' obsahovat nesmí, UID je poslední
+            let mut parts = name.splitn(3, '_');
+            let (ns, pod, uid) = (parts.next()?, parts.next()?, parts.next()?);
+            Some((uid.to_string(), (ns.to_string(), pod.to_string())))
+        })
+        .collect()
+}
+
+pub fn update(metrics: &PodProcessMetrics, proc_root: &Path, pods_log_dir: &Path) -> Result<()> {
+    let mut pods: HashMap<String, GroupAggregate> = HashMap::new();
+
+    for pid in procfs::list_pids(proc_root)? {
+        let Some(uid) = kubepods::cgroup_path_of(proc_root, &pid.to_string())
+            .and_then(|p| kubepods::parse_cgroup_path(&p))
+            .and_then(|pc| pc.pod_uid)
+        else {
+            continue;
+        };
+        let Ok(sample) = procfs::read_proc_sample(proc_root, pid) else {
+            continue;
+        };
+
+        pods.entry(uid).or_default().add(&sample);
+    }
+
+    let names = pod_names(pods_log_dir);
+
+    let mut live = LiveSeries::default();
+    let mut offsets = OFFSETS.lock().expect("nodepods OFFSETS mutex poisoned");
+    for (uid, agg) in &pods {
+        let (ns, pod) = names
+            .get(uid)
+            .map(|(ns, pod)| (ns.as_str(), pod.as_str()))
+            .unwrap_or(("", ""));
+        let labels = [ns, pod, uid.as_str()];
+
+        metrics
+            .processes
+            .with_label_values(&labels)
+            .set(agg.processes);
+        metrics
+            .cpu_seconds
+            .with_label_values(&labels)
+            .set(offsets.adjust(uid, agg.cpu_seconds));
+        metrics
+            .rss_bytes
+            .with_label_values(&labels)
+            .set(agg.rss_bytes);
+        live.insert(&labels);
+    }
+
+    live.prune(&metrics.processes);
+    for vec in [&metrics.cpu_seconds, &metrics.rss_bytes] {
+        live.prune(vec);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn pod_names_from_pods_log_dir() {
+        let names = pod_names(&Config::for_fixtures().pods_log_dir);
+        assert_eq!(
+            names.get("7c1f4b2a-9d3e-4f5a-8b6c-1234567890ab"),
+            Some(&("payments".to_string(), "api-7d9f8c6b5-x2k4q".to_string()))
+        );
+        assert_eq!(names.len(), 1);
+        assert!(pod_names(Path::new("/nonexistent")).is_empty());
+    }
+}
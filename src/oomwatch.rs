@@ -0,0 +1,211 @@
+//! OOM counter z memory.events hlídaný přes inotify (OOM_WATCH).
+//!
+//! Snapshot memory.events jednou za interval stačí na celkové počty, ale
+//! krátký OOM kill mezi dvěma scrapy a následné znovuvytvoření cgroupy
+//! (restart kontejneru) counter resetuje dřív, než ho kdokoliv uvidí.
+//! Kernel při každé změně memory.events pošle IN_MODIFY, takže vlákno per
+//! root soubor přečte hned a přičte kladné delty oom / oom_kill do
+//! skutečného counteru. Update cyklus dělá totéž jako pojistku (soubor bez
+//! notifikací, watch ještě neběží).
+
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use tracing::{debug, warn};
+
+use crate::metrics::OomMetrics;
+
+/// Sledované položky memory.events
+const EVENTS: [&str; 2] = ["oom", "oom_kill"];
+
+struct Watch {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    /// Poslední syrové hodnoty oom / oom_kill
+    last: Option<[u64; 2]>,
+}
+
+/// Watch per jméno root
+static WATCHES: Lazy<Mutex<HashMap<String, Watch>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn read_events(path: &Path) -> Option<[u64; 2]> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut out = [0; 2];
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(key), Some(Ok(val))) = (parts.next(), parts.next().map(str::parse::<u64>)) else {
+            continue;
+        };
+        if let Some(i) = EVENTS.iter().position(|&e| e == key) {
+            out[i] = val;
+        }
+    }
+    Some(out)
+}
+
+/// Přečte memory.events a přičte nárůst od minula do counterů.
+fn sync(metrics: &OomMetrics, root_name: &str) {
+    let mut watches = WATCHES.lock().expect("oomwatch WATCHES mutex poisoned");
+    let Some(watch) = watches.get_mut(root_name) else {
+        return;
+    };
+    let Some(current) = read_events(&watch.path) else {
+        return;
+    };
+    for (i, event) in EVENTS.iter().enumerate() {
+        // první čtení = celý dosavadní počet; pokles = nová cgroupa od nuly
+        let delta = match watch.last {
+            Some(last) if current[i] >= last[i] => current[i] - last[i],
+            _ => current[i],
+        };
+        // série existuje i s nulou, ať rate() má od čeho počítat
+        let counter = metrics.events_total.with_label_values(&[root_name, event]);
+        if delta == 0 {
+            continue;
+        }
+        counter.inc_by(delta);
+        if *event == "oom_kill" && watch.last.is_some() {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs_f64())
+                .unwrap_or(0.0);
+            metrics
+                .last_oom_kill_timestamp_seconds
+                .with_label_values(&[root_name])
+                .set(now);
+        }
+    }
+    watch.last = Some(current);
+}
+
+/// Blokuje na inotify fd, dokud se watch nezastaví nebo soubor nezmizí.
+fn watch_loop(
+    metrics: OomMetrics,
+    root_name: String,
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+) -> Result<()> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).context("path contains NUL")?;
+    // SAFETY: inotify_init1() nepracuje s žádnou pamětí volajícího
+    let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error()).context("inotify_init1");
+    }
+    // SAFETY: fd je platný inotify fd, c_path je NUL-terminated CString,
+    // který žije po celé volání
+    let wd = unsafe { libc::inotify_add_watch(fd, c_path.as_ptr(), libc::IN_MODIFY) };
+    if wd < 0 {
+        let err = std::io::Error::last_os_error();
+        // SAFETY: fd vlastníme jen my a dál se už nepoužije
+        unsafe { libc::close(fd) };
+        return Err(err).with_context(|| format!("inotify_add_watch {}", path.display()));
+    }
+    debug!(root = %root_name, path = %path.display(), "watching memory.events");
+
+    let mut buf = [0u8; 4096];
+    let result = loop {
+        if stop.load(Ordering::Relaxed) {
+            break Ok(());
+        }
+        // timeout kvůli kontrole stop flagu
+        let mut pfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: pfd je platný &mut jediného pollfd, počet 1 mu odpovídá
+        let n = unsafe { libc::poll(&mut pfd, 1, 1000) };
+        if n < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            break Err(err).context("poll inotify fd");
+        }
+        if n == 0 {
+            continue;
+        }
+        // SAFETY: kernel zapíše nejvýš buf.len() bajtů do vlastněného bufferu
+        let len = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+        if len < 0 {
+            break Err(std::io::Error::last_os_error()).context("read inotify fd");
+        }
+        // IN_IGNORED = soubor (cgroupa) zmizel, watch končí
+        let mut ignored = false;
+        let mut offset = 0;
+        while offset + std::mem::size_of::<libc::inotify_event>() <= len as usize {
+            // SAFETY: podmínka cyklu hlídá, že celý inotify_event leží v prvních
+            // len bajtech buf; read_unaligned nevyžaduje zarovnání
+            let event = unsafe {
+                std::ptr::read_unaligned(buf.as_ptr().add(offset).cast::<libc::inotify_event>())
+            };
+            ignored |= event.mask & libc::IN_IGNORED != 0;
+            offset += std::mem::size_of::<libc::inotify_event>() + event.len as usize;
+        }
+        sync(&metrics, &root_name);
+        if ignored {
+            break Ok(());
+        }
+    };
+
+    // SAFETY: fd vlastníme jen my a po smyčce se už nepoužije
+    unsafe { libc::close(fd) };
+    result
+}
+
+/// Zajistí watch na memory.events v `dir` a synchronně dorovná countery.
+/// Při změně adresáře (CGROUP_FOLLOW_TARGET) se starý watch zastaví.
+pub fn update(metrics: &OomMetrics, root_name: &str, dir: &Path) {
+    let path = dir.join("memory.events");
+    {
+        let mut watches = WATCHES.lock().expect("oomwatch WATCHES mutex poisoned");
+        let running = watches
+            .get(root_name)
+            .is_some_and(|w| w.path == path && !w.stop.load(Ordering::Relaxed));
+        if !running && path.exists() {
+            if let Some(old) = watches.get(root_name) {
+                old.stop.store(true, Ordering::Relaxed);
+            }
+            let stop = Arc::new(AtomicBool::new(false));
+            // last se zachová - stejná cgroupa po pádu watche nemá počítat znovu
+            let last = watches
+                .get(root_name)
+                .filter(|w| w.path == path)
+                .and_then(|w| w.last);
+            watches.insert(
+                root_name.to_string(),
+                Watch {
+                    path: path.clone(),
+                    stop: stop.clone(),
+                    last,
+                },
+            );
+
+            let (metrics, root_name, path) = (metrics.clone(), root_name.to_string(), path.clone());
+            std::thread::spawn(move || {
+                match watch_loop(metrics, root_name.clone(), path, stop.clone()) {
+                    // cgroupa zmizela - další update watch spustí znovu
+                    Ok(()) => stop.store(true, Ordering::Relaxed),
+                    // bez inotify zůstane jen polling, znovu to nezkoušíme
+                    Err(e) => warn!(
+                        root = %root_name,
+                        error = %e,
+                        "memory.events watch failed, falling back to polling"
+                    ),
+                }
+            });
+        }
+    }
+    sync(metrics, root_name);
+}
@@ -0,0 +1,85 @@
+//! Maxima za klouzavá okna (PEAK_WINDOWS) - na rozdíl od memory.peak
+//! nejsou lifetime a nenulují se restartem podu (viz STATE_FILE).
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::metrics::WindowGauge;
+
+/// Monotónní fronta (timestamp, hodnota): hodnoty klesají od čela,
+/// takže maximum okna je vždy na čele a paměť zůstává malá.
+#[derive(Debug, Default)]
+struct WindowMax {
+    samples: VecDeque<(f64, f64)>,
+}
+
+impl WindowMax {
+    fn observe(&mut self, now: f64, window: Duration, value: f64) -> f64 {
+        while self.samples.back().is_some_and(|&(_, v)| v <= value) {
+            self.samples.pop_back();
+        }
+        self.samples.push_back((now, value));
+
+        let cutoff = now - window.as_secs_f64();
+        while self.samples.front().is_some_and(|&(ts, _)| ts < cutoff) {
+            self.samples.pop_front();
+        }
+
+        self.samples.front().map(|&(_, v)| v).unwrap_or(value)
+    }
+}
+
+/// Klíč = "<metrika>:<zdroj>:<okno>".
+static WINDOWS: Lazy<Mutex<HashMap<String, WindowMax>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn now_secs() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Kopie všech oken pro uložení do STATE_FILE.
+pub fn snapshot() -> HashMap<String, Vec<(f64, f64)>> {
+    WINDOWS
+        .lock()
+        .expect("peak WINDOWS mutex poisoned")
+        .iter()
+        .map(|(k, w)| (k.clone(), w.samples.iter().copied().collect()))
+        .collect()
+}
+
+/// Obnoví okna ze STATE_FILE; staré vzorky odpadnou při dalším observe().
+pub fn restore(saved: HashMap<String, Vec<(f64, f64)>>) {
+    let mut windows = WINDOWS.lock().expect("peak WINDOWS mutex poisoned");
+    for (key, samples) in saved {
+        windows.insert(
+            key,
+            WindowMax {
+                samples: samples.into(),
+            },
+        );
+    }
+}
+
+/// Započítá nový vzorek do všech oken a nastaví odpovídající *_peak_<okno> gauge.
+pub fn observe(key: &str, gauges: &[WindowGauge], value: f64) {
+    if gauges.is_empty() {
+        return;
+    }
+
+    let now = now_secs();
+    let mut windows = WINDOWS.lock().expect("peak WINDOWS mutex poisoned");
+    for wg in gauges {
+        let max = windows
+            .entry(format!("{key}:{}", wg.label))
+            .or_default()
+            .observe(now, wg.window, value);
+        wg.gauge.set(max);
+    }
+}
@@ -0,0 +1,150 @@
+//! Jednoduché lokální probe (PROBE_HTTP, PROBE_TCP, PROBE_DNS) spouštěné každý cyklus -
+//! "blackbox-exporter-lite" pro kontroly typu /health na localhostu.
+//!
+//! HTTP je ručně psaný HTTP/1.1 GET přes std TcpStream (jen http://, bez TLS),
+//! ať kvůli pár requestům netaháme HTTP klienta.
+
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use crate::{config::HttpProbe, metrics::ProbeMetrics};
+
+fn resolve(addr: &str) -> Result<SocketAddr> {
+    addr.to_socket_addrs()
+        .with_context(|| format!("resolve {addr}"))?
+        .next()
+        .with_context(|| format!("no address for {addr}"))
+}
+
+fn probe_tcp(addr: &str, timeout: Duration) -> Result<()> {
+    TcpStream::connect_timeout(&resolve(addr)?, timeout).context("connect")?;
+    Ok(())
+}
+
+/// Resolve přes systémový resolver (getaddrinfo) - ten timeout nemá, takže
+/// běží ve vlákně a po timeoutu se na něj přestane čekat.
+fn probe_dns(name: &str, timeout: Duration) -> Result<usize> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let host = name.to_string();
+    std::thread::spawn(move || {
+        let result = (host.as_str(), 0u16)
+            .to_socket_addrs()
+            .map(|addrs| addrs.count());
+        let _ = tx.send(result);
+    });
+
+    let count = rx
+        .recv_timeout(timeout)
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", timeout.as_secs_f64()))?
+        .context("resolve")?;
+    if count == 0 {
+        anyhow::bail!("no addresses");
+    }
+    Ok(count)
+}
+
+/// Vrátí HTTP status kód; success = 2xx/3xx rozhoduje volající.
+fn probe_http(probe: &HttpProbe, timeout: Duration) -> Result<u16> {
+    let mut stream =
+        TcpStream::connect_timeout(&resolve(&probe.addr)?, timeout).context("connect")?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: cgroup-runtime-exporter\r\nConnection: close\r\n\r\n",
+        probe.path, probe.host
+    )
+    .context("send request")?;
+
+    // stačí status line, tělo nás nezajímá
+    let mut buf = [0u8; 64];
+    let n = stream.read(&mut buf).context("read response")?;
+    let head = String::from_utf8_lossy(&buf[..n]);
+    head.split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .context("malformed HTTP status line")
+}
+
+pub fn update(
+    metrics: &ProbeMetrics,
+    http: &[HttpProbe],
+    tcp: &[String],
+    dns: &[String],
+    timeout: Duration,
+) {
+    for probe in http {
+        let start = Instant::now();
+        let result = probe_http(probe, timeout);
+        let labels = ["http", probe.url.as_str()];
+
+        metrics
+            .duration_seconds
+            .with_label_values(&labels)
+            .set(start.elapsed().as_secs_f64());
+        let success = match result {
+            Ok(code) => {
+                metrics
+                    .http_status_code
+                    .with_label_values(&[&probe.url])
+                    .set(code as i64);
+                (200..400).contains(&code)
+            }
+            Err(e) => {
+                debug!(url = %probe.url, error = %format!("{e:#}"), "http probe failed");
+                metrics
+                    .http_status_code
+                    .with_label_values(&[&probe.url])
+                    .set(0);
+                false
+            }
+        };
+        metrics
+            .success
+            .with_label_values(&labels)
+            .set(success as i64);
+    }
+
+    for name in dns {
+        let start = Instant::now();
+        let result = probe_dns(name, timeout);
+        let labels = ["dns", name.as_str()];
+
+        metrics
+            .duration_seconds
+            .with_label_values(&labels)
+            .set(start.elapsed().as_secs_f64());
+        if let Err(ref e) = result {
+            debug!(name = %name, error = %format!("{e:#}"), "dns probe failed");
+        }
+        metrics
+            .success
+            .with_label_values(&labels)
+            .set(result.is_ok() as i64);
+    }
+
+    for addr in tcp {
+        let start = Instant::now();
+        let result = probe_tcp(addr, timeout);
+        let labels = ["tcp", addr.as_str()];
+
+        metrics
+            .duration_seconds
+            .with_label_values(&labels)
+            .set(start.elapsed().as_secs_f64());
+        if let Err(ref e) = result {
+            debug!(addr = %addr, error = %format!("{e:#}"), "tcp probe failed");
+        }
+        metrics
+            .success
+            .with_label_values(&labels)
+            .set(result.is_ok() as i64);
+    }
+}
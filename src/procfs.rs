@@ -1,59 +1,219 @@
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use once_cell::sync::Lazy;
-use tracing::{debug, info};
+use serde::Serialize;
+use tracing::{debug, info, warn};
 
 use crate::config::ProcessTarget;
-use crate::metrics::ProcessMetrics;
+use crate::delta::DeltaTracker;
+use crate::hierarchy::Layout;
+use crate::metrics::{
+    LiveSeries, PidMetrics, ProcessMetrics, RootGaugeVec, RootIntGaugeVec, SmapsMetrics,
+    StallMetrics, TaskstatsMetrics, WindowGauge,
+};
+use crate::monotonic::CounterOffsets;
+use crate::peaks;
+use crate::taskstats;
+
+/// Předchozí CPU součet skupiny pro process_cpu_usage_cores.
+static DELTAS: Lazy<Mutex<DeltaTracker>> = Lazy::new(|| Mutex::new(DeltaTracker::new()));
+
+/// Stav per ProcessMetrics::scope (None = TARGET_PID*, Some = skupina).
+type PerScope<T> = HashMap<Option<String>, T>;
+
+/// Historie (čas updatu, existoval aspoň jeden proces) pro process_availability_ratio.
+type History = VecDeque<(Instant, bool)>;
+static AVAILABILITY: Lazy<Mutex<PerScope<History>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Start time per PID z minulého updatu pro process_restarts_total
+/// (chybí = ještě žádný update, první sada se nepočítá).
+static START_TIMES: Lazy<Mutex<PerScope<HashMap<i32, f64>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Offsety CPU/IO counterů skupiny - restart procesu je nesmí vrátit dolů.
+pub static OFFSETS: Lazy<Mutex<CounterOffsets>> = Lazy::new(|| Mutex::new(CounterOffsets::new()));
+
+/// Klíč stavu (offsety, delty, peaky) sady metrik - skupiny z TARGET_GROUPS
+/// nesmí sdílet stav s TARGET_PID* ani mezi sebou.
+fn scoped(metrics: &ProcessMetrics, key: &str) -> String {
+    match metrics.scope {
+        Some(ref group) => format!("group:{group}:{key}"),
+        None => key.to_string(),
+    }
+}
 
 fn read_to_string(path: &PathBuf) -> Result<String> {
     Ok(std::fs::read_to_string(path)?.trim().to_string())
 }
 
+/// Které pravidlo ProcessTarget daný PID vybralo.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchRule {
+    TargetPid,
+    TargetPidList,
+    RegexCmdline,
+    RegexComm,
+    /// Člen cgroupy z cgroup.procs (TARGET_CGROUP_PROCS)
+    CgroupProcs,
+    /// Potomek matchnutého procesu (FOLLOW_CHILDREN)
+    Descendant,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TargetMatch {
+    pub pid: i32,
+    pub rule: MatchRule,
+}
+
+/// Popis matchnutého procesu pro GET /targets.
+#[derive(Debug, Serialize)]
+pub struct TargetInfo {
+    pub pid: i32,
+    pub exists: bool,
+    pub comm: String,
+    pub cmdline: String,
+    pub start_time_seconds: Option<f64>,
+    pub rule: MatchRule,
+}
+
+/// Jeden vzorek /proc/<pid>/{stat,status,io}.
 #[derive(Default)]
-struct ProcSample {
-    cpu_user_seconds: f64,
-    cpu_system_seconds: f64,
-    start_time_seconds: Option<f64>,
+pub struct ProcSample {
+    pub cpu_user_seconds: f64,
+    pub cpu_system_seconds: f64,
+    pub start_time_seconds: Option<f64>,
+    /// Stav procesu (pole 3 /proc/<pid>/stat)
+    pub state: Option<char>,
 
-    mem_rss_bytes: f64,
-    mem_vms_bytes: f64,
-    mem_swap_bytes: f64,
+    pub mem_rss_bytes: f64,
+    pub mem_vms_bytes: f64,
+    pub mem_swap_bytes: f64,
+    /// High-water marky udržované kernelem (VmHWM, VmPeak)
+    pub mem_rss_hwm_bytes: f64,
+    pub mem_vms_peak_bytes: f64,
+    pub threads: f64,
+    /// /proc/<pid>/oom_score a oom_score_adj
+    pub oom_score: Option<f64>,
+    pub oom_score_adj: f64,
+    /// Počet položek /proc/<pid>/fd (0, když adresář nejde číst)
+    pub open_fds: f64,
+    /// Soft limit "Max open files" z /proc/<pid>/limits
+    pub max_fds: Option<f64>,
+    /// [soft, hard] pro každý z RESOURCE_LIMITS (unlimited = +Inf)
+    pub limits: [[Option<f64>; 2]; RESOURCE_LIMITS.len()],
 
-    io_rchar_bytes_total: f64,
-    io_wchar_bytes_total: f64,
-    io_syscr_total: f64,
-    io_syscw_total: f64,
-    io_read_bytes_total: f64,
-    io_write_bytes_total: f64,
-    io_cancelled_write_bytes_total: f64,
+    pub io_rchar_bytes_total: f64,
+    pub io_wchar_bytes_total: f64,
+    pub io_syscr_total: f64,
+    pub io_syscw_total: f64,
+    pub io_read_bytes_total: f64,
+    pub io_write_bytes_total: f64,
+    pub io_cancelled_write_bytes_total: f64,
+
+    pub voluntary_ctxt_switches: f64,
+    pub nonvoluntary_ctxt_switches: f64,
+
+    pub minor_page_faults: f64,
+    pub major_page_faults: f64,
+}
+
+/// Součty přes skupinu procesů (pod, kontejner) pro per-skupinové metriky.
+#[derive(Debug, Default)]
+pub struct GroupAggregate {
+    pub processes: i64,
+    pub cpu_seconds: f64,
+    pub rss_bytes: f64,
+}
+
+impl GroupAggregate {
+    pub fn add(&mut self, sample: &ProcSample) {
+        self.processes += 1;
+        self.cpu_seconds += sample.cpu_user_seconds + sample.cpu_system_seconds;
+        self.rss_bytes += sample.mem_rss_bytes;
+    }
 }
 
 /// Původní API - jeden konkrétní PID.
 /// Interně jen volá agregaci nad jedním PIDem.
 #[allow(dead_code)]
-pub fn update(metrics: &ProcessMetrics, pid: i32) -> Result<()> {
-    update_for_pids(metrics, &[pid])
+pub fn update(metrics: &ProcessMetrics, proc_root: &Path, pid: i32) -> Result<()> {
+    update_for_pids(metrics, proc_root, &[pid])
 }
 
+/// Restart = PID, který ze skupiny zmizel nebo má jiný start time (proces
+/// skončil a PID dostal jiný). Nový PID sám o sobě restart není (scale-up).
+fn track_restarts(metrics: &ProcessMetrics, current: HashMap<i32, f64>) {
+    let mut all = START_TIMES
+        .lock()
+        .expect("procfs START_TIMES mutex poisoned");
+    if let Some(last) = all.get(&metrics.scope) {
+        let restarted = last
+            .iter()
+            .filter(|(pid, start)| {
+                current
+                    .get(pid)
+                    .is_none_or(|cur| (cur - *start).abs() > 0.5)
+            })
+            .count();
+        metrics.restarts_total.inc_by(restarted as u64);
+    }
+    all.insert(metrics.scope.clone(), current);
+}
+
+/// Řádky /proc/<pid>/limits exportované jako process_resource_limit:
+/// (název v souboru, label resource).
+const RESOURCE_LIMITS: [(&str, &str); 4] = [
+    ("Max open files", "open_files"),
+    ("Max address space", "address_space"),
+    ("Max locked memory", "locked_memory"),
+    ("Max processes", "processes"),
+];
+
+/// Exportované stavy procesů (process_states).
+const PROCESS_STATES: [char; 6] = ['R', 'S', 'D', 'Z', 'T', 'I'];
+
 /// Aktualizuje metriky pro skupinu PIDů.
 ///
-/// - CPU a IO „countery“ se prostě sečtou.
+/// - CPU a IO „countery“ se sečtou a drží monotónní i přes restart procesu.
 /// - paměťové hodnoty se také sečtou.
 /// - start_time_seconds = nejstarší start time ze skupiny.
 /// - uptime_seconds = now - min(start_time).
-pub fn update_for_pids(metrics: &ProcessMetrics, pids: &[i32]) -> Result<()> {
+pub fn update_for_pids(metrics: &ProcessMetrics, proc_root: &Path, pids: &[i32]) -> Result<()> {
+    let present = pids
+        .iter()
+        .any(|pid| proc_root.join(pid.to_string()).is_dir());
+    update_availability(metrics.scope.clone(), &metrics.availability, present);
+
     let mut agg = ProcSample::default();
     let mut oldest_start: Option<f64> = None;
     let mut any = false;
+    let mut matched = 0i64;
+    let mut states = [0i64; PROCESS_STATES.len()];
+    let mut start_times: HashMap<i32, f64> = HashMap::new();
+    let mut min_max_fds: Option<f64> = None;
+    let mut min_limits = [[None::<f64>; 2]; RESOURCE_LIMITS.len()];
+    // (oom_score, oom_score_adj) procesu s nejvyšším skóre
+    let mut worst_oom: Option<(f64, f64)> = None;
+    let mut fds_ratio: f64 = 0.0;
 
     for &pid in pids {
-        let sample = read_proc_sample(pid)?;
+        // proces mezi resolve a čtením skončil (forknutý worker z FOLLOW_CHILDREN,
+        // krátký člen cgroup.procs) - zbytek skupiny se počítá dál
+        let sample = match read_proc_sample(proc_root, pid) {
+            Ok(sample) => sample,
+            Err(e) => {
+                debug!(pid, error = %e, "skipping unreadable target process");
+                continue;
+            }
+        };
         any = true;
+        matched += 1;
 
         agg.cpu_user_seconds += sample.cpu_user_seconds;
         agg.cpu_system_seconds += sample.cpu_system_seconds;
@@ -61,6 +221,35 @@ pub fn update_for_pids(metrics: &ProcessMetrics, pids: &[i32]) -> Result<()> {
         agg.mem_rss_bytes += sample.mem_rss_bytes;
         agg.mem_vms_bytes += sample.mem_vms_bytes;
         agg.mem_swap_bytes += sample.mem_swap_bytes;
+        // high-water marky se nesčítají - peaky členů nenastaly současně
+        agg.mem_rss_hwm_bytes = agg.mem_rss_hwm_bytes.max(sample.mem_rss_hwm_bytes);
+        agg.mem_vms_peak_bytes = agg.mem_vms_peak_bytes.max(sample.mem_vms_peak_bytes);
+        agg.threads += sample.threads;
+        if let Some(score) = sample.oom_score
+            && worst_oom.is_none_or(|(worst, _)| score > worst)
+        {
+            worst_oom = Some((score, sample.oom_score_adj));
+        }
+        // t (tracing stop) je pro účely alertů totéž co T
+        let state = sample.state.map(|s| if s == 't' { 'T' } else { s });
+        if let Some(i) = PROCESS_STATES.iter().position(|&s| Some(s) == state) {
+            states[i] += 1;
+        }
+        agg.open_fds += sample.open_fds;
+        // limity jsou per proces - ve skupině platí ten nejpřísnější
+        for (min, limit) in min_limits
+            .iter_mut()
+            .flatten()
+            .zip(sample.limits.iter().flatten())
+        {
+            if let Some(v) = *limit {
+                *min = Some(min.map_or(v, |cur: f64| cur.min(v)));
+            }
+        }
+        if let Some(max) = sample.max_fds.filter(|&m| m > 0.0) {
+            min_max_fds = Some(min_max_fds.map_or(max, |cur| cur.min(max)));
+            fds_ratio = fds_ratio.max(sample.open_fds / max);
+        }
 
         agg.io_rchar_bytes_total += sample.io_rchar_bytes_total;
         agg.io_wchar_bytes_total += sample.io_wchar_bytes_total;
@@ -69,8 +258,13 @@ pub fn update_for_pids(metrics: &ProcessMetrics, pids: &[i32]) -> Result<()> {
         agg.io_read_bytes_total += sample.io_read_bytes_total;
         agg.io_write_bytes_total += sample.io_write_bytes_total;
         agg.io_cancelled_write_bytes_total += sample.io_cancelled_write_bytes_total;
+        agg.minor_page_faults += sample.minor_page_faults;
+        agg.major_page_faults += sample.major_page_faults;
+        agg.voluntary_ctxt_switches += sample.voluntary_ctxt_switches;
+        agg.nonvoluntary_ctxt_switches += sample.nonvoluntary_ctxt_switches;
 
         if let Some(start) = sample.start_time_seconds {
+            start_times.insert(pid, start);
             oldest_start = Some(match oldest_start {
                 Some(cur) if cur <= start => cur,
                 _ => start,
@@ -78,44 +272,173 @@ pub fn update_for_pids(metrics: &ProcessMetrics, pids: &[i32]) -> Result<()> {
         }
     }
 
+    metrics.target_matched.set(matched);
+    track_restarts(metrics, start_times);
+
+    // Countery - i prázdná skupina jde přes offsety, ať zůstanou monotónní
+    let (cpu_user, cpu_system) = {
+        let mut offsets = OFFSETS.lock().expect("procfs OFFSETS mutex poisoned");
+        for (key, raw, gauge) in [
+            (
+                "io_rchar",
+                agg.io_rchar_bytes_total,
+                &metrics.io_rchar_bytes_total,
+            ),
+            (
+                "io_wchar",
+                agg.io_wchar_bytes_total,
+                &metrics.io_wchar_bytes_total,
+            ),
+            ("io_syscr", agg.io_syscr_total, &metrics.io_syscr_total),
+            ("io_syscw", agg.io_syscw_total, &metrics.io_syscw_total),
+            (
+                "io_read_bytes",
+                agg.io_read_bytes_total,
+                &metrics.io_read_bytes_total,
+            ),
+            (
+                "io_write_bytes",
+                agg.io_write_bytes_total,
+                &metrics.io_write_bytes_total,
+            ),
+            (
+                "io_cancelled_write_bytes",
+                agg.io_cancelled_write_bytes_total,
+                &metrics.io_cancelled_write_bytes_total,
+            ),
+            (
+                "minor_page_faults",
+                agg.minor_page_faults,
+                &metrics.minor_page_faults_total,
+            ),
+            (
+                "major_page_faults",
+                agg.major_page_faults,
+                &metrics.major_page_faults_total,
+            ),
+            (
+                "voluntary_ctxt_switches",
+                agg.voluntary_ctxt_switches,
+                &metrics.voluntary_ctxt_switches_total,
+            ),
+            (
+                "nonvoluntary_ctxt_switches",
+                agg.nonvoluntary_ctxt_switches,
+                &metrics.nonvoluntary_ctxt_switches_total,
+            ),
+        ] {
+            gauge.set(offsets.adjust(&scoped(metrics, key), raw));
+        }
+        (
+            offsets.adjust(&scoped(metrics, "cpu_user"), agg.cpu_user_seconds),
+            offsets.adjust(&scoped(metrics, "cpu_system"), agg.cpu_system_seconds),
+        )
+    };
+    metrics.cpu_user_seconds.set(cpu_user);
+    metrics.cpu_system_seconds.set(cpu_system);
+
+    // všechny stavy vždy, i prázdná skupina - nula je informace
+    for (state, count) in PROCESS_STATES.iter().zip(states) {
+        metrics
+            .states
+            .with_label_values(&[&state.to_string()])
+            .set(count);
+    }
+
+    if let Some(ref vec) = metrics.thread_cpu_seconds {
+        update_thread_cpu(vec, &scoped(metrics, "thread_cpu"), proc_root, pids);
+    }
+    if let Some(ref smaps) = metrics.smaps {
+        update_smaps(smaps, proc_root, pids);
+    }
+    if let Some(ref stalls) = metrics.stalls {
+        update_stalls(stalls, metrics.scope.clone(), proc_root, pids);
+    }
+    update_listening_ports(&metrics.listening_ports, proc_root, pids);
+
     if !any {
-        // Skupina je prázdná → všechno vynulujeme, ať je to jasně vidět.
-        metrics.cpu_user_seconds.set(0.0);
-        metrics.cpu_system_seconds.set(0.0);
+        // Skupina je prázdná → gauge vynulujeme, ať je to jasně vidět.
         metrics.start_time_seconds.set(0.0);
         metrics.uptime_seconds.set(0.0);
 
         metrics.mem_rss_bytes.set(0.0);
         metrics.mem_vms_bytes.set(0.0);
         metrics.mem_swap_bytes.set(0.0);
-
-        metrics.io_rchar_bytes_total.set(0.0);
-        metrics.io_wchar_bytes_total.set(0.0);
-        metrics.io_syscr_total.set(0.0);
-        metrics.io_syscw_total.set(0.0);
-        metrics.io_read_bytes_total.set(0.0);
-        metrics.io_write_bytes_total.set(0.0);
-        metrics.io_cancelled_write_bytes_total.set(0.0);
+        metrics.mem_rss_hwm_bytes.set(0.0);
+        metrics.mem_vms_peak_bytes.set(0.0);
+        metrics.threads.set(0.0);
+        metrics.oom_score.set(0.0);
+        metrics.oom_score_adj.set(0.0);
+        metrics.open_fds.set(0.0);
+        metrics.max_fds.set(0.0);
+        metrics.resource_limits.reset();
+        metrics.fds_utilization_ratio.set(0.0);
 
         return Ok(());
     }
 
-    metrics.cpu_user_seconds.set(agg.cpu_user_seconds);
-    metrics.cpu_system_seconds.set(agg.cpu_system_seconds);
+    if let Some(ref gauge) = metrics.cpu_usage_cores {
+        let total = cpu_user + cpu_system;
+        let mut deltas = DELTAS.lock().expect("procfs DELTAS mutex poisoned");
+        if let Some(cores) = deltas
+            .observe(&scoped(metrics, "cpu"), total)
+            .and_then(|d| d.per_second())
+        {
+            gauge.set(cores);
+        }
+    }
+
+    // z monotónních totalů - restart procesu nedá zápornou rychlost
+    if let Some(ref rates) = metrics.io_rates {
+        let mut deltas = DELTAS.lock().expect("procfs DELTAS mutex poisoned");
+        for (key, total, rate) in [
+            (
+                "io_read_bytes",
+                &metrics.io_read_bytes_total,
+                &rates.read_bytes_per_second,
+            ),
+            (
+                "io_write_bytes",
+                &metrics.io_write_bytes_total,
+                &rates.write_bytes_per_second,
+            ),
+        ] {
+            if let Some(v) = deltas
+                .observe(&scoped(metrics, key), total.get())
+                .and_then(|d| d.per_second())
+            {
+                rate.set(v);
+            }
+        }
+    }
 
     metrics.mem_rss_bytes.set(agg.mem_rss_bytes);
+    peaks::observe(
+        &scoped(metrics, "process:rss"),
+        &metrics.mem_rss_peaks,
+        agg.mem_rss_bytes,
+    );
     metrics.mem_vms_bytes.set(agg.mem_vms_bytes);
     metrics.mem_swap_bytes.set(agg.mem_swap_bytes);
-
-    metrics.io_rchar_bytes_total.set(agg.io_rchar_bytes_total);
-    metrics.io_wchar_bytes_total.set(agg.io_wchar_bytes_total);
-    metrics.io_syscr_total.set(agg.io_syscr_total);
-    metrics.io_syscw_total.set(agg.io_syscw_total);
-    metrics.io_read_bytes_total.set(agg.io_read_bytes_total);
-    metrics.io_write_bytes_total.set(agg.io_write_bytes_total);
-    metrics
-        .io_cancelled_write_bytes_total
-        .set(agg.io_cancelled_write_bytes_total);
+    metrics.mem_rss_hwm_bytes.set(agg.mem_rss_hwm_bytes);
+    metrics.mem_vms_peak_bytes.set(agg.mem_vms_peak_bytes);
+    metrics.threads.set(agg.threads);
+    let (oom_score, oom_score_adj) = worst_oom.unwrap_or_default();
+    metrics.oom_score.set(oom_score);
+    metrics.oom_score_adj.set(oom_score_adj);
+    metrics.open_fds.set(agg.open_fds);
+    metrics.max_fds.set(min_max_fds.unwrap_or(0.0));
+    for ((_, resource), limits) in RESOURCE_LIMITS.iter().zip(min_limits) {
+        for (kind, limit) in ["soft", "hard"].into_iter().zip(limits) {
+            if let Some(v) = limit {
+                metrics
+                    .resource_limits
+                    .with_label_values(&[resource, kind])
+                    .set(v);
+            }
+        }
+    }
+    metrics.fds_utilization_ratio.set(fds_ratio);
 
     if let Some(start_time) = oldest_start {
         metrics.start_time_seconds.set(start_time);
@@ -129,47 +452,548 @@ pub fn update_for_pids(metrics: &ProcessMetrics, pids: &[i32]) -> Result<()> {
     Ok(())
 }
 
+/// Jméno vlákna bez pořadového čísla (`GC Thread#3` → `GC Thread`,
+/// `pool-1-thread-17` → `pool-1-thread`) - jinak by každý worker poolu byl
+/// vlastní série.
+fn thread_group_name(comm: &str) -> String {
+    let name = comm
+        .trim_end_matches(|c: char| c.is_ascii_digit())
+        .trim_end_matches(['#', '-', '_', ' ', '/', ':']);
+    if name.is_empty() { comm } else { name }.to_string()
+}
+
+/// CPU vláken skupiny sečtené podle jména vlákna, monotónní přes ukončená
+/// vlákna (offset per jméno).
+fn update_thread_cpu(vec: &RootGaugeVec, scope: &str, proc_root: &Path, pids: &[i32]) {
+    let ticks_per_sec = ticks_per_second();
+    if ticks_per_sec <= 0.0 {
+        return;
+    }
+    let mut by_name: HashMap<String, f64> = HashMap::new();
+    for &pid in pids {
+        let Ok(tasks) = fs::read_dir(proc_root.join(pid.to_string()).join("task")) else {
+            continue;
+        };
+        for task in tasks.filter_map(|e| e.ok()) {
+            let Ok(stat) = fs::read_to_string(task.path().join("stat")) else {
+                continue;
+            };
+            // jméno vlákna smí obsahovat mezery i závorky - pole až za poslední ')'
+            let Some((head, rest)) = stat.rsplit_once(')') else {
+                continue;
+            };
+            let Some((_, comm)) = head.split_once('(') else {
+                continue;
+            };
+            // za ')' začíná pole 3 (state), utime/stime jsou pole 14/15
+            let fields: Vec<&str> = rest.split_whitespace().collect();
+            let ticks: u64 = fields
+                .get(11..13)
+                .map(|f| f.iter().filter_map(|v| v.parse::<u64>().ok()).sum())
+                .unwrap_or(0);
+            *by_name.entry(thread_group_name(comm)).or_default() += ticks as f64 / ticks_per_sec;
+        }
+    }
+
+    let mut live = LiveSeries::default();
+    let mut offsets = OFFSETS.lock().expect("procfs OFFSETS mutex poisoned");
+    for (name, seconds) in &by_name {
+        vec.with_label_values(&[name])
+            .set(offsets.adjust(&format!("{scope}:{name}"), *seconds));
+        live.insert(&[name]);
+    }
+    vec.prune(&live);
+}
+
+/// Od kdy je vlákno (pid, tid) nepřetržitě vidět v D (PROCESS_STALLS)
+type StalledSince = HashMap<(i32, i32), Instant>;
+static STALLED_SINCE: Lazy<Mutex<PerScope<StalledSince>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Vlákna skupiny v D a wchan nejdéle zaseklého. Vlákno, které mezi
+/// updaty D opustí, začíná při dalším zaseknutí od nuly.
+fn update_stalls(metrics: &StallMetrics, scope: Option<String>, proc_root: &Path, pids: &[i32]) {
+    let now = Instant::now();
+    let mut stalled: Vec<(i32, i32, String)> = Vec::new();
+    for &pid in pids {
+        let Ok(tasks) = fs::read_dir(proc_root.join(pid.to_string()).join("task")) else {
+            continue;
+        };
+        for task in tasks.filter_map(|e| e.ok()) {
+            let Some(tid) = task
+                .file_name()
+                .to_str()
+                .and_then(|s| s.parse::<i32>().ok())
+            else {
+                continue;
+            };
+            let Ok(stat) = fs::read_to_string(task.path().join("stat")) else {
+                continue;
+            };
+            let Some((head, rest)) = stat.rsplit_once(')') else {
+                continue;
+            };
+            if rest.split_whitespace().next() != Some("D") {
+                continue;
+            }
+            let comm = head.split_once('(').map(|(_, c)| c).unwrap_or_default();
+            stalled.push((pid, tid, comm.to_string()));
+        }
+    }
+
+    let mut all = STALLED_SINCE
+        .lock()
+        .expect("procfs STALLED_SINCE mutex poisoned");
+    let since = all.entry(scope).or_default();
+    since.retain(|key, _| stalled.iter().any(|(pid, tid, _)| (*pid, *tid) == *key));
+    for (pid, tid, _) in &stalled {
+        since.entry((*pid, *tid)).or_insert(now);
+    }
+    let longest = stalled
+        .iter()
+        .min_by_key(|(pid, tid, _)| since.get(&(*pid, *tid)).copied().unwrap_or(now));
+
+    metrics.uninterruptible_tasks.set(stalled.len() as i64);
+    let mut live = LiveSeries::default();
+    if let Some((pid, tid, comm)) = longest {
+        let stalled_for = since
+            .get(&(*pid, *tid))
+            .map(|t| now.duration_since(*t).as_secs_f64())
+            .unwrap_or(0.0);
+        metrics.longest_stall_seconds.set(stalled_for);
+        // wchan čte jen vlastník / CAP_SYS_PTRACE, jinak "0" - kernel symbol neprozradí
+        let wchan = fs::read_to_string(
+            proc_root
+                .join(pid.to_string())
+                .join("task")
+                .join(tid.to_string())
+                .join("wchan"),
+        )
+        .map(|s| s.trim().to_string())
+        .ok()
+        .filter(|s| !s.is_empty() && s != "0")
+        .unwrap_or_else(|| "unknown".to_string());
+        let (pid, tid) = (pid.to_string(), tid.to_string());
+        let labels = [pid.as_str(), tid.as_str(), comm, wchan.as_str()];
+        metrics.stalled_task_info.with_label_values(&labels).set(1);
+        live.insert(&labels);
+    } else {
+        metrics.longest_stall_seconds.set(0.0);
+    }
+    // jen nejdéle zaseklé vlákno - předchozí zmizí
+    metrics.stalled_task_info.prune(&live);
+}
+
+/// Inody socketů z /proc/<pid>/fd (`socket:[12345]`).
+fn socket_inodes(proc_root: &Path, pid: i32) -> HashSet<u64> {
+    let Ok(entries) = fs::read_dir(proc_root.join(pid.to_string()).join("fd")) else {
+        return HashSet::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| fs::read_link(e.path()).ok())
+        .filter_map(|target| {
+            target
+                .to_str()?
+                .strip_prefix("socket:[")?
+                .strip_suffix(']')?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+/// (proto, inode → port) jedné net/tcp{,6} tabulky.
+type ListenTable = (&'static str, HashMap<u64, u16>);
+
+/// Porty v LISTEN, jejichž socket drží některý z cílových procesů. Tabulky
+/// se čtou z net namespace procesu (/proc/<pid>/net), každý namespace jednou.
+fn update_listening_ports(vec: &RootIntGaugeVec, proc_root: &Path, pids: &[i32]) {
+    let mut tables: HashMap<String, Vec<ListenTable>> = HashMap::new();
+    let mut ports: HashSet<(u16, &'static str)> = HashSet::new();
+
+    for &pid in pids {
+        let inodes = socket_inodes(proc_root, pid);
+        if inodes.is_empty() {
+            continue;
+        }
+        let pid_dir = proc_root.join(pid.to_string());
+        // bez přístupu k ns/net se tabulky čtou per PID
+        let netns = fs::read_link(pid_dir.join("ns/net"))
+            .map(|l| l.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| format!("pid:{pid}"));
+        let tables = tables.entry(netns).or_insert_with(|| {
+            [("tcp", "net/tcp"), ("tcp6", "net/tcp6")]
+                .into_iter()
+                .filter_map(|(proto, file)| {
+                    crate::tcp::listening_ports(&pid_dir.join(file))
+                        .ok()
+                        .map(|t| (proto, t))
+                })
+                .collect()
+        });
+        for (proto, table) in tables.iter() {
+            for inode in &inodes {
+                if let Some(&port) = table.get(inode) {
+                    ports.insert((port, proto));
+                }
+            }
+        }
+    }
+
+    let mut live = LiveSeries::default();
+    for (port, proto) in ports {
+        let port = port.to_string();
+        vec.with_label_values(&[&port, proto]).set(1);
+        live.insert(&[&port, proto]);
+    }
+    vec.prune(&live);
+}
+
+/// Součet smaps_rollup přes skupinu (PROCESS_SMAPS). Proces bez oprávnění
+/// nebo mezitím ukončený se přeskočí.
+fn update_smaps(metrics: &SmapsMetrics, proc_root: &Path, pids: &[i32]) {
+    // Pss, Pss_Anon, Private_Clean, Private_Dirty, Shared_Clean, Shared_Dirty
+    const KEYS: [&str; 6] = [
+        "Pss:",
+        "Pss_Anon:",
+        "Private_Clean:",
+        "Private_Dirty:",
+        "Shared_Clean:",
+        "Shared_Dirty:",
+    ];
+    let mut kb = [0u64; KEYS.len()];
+    for &pid in pids {
+        let Ok(content) = fs::read_to_string(proc_root.join(pid.to_string()).join("smaps_rollup"))
+        else {
+            continue;
+        };
+        for line in content.lines() {
+            if let Some(i) = KEYS.iter().position(|k| line.starts_with(k)) {
+                kb[i] += grab_kb(line);
+            }
+        }
+    }
+    let bytes = kb.map(|v| (v * 1024) as f64);
+    metrics.pss_bytes.set(bytes[0]);
+    metrics.pss_anon_bytes.set(bytes[1]);
+    metrics.private_clean_bytes.set(bytes[2]);
+    metrics.private_dirty_bytes.set(bytes[3]);
+    metrics.uss_bytes.set(bytes[2] + bytes[3]);
+    metrics.shared_bytes.set(bytes[4] + bytes[5]);
+}
+
+/// Započítá jeden update do okna dostupnosti a nastaví ratio.
+fn update_availability(scope: Option<String>, wg: &WindowGauge, present: bool) {
+    let now = Instant::now();
+    let mut all = AVAILABILITY
+        .lock()
+        .expect("procfs AVAILABILITY mutex poisoned");
+    let history = all.entry(scope).or_default();
+    history.push_back((now, present));
+    while history
+        .front()
+        .is_some_and(|&(at, _)| now.duration_since(at) > wg.window)
+    {
+        history.pop_front();
+    }
+
+    let up = history.iter().filter(|&&(_, p)| p).count();
+    wg.gauge.set(up as f64 / history.len() as f64);
+}
+
+/// PIDy, pro které je vyplněné process_info - obnovuje se jen při změně.
+static INFO_PIDS: Lazy<Mutex<Vec<i32>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Max. délka cmdline v labelu process_info.
+const INFO_CMDLINE_MAX_CHARS: usize = 128;
+
+/// Znovu vyplní process_info, když se změnila sada PIDů.
+fn update_info(vec: &prometheus::IntGaugeVec, proc_root: &Path, pids: &[i32]) {
+    let mut sorted = pids.to_vec();
+    sorted.sort_unstable();
+    let mut last = INFO_PIDS.lock().expect("procfs INFO_PIDS mutex poisoned");
+    if *last == sorted {
+        return;
+    }
+
+    let mut live = LiveSeries::default();
+    for &pid in &sorted {
+        let pid_dir = proc_root.join(pid.to_string());
+        let Ok(status) = fs::read_to_string(pid_dir.join("status")) else {
+            continue;
+        };
+        // Uid: real effective saved fs
+        let uid = status
+            .lines()
+            .find_map(|l| l.strip_prefix("Uid:"))
+            .and_then(|v| v.split_whitespace().next())
+            .unwrap_or_default();
+        let comm = fs::read_to_string(pid_dir.join("comm")).unwrap_or_default();
+        // exe cizího procesu vyžaduje ptrace oprávnění - pak zůstane prázdné
+        let exe = fs::read_link(pid_dir.join("exe"))
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let cmdline: String = fs::read_to_string(pid_dir.join("cmdline"))
+            .unwrap_or_default()
+            .trim_end_matches('\0')
+            .replace('\0', " ")
+            .chars()
+            .take(INFO_CMDLINE_MAX_CHARS)
+            .collect();
+        let pid = pid.to_string();
+        let labels = [
+            pid.as_str(),
+            comm.trim(),
+            exe.as_str(),
+            uid,
+            cmdline.as_str(),
+        ];
+        vec.with_label_values(&labels).set(1);
+        live.insert(&labels);
+    }
+    live.prune(vec);
+    *last = sorted;
+}
+
+/// Série per proces (PROCESS_METRICS_MODE=per_pid). Hodnoty jsou syrové -
+/// restart procesu je nový PID a tím i nová série.
+pub fn update_per_pid(metrics: &PidMetrics, proc_root: &Path, pids: &[i32]) {
+    update_info(&metrics.info, proc_root, pids);
+
+    let mut live = LiveSeries::default();
+    for &pid in pids {
+        let Ok(sample) = read_proc_sample(proc_root, pid) else {
+            continue;
+        };
+        let comm = fs::read_to_string(proc_root.join(pid.to_string()).join("comm"))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let pid = pid.to_string();
+        let labels = [pid.as_str(), comm.as_str()];
+        live.insert(&labels);
+
+        for (v, value) in [
+            (&metrics.cpu_user_seconds, sample.cpu_user_seconds),
+            (&metrics.cpu_system_seconds, sample.cpu_system_seconds),
+            (&metrics.mem_rss_bytes, sample.mem_rss_bytes),
+            (&metrics.mem_vms_bytes, sample.mem_vms_bytes),
+            (&metrics.mem_swap_bytes, sample.mem_swap_bytes),
+            (&metrics.io_read_bytes_total, sample.io_read_bytes_total),
+            (&metrics.io_write_bytes_total, sample.io_write_bytes_total),
+        ] {
+            v.with_label_values(&labels).set(value);
+        }
+        if let Some(start) = sample.start_time_seconds {
+            metrics
+                .start_time_seconds
+                .with_label_values(&labels)
+                .set(start);
+        }
+    }
+
+    for v in [
+        &metrics.cpu_user_seconds,
+        &metrics.cpu_system_seconds,
+        &metrics.start_time_seconds,
+        &metrics.mem_rss_bytes,
+        &metrics.mem_vms_bytes,
+        &metrics.mem_swap_bytes,
+        &metrics.io_read_bytes_total,
+        &metrics.io_write_bytes_total,
+    ] {
+        live.prune(v);
+    }
+}
+
 /// Aktualizace metrik podle ProcessTarget:
 ///  - Single(pid)  → agregace nad jedním PIDem (kompatibilní s TARGET_PID)
 ///  - PidList([...]) → agregace nad explicitním seznamem PIDů
 ///  - Regex(re) → najdeme PIDy v /proc podle regexu a agregujeme přes ně
-pub fn update_for_target(metrics: &ProcessMetrics, target: &ProcessTarget) -> Result<()> {
-    match target {
-        ProcessTarget::Single(pid) => update_for_pids(metrics, &[*pid]),
-        ProcessTarget::PidList(pids) => update_for_pids(metrics, pids),
-        ProcessTarget::Regex(re) => {
-            let pids = find_pids_by_regex(re)?;
-            update_for_pids(metrics, &pids)
+///
+/// S `per_pid` navíc série per proces nad stejnou sadou PIDů, s
+/// `follow_children` i přes potomky matchnutých procesů. Delay accounting
+/// (`taskstats`) je best-effort - chyba netlinku jen zaloguje warning.
+pub fn update_for_target(
+    metrics: &ProcessMetrics,
+    per_pid: Option<&PidMetrics>,
+    taskstats: Option<&TaskstatsMetrics>,
+    proc_root: &Path,
+    target: &ProcessTarget,
+    follow_children: bool,
+) -> Result<()> {
+    let mut matches = resolve_target(proc_root, target)?;
+    if follow_children {
+        matches = with_descendants(proc_root, matches)?;
+    }
+    let pids: Vec<i32> = matches.iter().map(|m| m.pid).collect();
+    if let Some(per_pid) = per_pid {
+        update_per_pid(per_pid, proc_root, &pids);
+    }
+    if let Some(taskstats) = taskstats
+        && let Err(e) = taskstats::update(taskstats, proc_root, &pids)
+    {
+        warn!(error = %e, root_cause = %e.root_cause(), "taskstats delay accounting failed");
+    }
+    update_for_pids(metrics, proc_root, &pids)
+}
+
+/// Rozbalí ProcessTarget na konkrétní PIDy včetně pravidla, které je vybralo.
+pub fn resolve_target(proc_root: &Path, target: &ProcessTarget) -> Result<Vec<TargetMatch>> {
+    let matches = match target {
+        ProcessTarget::Single(pid) => vec![TargetMatch {
+            pid: *pid,
+            rule: MatchRule::TargetPid,
+        }],
+        ProcessTarget::PidList(pids) => pids
+            .iter()
+            .map(|&pid| TargetMatch {
+                pid,
+                rule: MatchRule::TargetPidList,
+            })
+            .collect(),
+        ProcessTarget::Regex { re, cache_ttl } => cached_pids_by_regex(proc_root, re, *cache_ttl)?,
+        // jen přímí členové - procesy v podřízených cgroupách tu nejsou
+        ProcessTarget::Cgroup(dir) => Layout::detect(dir)
+            .read("cgroup.procs")
+            .with_context(|| format!("read cgroup.procs in {}", dir.display()))?
+            .lines()
+            .filter_map(|l| l.trim().parse::<i32>().ok())
+            .map(|pid| TargetMatch {
+                pid,
+                rule: MatchRule::CgroupProcs,
+            })
+            .collect(),
+    };
+    Ok(matches)
+}
+
+/// PPID z pole 4 /proc/<pid>/stat (comm smí obsahovat mezery i závorky).
+fn read_ppid(proc_root: &Path, pid: i32) -> Option<i32> {
+    let content = fs::read_to_string(proc_root.join(pid.to_string()).join("stat")).ok()?;
+    let (_, rest) = content.rsplit_once(')')?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Přidá k matchům všechny jejich potomky (scan PPID přes celý /proc -
+/// /proc/<pid>/task/*/children nemusí být v kernelu zapnuté).
+pub fn with_descendants(proc_root: &Path, matches: Vec<TargetMatch>) -> Result<Vec<TargetMatch>> {
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for pid in list_pids(proc_root)? {
+        if let Some(ppid) = read_ppid(proc_root, pid) {
+            children.entry(ppid).or_default().push(pid);
         }
     }
+
+    let mut seen: HashSet<i32> = matches.iter().map(|m| m.pid).collect();
+    let mut stack: Vec<i32> = matches.iter().map(|m| m.pid).collect();
+    let mut result = matches;
+    while let Some(pid) = stack.pop() {
+        for &child in children.get(&pid).into_iter().flatten() {
+            if seen.insert(child) {
+                result.push(TargetMatch {
+                    pid: child,
+                    rule: MatchRule::Descendant,
+                });
+                stack.push(child);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Detailní popis aktuálně matchnutých procesů (GET /targets).
+pub fn describe_targets(
+    proc_root: &Path,
+    target: &ProcessTarget,
+    follow_children: bool,
+) -> Result<Vec<TargetInfo>> {
+    let mut result = Vec::new();
+
+    let mut matches = resolve_target(proc_root, target)?;
+    if follow_children {
+        matches = with_descendants(proc_root, matches)?;
+    }
+    for m in matches {
+        let pid_dir = proc_root.join(m.pid.to_string());
+        let comm = fs::read_to_string(pid_dir.join("comm"))
+            .map(|s| s.trim().to_string())
+            .ok();
+        let cmdline = fs::read_to_string(pid_dir.join("cmdline"))
+            .unwrap_or_default()
+            .trim_end_matches('\0')
+            .replace('\0', " ");
+
+        result.push(TargetInfo {
+            pid: m.pid,
+            exists: comm.is_some(),
+            comm: comm.unwrap_or_default(),
+            cmdline,
+            start_time_seconds: read_start_time(proc_root, m.pid),
+            rule: m.rule,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Boot time (btime) z /proc/stat v sekundách od epochy.
+fn boot_time(proc_root: &Path) -> Result<u64> {
+    Ok(std::fs::read_to_string(proc_root.join("stat"))?
+        .lines()
+        .find(|l| l.starts_with("btime "))
+        .and_then(|l| l.split_whitespace().nth(1))
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0))
+}
+
+fn ticks_per_second() -> f64 {
+    let t = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    t as f64
+}
+
+/// Start time procesu (sekundy od epochy) z pole 22 /proc/<pid>/stat.
+fn read_start_time(proc_root: &Path, pid: i32) -> Option<f64> {
+    let content = fs::read_to_string(proc_root.join(format!("{}/stat", pid))).ok()?;
+    let start_ticks = content.split_whitespace().nth(21)?.parse::<u64>().ok()? as f64;
+    let ticks_per_sec = ticks_per_second();
+    if ticks_per_sec <= 0.0 {
+        return None;
+    }
+    let boot = boot_time(proc_root).ok()?;
+    Some(boot as f64 + start_ticks / ticks_per_sec)
 }
 
-fn read_proc_sample(pid: i32) -> Result<ProcSample> {
+pub fn read_proc_sample(proc_root: &Path, pid: i32) -> Result<ProcSample> {
     let mut sample = ProcSample::default();
+    let pid_dir = proc_root.join(pid.to_string());
 
     // --- /proc/<pid>/stat ---
-    let stat_path = PathBuf::from(format!("/proc/{}/stat", pid));
+    let stat_path = pid_dir.join("stat");
     let content = read_to_string(&stat_path).context("read /proc/<pid>/stat")?;
     let parts: Vec<&str> = content.split_whitespace().collect();
+    // comm smí obsahovat mezery - stav je první pole za poslední ')'
+    sample.state = content
+        .rsplit_once(')')
+        .and_then(|(_, rest)| rest.trim_start().chars().next());
 
     if parts.len() > 21 {
+        // proc(5): minflt=10, majflt=12 (indexy 9,11)
+        sample.minor_page_faults = parts[9].parse::<u64>().unwrap_or(0) as f64;
+        sample.major_page_faults = parts[11].parse::<u64>().unwrap_or(0) as f64;
+
         // proc(5): utime=14, stime=15, starttime=22 (indexy 13,14,21)
         let utime_ticks: f64 = parts[13].parse::<u64>().unwrap_or(0) as f64;
         let stime_ticks: f64 = parts[14].parse::<u64>().unwrap_or(0) as f64;
         let start_ticks: f64 = parts[21].parse::<u64>().unwrap_or(0) as f64;
 
-        let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) } as f64;
+        let ticks_per_sec = ticks_per_second();
         if ticks_per_sec > 0.0 {
             sample.cpu_user_seconds = utime_ticks / ticks_per_sec;
             sample.cpu_system_seconds = stime_ticks / ticks_per_sec;
 
             // boot time z /proc/stat (btime)
-            let boot_time = std::fs::read_to_string("/proc/stat")?
-                .lines()
-                .find(|l| l.starts_with("btime "))
-                .and_then(|l| l.split_whitespace().nth(1))
-                .and_then(|v| v.parse::<u64>().ok())
-                .unwrap_or(0);
+            let boot_time = boot_time(proc_root)?;
 
             let start_time = boot_time as f64 + start_ticks / ticks_per_sec;
             sample.start_time_seconds = Some(start_time);
@@ -177,11 +1001,13 @@ fn read_proc_sample(pid: i32) -> Result<ProcSample> {
     }
 
     // --- /proc/<pid>/status ---
-    let status_path = PathBuf::from(format!("/proc/{}/status", pid));
+    let status_path = pid_dir.join("status");
     let content = read_to_string(&status_path).context("read /proc/<pid>/status")?;
     let mut rss_kb = 0u64;
     let mut vms_kb = 0u64;
     let mut swap_kb = 0u64;
+    let mut hwm_kb = 0u64;
+    let mut peak_kb = 0u64;
 
     for line in content.lines() {
         if line.starts_with("VmRSS:") {
@@ -190,19 +1016,60 @@ fn read_proc_sample(pid: i32) -> Result<ProcSample> {
             vms_kb = grab_kb(line);
         } else if line.starts_with("VmSwap:") {
             swap_kb = grab_kb(line);
+        } else if line.starts_with("VmHWM:") {
+            hwm_kb = grab_kb(line);
+        } else if line.starts_with("VmPeak:") {
+            peak_kb = grab_kb(line);
+        } else if line.starts_with("Threads:") {
+            sample.threads = grab_kb(line) as f64;
+        } else if line.starts_with("voluntary_ctxt_switches:") {
+            sample.voluntary_ctxt_switches = grab_kb(line) as f64;
+        } else if line.starts_with("nonvoluntary_ctxt_switches:") {
+            sample.nonvoluntary_ctxt_switches = grab_kb(line) as f64;
         }
     }
 
     sample.mem_rss_bytes = (rss_kb * 1024) as f64;
     sample.mem_vms_bytes = (vms_kb * 1024) as f64;
     sample.mem_swap_bytes = (swap_kb * 1024) as f64;
+    sample.mem_rss_hwm_bytes = (hwm_kb * 1024) as f64;
+    sample.mem_vms_peak_bytes = (peak_kb * 1024) as f64;
 
-    // --- /proc/<pid>/io ---
-    let io_path = PathBuf::from(format!("/proc/{}/io", pid));
-    let content = match read_to_string(&io_path) {
-        Ok(c) => c,
-        Err(_) => String::new(), // některá prostředí /proc/<pid>/io nemají - IO metriky zůstanou 0
+    // --- /proc/<pid>/oom_score{,_adj} ---
+    let read_f64 = |name: &str| {
+        fs::read_to_string(pid_dir.join(name))
+            .ok()
+            .and_then(|s| s.trim().parse::<f64>().ok())
     };
+    sample.oom_score = read_f64("oom_score");
+    sample.oom_score_adj = read_f64("oom_score_adj").unwrap_or(0.0);
+
+    // --- /proc/<pid>/fd + limits ---
+    // fd adresář cizího procesu vyžaduje ptrace oprávnění - pak zůstane 0
+    sample.open_fds = fs::read_dir(pid_dir.join("fd"))
+        .map(|entries| entries.count() as f64)
+        .unwrap_or(0.0);
+    let limits = fs::read_to_string(pid_dir.join("limits")).unwrap_or_default();
+    for line in limits.lines() {
+        let Some((i, rest)) = RESOURCE_LIMITS
+            .iter()
+            .enumerate()
+            .find_map(|(i, (name, _))| line.strip_prefix(name).map(|rest| (i, rest)))
+        else {
+            continue;
+        };
+        let mut values = rest.split_whitespace().map(|v| match v {
+            "unlimited" => Some(f64::INFINITY),
+            v => v.parse::<f64>().ok(),
+        });
+        sample.limits[i] = [values.next().flatten(), values.next().flatten()];
+    }
+    sample.max_fds = sample.limits[0][0];
+
+    // --- /proc/<pid>/io ---
+    let io_path = pid_dir.join("io");
+    // některá prostředí /proc/<pid>/io nemají - IO metriky zůstanou 0
+    let content = read_to_string(&io_path).unwrap_or_default();
 
     let mut rchar = 0u64;
     let mut wchar = 0u64;
@@ -277,10 +1144,10 @@ fn should_log_regex_match() -> bool {
     }
 }
 
-fn find_pids_by_regex(re: &regex::Regex) -> Result<Vec<i32>> {
-    let mut result = Vec::new();
-
-    for entry in fs::read_dir("/proc")? {
+/// Všechny PIDy viditelné v proc_root.
+pub fn list_pids(proc_root: &Path) -> Result<Vec<i32>> {
+    let mut pids = Vec::new();
+    for entry in fs::read_dir(proc_root)? {
         let entry = entry?;
         let name = entry.file_name();
         let name = name.to_string_lossy();
@@ -290,35 +1157,83 @@ fn find_pids_by_regex(re: &regex::Regex) -> Result<Vec<i32>> {
             continue;
         }
 
-        let pid: i32 = match name.parse() {
-            Ok(p) => p,
-            Err(_) => continue,
-        };
+        if let Ok(pid) = name.parse::<i32>() {
+            pids.push(pid);
+        }
+    }
+    Ok(pids)
+}
 
-        // Nejprve zkusíme cmdline
-        let cmdline_path = format!("/proc/{}/cmdline", pid);
-        let cmdline = fs::read_to_string(&cmdline_path).unwrap_or_default();
-        let cmdline_pretty = cmdline.replace('\0', " ");
+/// Výsledek posledního plného průchodu /proc pro TARGET_PID_REGEXP_CACHE_SECS.
+struct RegexCache {
+    at: Instant,
+    pattern: String,
+    matches: Vec<TargetMatch>,
+}
 
-        debug!(pid, ?cmdline_pretty, "testing pid against regex");
+static REGEX_CACHE: Lazy<Mutex<Option<RegexCache>>> = Lazy::new(|| Mutex::new(None));
 
-        if re.is_match(&cmdline_pretty) {
-            result.push(pid);
-            continue;
-        }
+/// Jako find_pids_by_regex, ale plný průchod /proc jen jednou za `ttl`.
+/// Mezi tím se jen ověří, že nacachované PIDy pořád existují a matchují -
+/// nové procesy se tak objeví nejpozději po `ttl`.
+fn cached_pids_by_regex(
+    proc_root: &Path,
+    re: &regex::Regex,
+    ttl: Duration,
+) -> Result<Vec<TargetMatch>> {
+    if ttl.is_zero() {
+        return find_pids_by_regex(proc_root, re);
+    }
+    let mut cache = REGEX_CACHE
+        .lock()
+        .expect("procfs REGEX_CACHE mutex poisoned");
+    if let Some(ref mut c) = *cache
+        && c.pattern == re.as_str()
+        && c.at.elapsed() < ttl
+    {
+        c.matches
+            .retain(|m| match_rule(proc_root, m.pid, re).is_some());
+        return Ok(c.matches.clone());
+    }
 
-        // Fallback na /proc/<pid>/comm - typicky obsahuje „nginx“ atd.
-        let comm_path = format!("/proc/{}/comm", pid);
-        let comm = fs::read_to_string(&comm_path).unwrap_or_default();
-        let comm_trimmed = comm.trim();
+    let matches = find_pids_by_regex(proc_root, re)?;
+    *cache = Some(RegexCache {
+        at: Instant::now(),
+        pattern: re.as_str().to_string(),
+        matches: matches.clone(),
+    });
+    Ok(matches)
+}
 
-        debug!(pid, ?comm_trimmed, "testing comm against regex");
+/// Podle čeho PID matchuje regex - nejprve cmdline, pak comm.
+fn match_rule(proc_root: &Path, pid: i32, re: &regex::Regex) -> Option<MatchRule> {
+    // Nejprve zkusíme cmdline
+    let cmdline_path = proc_root.join(format!("{}/cmdline", pid));
+    let cmdline = fs::read_to_string(&cmdline_path).ok()?;
+    let cmdline_pretty = cmdline.replace('\0', " ");
 
-        if re.is_match(comm_trimmed) {
-            result.push(pid);
-        }
+    debug!(pid, ?cmdline_pretty, "testing pid against regex");
+
+    if re.is_match(&cmdline_pretty) {
+        return Some(MatchRule::RegexCmdline);
     }
 
+    // Fallback na /proc/<pid>/comm - typicky obsahuje „nginx“ atd.
+    let comm_path = proc_root.join(format!("{}/comm", pid));
+    let comm = fs::read_to_string(&comm_path).unwrap_or_default();
+    let comm_trimmed = comm.trim();
+
+    debug!(pid, ?comm_trimmed, "testing comm against regex");
+
+    re.is_match(comm_trimmed).then_some(MatchRule::RegexComm)
+}
+
+fn find_pids_by_regex(proc_root: &Path, re: &regex::Regex) -> Result<Vec<TargetMatch>> {
+    let result: Vec<TargetMatch> = list_pids(proc_root)?
+        .into_iter()
+        .filter_map(|pid| match_rule(proc_root, pid, re).map(|rule| TargetMatch { pid, rule }))
+        .collect();
+
     // INFO log max. 1× za 5 minut
     if should_log_regex_match() {
         info!(
@@ -330,3 +1245,93 @@ fn find_pids_by_regex(re: &regex::Regex) -> Result<Vec<i32>> {
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::config::{Config, TargetGroup};
+
+    #[test]
+    fn update_reads_fixture_process() {
+        let cfg = Config::for_fixtures();
+        let metrics = ProcessMetrics::new(&Registry::new(), &cfg).unwrap();
+
+        update(&metrics, &cfg.proc_root, 4242).unwrap();
+
+        assert_eq!(metrics.target_matched.get(), 1);
+        assert_eq!(metrics.cpu_user_seconds.get(), 934.12);
+        assert_eq!(metrics.mem_rss_bytes.get(), 536870912.0);
+        assert_eq!(metrics.open_fds.get(), 256.0);
+        assert_eq!(metrics.threads.get(), 42.0);
+    }
+
+    #[test]
+    fn update_for_pids_takes_strictest_fd_limit_of_group() {
+        let mut cfg = Config::for_fixtures();
+        // vlastní scope - offsety se nepotkají s testem jednoho procesu
+        cfg.target_groups = vec![TargetGroup {
+            name: "all".to_string(),
+            regex: regex::Regex::new(".").unwrap(),
+        }];
+        let metrics = ProcessMetrics::new_for_groups(&Registry::new(), &cfg)
+            .unwrap()
+            .remove(0);
+
+        update_for_pids(&metrics, &cfg.proc_root, &[4242, 4343]).unwrap();
+
+        assert_eq!(metrics.target_matched.get(), 2);
+        assert_eq!(metrics.open_fds.get(), 296.0);
+        // 4242 má soft NOFILE 1024, 4343 1048576
+        assert_eq!(metrics.max_fds.get(), 1024.0);
+        assert_eq!(metrics.fds_utilization_ratio.get(), 0.25);
+        assert_eq!(
+            metrics
+                .resource_limits
+                .with_label_values(&["open_files", "soft"])
+                .get(),
+            1024.0
+        );
+    }
+
+    #[test]
+    fn update_smaps_sums_group_and_skips_missing_rollup() {
+        let mut cfg = Config::for_fixtures();
+        cfg.process_smaps = true;
+        let metrics = ProcessMetrics::new(&Registry::new(), &cfg).unwrap();
+        let smaps = metrics.smaps.as_ref().unwrap();
+
+        // 4244 smaps_rollup nemá
+        update_smaps(smaps, &cfg.proc_root, &[4242, 4343, 4244]);
+
+        assert_eq!(smaps.pss_bytes.get(), (498_000.0 + 51_200.0) * 1024.0);
+        assert_eq!(smaps.pss_anon_bytes.get(), (460_000.0 + 40_000.0) * 1024.0);
+        assert_eq!(
+            smaps.uss_bytes.get(),
+            (2_048.0 + 470_000.0 + 1_024.0 + 42_000.0) * 1024.0
+        );
+        assert_eq!(
+            smaps.shared_bytes.get(),
+            (24_000.0 + 128.0 + 22_400.0 + 112.0) * 1024.0
+        );
+    }
+
+    #[test]
+    fn update_for_pids_exports_oom_exposure_of_highest_score() {
+        let mut cfg = Config::for_fixtures();
+        cfg.target_groups = vec![TargetGroup {
+            name: "oom".to_string(),
+            regex: regex::Regex::new(".").unwrap(),
+        }];
+        let metrics = ProcessMetrics::new_for_groups(&Registry::new(), &cfg)
+            .unwrap()
+            .remove(0);
+
+        // oom_score: 4242 = 1342, 4343 = 1004, 4244 = 1102
+        update_for_pids(&metrics, &cfg.proc_root, &[4343, 4242, 4244]).unwrap();
+
+        assert_eq!(metrics.oom_score.get(), 1342.0);
+        assert_eq!(metrics.oom_score_adj.get(), 936.0);
+    }
+}
@@ -0,0 +1,40 @@
+//! Pojmenované skupiny procesů (TARGET_GROUPS): víc tříd procesů v jednom
+//! kontejneru (nginx + celery worker) bez exporteru per třídu.
+//!
+//! Jeden průchod /proc per update; proces se započítá do každé skupiny,
+//! jejíž regex matchne cmdline nebo comm (skupiny se mohou překrývat).
+//! Každá skupina pak jde stejnou agregací jako TARGET_PID* (process_group_*
+//! s labelem group).
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::Result;
+
+use crate::{config::TargetGroup, metrics::ProcessMetrics, procfs};
+
+/// `metrics` odpovídá `groups` podle pořadí (ProcessMetrics::new_for_groups).
+pub fn update(metrics: &[ProcessMetrics], proc_root: &Path, groups: &[TargetGroup]) -> Result<()> {
+    let mut pids: HashMap<&str, Vec<i32>> = HashMap::new();
+
+    for pid in procfs::list_pids(proc_root)? {
+        let pid_dir = proc_root.join(pid.to_string());
+        let cmdline = std::fs::read_to_string(pid_dir.join("cmdline"))
+            .unwrap_or_default()
+            .replace('\0', " ");
+        let comm = std::fs::read_to_string(pid_dir.join("comm")).unwrap_or_default();
+        for group in groups
+            .iter()
+            .filter(|g| g.regex.is_match(&cmdline) || g.regex.is_match(comm.trim()))
+        {
+            pids.entry(group.name.as_str()).or_default().push(pid);
+        }
+    }
+
+    // skupiny jsou pevné z konfigurace - prázdná skupina je nula, ne chybějící série
+    for (group, metrics) in groups.iter().zip(metrics) {
+        let pids = pids.remove(group.name.as_str()).unwrap_or_default();
+        procfs::update_for_pids(metrics, proc_root, &pids)?;
+    }
+
+    Ok(())
+}
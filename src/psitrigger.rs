@@ -0,0 +1,133 @@
+//! PSI triggery (PSI_TRIGGERS): místo vzorkování {cpu,memory,io}.pressure
+//! jednou za interval se do souboru zapíše práh (`some 150000 1000000` =
+//! aspoň 150 ms stall v okně 1 s) a kernel pak při každém překročení
+//! probudí poll() s POLLPRI. Krátký, ale tvrdý stall mezi dvěma updaty
+//! tak nezmizí v průměrech.
+//!
+//! Trigger žije jen dokud je fd otevřený - každý drží vlastní vlákno.
+//! Neprivilegovaný proces smí jen okna v násobcích 2 s.
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    os::{fd::AsRawFd, unix::fs::OpenOptionsExt},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use tracing::{debug, warn};
+
+use crate::{config::PsiTrigger, hierarchy::Layout, metrics::PsiTriggerMetrics};
+
+struct Watch {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+}
+
+/// Běžící triggery per (root, index v PSI_TRIGGERS)
+static WATCHES: Lazy<Mutex<HashMap<(String, usize), Watch>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn watch_loop(
+    counter: prometheus::IntCounter,
+    trigger: &PsiTrigger,
+    path: &Path,
+    stop: &AtomicBool,
+) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+        .with_context(|| format!("open {}", path.display()))?;
+    // kernel chce celý trigger v jednom write včetně NUL
+    file.write_all(format!("{}\0", trigger.spec()).as_bytes())
+        .with_context(|| format!("register trigger '{}'", trigger.spec()))?;
+    debug!(path = %path.display(), trigger = %trigger.spec(), "PSI trigger registered");
+
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let mut pfd = libc::pollfd {
+            fd: file.as_raw_fd(),
+            events: libc::POLLPRI,
+            revents: 0,
+        };
+        // timeout kvůli kontrole stop flagu
+        // SAFETY: pollfd žije na stacku po celou dobu volání
+        let n = unsafe { libc::poll(&mut pfd, 1, 1000) };
+        if n < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err).context("poll pressure file");
+        }
+        // POLLERR = cgroupa zmizela
+        if pfd.revents & libc::POLLERR != 0 {
+            return Ok(());
+        }
+        if pfd.revents & libc::POLLPRI != 0 {
+            counter.inc();
+        }
+    }
+}
+
+/// Zajistí běžící triggery pro `dir`; při změně adresáře staré zastaví.
+pub fn update(metrics: &PsiTriggerMetrics, triggers: &[PsiTrigger], root_name: &str, dir: &Path) {
+    // pressure soubory jsou jen ve v2 (u hybridu v `unified`)
+    let layout = Layout::detect(dir);
+    let mut watches = WATCHES.lock().expect("psitrigger WATCHES mutex poisoned");
+    for (i, trigger) in triggers.iter().enumerate() {
+        let path = layout
+            .v2_dir()
+            .join(format!("{}.pressure", trigger.resource));
+        let key = (root_name.to_string(), i);
+        let running = watches
+            .get(&key)
+            .is_some_and(|w| w.path == path && !w.stop.load(Ordering::Relaxed));
+        if running || !path.exists() {
+            continue;
+        }
+        if let Some(old) = watches.get(&key) {
+            old.stop.store(true, Ordering::Relaxed);
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        watches.insert(
+            key,
+            Watch {
+                path: path.clone(),
+                stop: stop.clone(),
+            },
+        );
+        // série existuje i s nulou
+        let counter = metrics.events_total.with_label_values(&[
+            root_name,
+            &trigger.resource,
+            &trigger.kind,
+            &trigger.threshold_label(),
+        ]);
+        let (trigger, root_name) = (trigger.clone(), root_name.to_string());
+        std::thread::spawn(move || {
+            match watch_loop(counter, &trigger, &path, &stop) {
+                // cgroupa zmizela - další update trigger zaregistruje znovu
+                Ok(()) => stop.store(true, Ordering::Relaxed),
+                // typicky EPERM / EINVAL (okno) - znovu to nezkoušíme
+                Err(e) => warn!(
+                    root = %root_name,
+                    trigger = %trigger.spec(),
+                    error = %e,
+                    root_cause = %e.root_cause(),
+                    "PSI trigger failed"
+                ),
+            }
+        });
+    }
+}
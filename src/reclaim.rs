@@ -0,0 +1,83 @@
+//! Proaktivní reclaim sonda (MEMORY_RECLAIM_PROBE_BYTES): jednou za
+//! interval zapíše malé množství do memory.reclaim a změří, o kolik
+//! memory.current klesl - odhad "studené" paměti, kterou by cgroupa
+//! oželela bez dopadu.
+//!
+//! Zápis skutečně uvolňuje paměť (page cache, swap), proto jen opt-in a
+//! s minimálním intervalem. EAGAIN = kernel nedokázal uvolnit celé
+//! požadované množství, i to je užitečná informace.
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+
+use crate::{config::ReclaimProbe, metrics::ReclaimMetrics};
+
+/// Poslední běh per jméno root
+static LAST_RUN: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn read_current(dir: &Path) -> Result<u64> {
+    std::fs::read_to_string(dir.join("memory.current"))
+        .context("read memory.current")?
+        .trim()
+        .parse()
+        .context("parse memory.current")
+}
+
+pub fn update(
+    metrics: &ReclaimMetrics,
+    probe: &ReclaimProbe,
+    root_name: &str,
+    dir: &Path,
+) -> Result<()> {
+    {
+        let mut last = LAST_RUN.lock().expect("reclaim LAST_RUN mutex poisoned");
+        if last
+            .get(root_name)
+            .is_some_and(|at| at.elapsed() < probe.interval)
+        {
+            return Ok(());
+        }
+        last.insert(root_name.to_string(), Instant::now());
+    }
+
+    metrics.requested_bytes.set(probe.bytes as i64);
+    let before = read_current(dir)?;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(dir.join("memory.reclaim"))
+        .context("open memory.reclaim")?;
+    let complete = match file.write_all(probe.bytes.to_string().as_bytes()) {
+        Ok(()) => true,
+        Err(e) if e.raw_os_error() == Some(libc::EAGAIN) => false,
+        Err(e) => return Err(e).context("write memory.reclaim"),
+    };
+    let after = read_current(dir)?;
+
+    let labels = [root_name];
+    metrics
+        .reclaimed_bytes
+        .with_label_values(&labels)
+        .set(before.saturating_sub(after) as f64);
+    metrics
+        .complete
+        .with_label_values(&labels)
+        .set(i64::from(complete));
+    metrics
+        .last_run_timestamp_seconds
+        .with_label_values(&labels)
+        .set(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs_f64())
+                .unwrap_or(0.0),
+        );
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Potomci cgroupy (CGROUP_RECURSIVE): systemd uvnitř kontejneru si pod
+//! CGROUP_ROOT zakládá vlastní podstromy, které jinak nejsou vidět.
+//!
+//! - per-child: každý potomek do hloubky CGROUP_RECURSIVE_MAX_DEPTH jako
+//!   série s labelem `cgroup` (cesta relativně k root)
+//! - aggregate: součet přes listy prohledaného stromu per root
+//!
+//! v2 cpu.stat i memory.current už jsou hierarchické (rodič obsahuje
+//! potomky), proto agregace sčítá jen listy - uzel v maximální hloubce se
+//! bere jako list, jeho hodnota zahrnuje celý zbytek podstromu.
+//! Čte se jen v2 formát (jako v POD_CGROUP_MODE).
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+
+use crate::{
+    config::RecursiveMode,
+    metrics::{LiveSeries, RecursiveMetrics, RootVec},
+    monotonic::CounterOffsets,
+};
+
+/// Offsety součtů v aggregate režimu - zmizelý potomek sumu sníží.
+pub static OFFSETS: Lazy<Mutex<CounterOffsets>> = Lazy::new(|| Mutex::new(CounterOffsets::new()));
+
+/// CPU a paměť jedné v2 cgroupy (sdílí i CGROUP_GLOB).
+#[derive(Default)]
+pub struct Sample {
+    pub cpu_usage_seconds: f64,
+    pub cpu_throttled_seconds: f64,
+    pub mem_current_bytes: f64,
+}
+
+pub fn read_sample(dir: &Path) -> Sample {
+    let mut sample = Sample::default();
+    if let Ok(cpu_stat) = std::fs::read_to_string(dir.join("cpu.stat")) {
+        for line in cpu_stat.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(Ok(val))) = (parts.next(), parts.next().map(str::parse::<u64>))
+            else {
+                continue;
+            };
+            match key {
+                "usage_usec" => sample.cpu_usage_seconds = val as f64 / 1_000_000.0,
+                "throttled_usec" => sample.cpu_throttled_seconds = val as f64 / 1_000_000.0,
+                _ => {}
+            }
+        }
+    }
+    if let Ok(s) = std::fs::read_to_string(dir.join("memory.current"))
+        && let Ok(v) = s.trim().parse::<u64>()
+    {
+        sample.mem_current_bytes = v as f64;
+    }
+    sample
+}
+
+/// `frozen 1` v cgroup.events (v2 freezer).
+fn is_frozen(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join("cgroup.events"))
+        .is_ok_and(|s| s.lines().any(|l| l.trim() == "frozen 1"))
+}
+
+fn child_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Potomci root do hloubky max_depth: (cesta, je list).
+fn walk(root: &Path, max_depth: usize) -> Vec<(PathBuf, bool)> {
+    let mut out = Vec::new();
+    let mut stack: Vec<(PathBuf, usize)> = child_dirs(root).into_iter().map(|d| (d, 1)).collect();
+    while let Some((dir, depth)) = stack.pop() {
+        let children = if depth < max_depth {
+            child_dirs(&dir)
+        } else {
+            Vec::new()
+        };
+        out.push((dir, children.is_empty()));
+        stack.extend(children.into_iter().map(|d| (d, depth + 1)));
+    }
+    out
+}
+
+pub fn update(
+    metrics: &RecursiveMetrics,
+    root_name: &str,
+    root: &Path,
+    mode: RecursiveMode,
+    max_depth: usize,
+) -> Result<()> {
+    std::fs::read_dir(root).context("read cgroup directory")?;
+    let nodes = walk(root, max_depth);
+
+    match mode {
+        RecursiveMode::PerChild => {
+            let vecs = [
+                &metrics.cpu_usage_seconds,
+                &metrics.cpu_throttled_seconds,
+                &metrics.mem_current_bytes,
+            ]
+            .map(|v| RootVec::new(v, root_name));
+            let mut live = LiveSeries::default();
+            for (dir, _) in &nodes {
+                let rel = dir.strip_prefix(root).unwrap_or(dir).to_string_lossy();
+                live.insert(&[&rel]);
+                let sample = read_sample(dir);
+                vecs[0]
+                    .with_label_values(&[&rel])
+                    .set(sample.cpu_usage_seconds);
+                vecs[1]
+                    .with_label_values(&[&rel])
+                    .set(sample.cpu_throttled_seconds);
+                vecs[2]
+                    .with_label_values(&[&rel])
+                    .set(sample.mem_current_bytes);
+            }
+            for v in &vecs {
+                v.prune(&live);
+            }
+        }
+        RecursiveMode::Aggregate => {
+            let mut total = Sample::default();
+            for (dir, _) in nodes.iter().filter(|(_, leaf)| *leaf) {
+                let sample = read_sample(dir);
+                total.cpu_usage_seconds += sample.cpu_usage_seconds;
+                total.cpu_throttled_seconds += sample.cpu_throttled_seconds;
+                total.mem_current_bytes += sample.mem_current_bytes;
+            }
+            let mut offsets = OFFSETS.lock().expect("recursive OFFSETS mutex poisoned");
+            let labels = [root_name];
+            metrics
+                .cpu_usage_seconds
+                .with_label_values(&labels)
+                .set(offsets.adjust(&format!("{root_name}/cpu_usage"), total.cpu_usage_seconds));
+            metrics
+                .cpu_throttled_seconds
+                .with_label_values(&labels)
+                .set(offsets.adjust(
+                    &format!("{root_name}/cpu_throttled"),
+                    total.cpu_throttled_seconds,
+                ));
+            metrics
+                .mem_current_bytes
+                .with_label_values(&labels)
+                .set(total.mem_current_bytes);
+        }
+    }
+
+    metrics
+        .cgroups
+        .with_label_values(&[root_name])
+        .set(nodes.len() as i64);
+    metrics
+        .frozen_children
+        .with_label_values(&[root_name])
+        .set(nodes.iter().filter(|(dir, _)| is_frozen(dir)).count() as i64);
+    Ok(())
+}
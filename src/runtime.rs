@@ -0,0 +1,48 @@
+//! Metriky tokio runtime exporteru (feature `runtime-metrics`).
+
+use tokio::runtime::Handle;
+
+use crate::metrics::RuntimeMetrics;
+
+/// Přečte aktuální stav runtime, ve kterém běží volající.
+pub fn update(metrics: &RuntimeMetrics) {
+    let Ok(handle) = Handle::try_current() else {
+        return;
+    };
+    let rt = handle.metrics();
+
+    let workers = rt.num_workers();
+    metrics.workers.set(workers as i64);
+    metrics.alive_tasks.set(rt.num_alive_tasks() as i64);
+    metrics
+        .global_queue_depth
+        .set(rt.global_queue_depth() as i64);
+
+    for worker in 0..workers {
+        let label = worker.to_string();
+        metrics
+            .worker_busy_seconds_total
+            .with_label_values(&[&label])
+            .set(rt.worker_total_busy_duration(worker).as_secs_f64());
+        metrics
+            .worker_park_total
+            .with_label_values(&[&label])
+            .set(rt.worker_park_count(worker) as i64);
+
+        #[cfg(tokio_unstable)]
+        metrics
+            .worker_mean_poll_seconds
+            .with_label_values(&[&label])
+            .set(rt.worker_mean_poll_time(worker).as_secs_f64());
+    }
+
+    #[cfg(tokio_unstable)]
+    {
+        metrics
+            .blocking_threads
+            .set(rt.num_blocking_threads() as i64);
+        metrics
+            .blocking_queue_depth
+            .set(rt.blocking_queue_depth() as i64);
+    }
+}
@@ -0,0 +1,151 @@
+//! Self-check při startu: běží exporter jako root, jaké má capabilities
+//! a jsou /proc a /sys připojené read-only? Výsledek jde do
+//! exporter_security_info a odchylky od "non-root, minimal caps" do logu.
+
+use std::{ffi::CString, os::unix::ffi::OsStrExt, path::Path};
+
+use tracing::{info, warn};
+
+use crate::{config::Config, metrics::Metrics};
+
+/// Jména capabilities podle čísla bitu (linux/capability.h).
+const CAP_NAMES: &[&str] = &[
+    "chown",
+    "dac_override",
+    "dac_read_search",
+    "fowner",
+    "fsetid",
+    "kill",
+    "setgid",
+    "setuid",
+    "setpcap",
+    "linux_immutable",
+    "net_bind_service",
+    "net_broadcast",
+    "net_admin",
+    "net_raw",
+    "ipc_lock",
+    "ipc_owner",
+    "sys_module",
+    "sys_rawio",
+    "sys_chroot",
+    "sys_ptrace",
+    "sys_pacct",
+    "sys_admin",
+    "sys_boot",
+    "sys_nice",
+    "sys_resource",
+    "sys_time",
+    "sys_tty_config",
+    "mknod",
+    "lease",
+    "audit_write",
+    "audit_control",
+    "setfcap",
+    "mac_override",
+    "mac_admin",
+    "syslog",
+    "wake_alarm",
+    "block_suspend",
+    "audit_read",
+    "perfmon",
+    "bpf",
+    "checkpoint_restore",
+];
+
+/// Capabilities, které exporter pro čtení /proc a /sys nikdy nepotřebuje.
+const DANGEROUS_CAPS: &[&str] = &["sys_admin", "sys_module", "sys_rawio", "net_admin", "bpf"];
+
+/// Efektivní capabilities vlastního procesu (CapEff z /proc/self/status).
+fn effective_caps() -> Option<Vec<&'static str>> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let hex = status
+        .lines()
+        .find_map(|l| l.strip_prefix("CapEff:"))?
+        .trim();
+    let mask = u64::from_str_radix(hex, 16).ok()?;
+
+    Some(
+        CAP_NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| mask & (1u64 << bit) != 0)
+            .map(|(_, &name)| name)
+            .collect(),
+    )
+}
+
+/// Je cesta na read-only mountu? None = nejde zjistit.
+fn is_read_only(path: &Path) -> Option<bool> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs je plain-old-data struktura, nulová hodnota je platná
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path žije do konce funkce, takže ukazatel je platný
+    // NUL-terminated řetězec po celou dobu volání; st je platný &mut
+    let rc = unsafe { libc::statvfs(c_path.as_ptr(), &mut st) };
+    (rc == 0).then_some(st.f_flag & libc::ST_RDONLY != 0)
+}
+
+fn flag(v: Option<bool>) -> &'static str {
+    match v {
+        Some(true) => "true",
+        Some(false) => "false",
+        None => "unknown",
+    }
+}
+
+/// Provede self-check a nastaví exporter_security_info{...} 1.
+pub fn init_security_info(metrics: &Metrics, cfg: &Config) {
+    // SAFETY: geteuid() nemá argumenty a vždy uspěje
+    let run_as_root = unsafe { libc::geteuid() } == 0;
+    let caps = effective_caps();
+    let proc_ro = is_read_only(&cfg.proc_root);
+    let sys_ro = is_read_only(&cfg.sys_root);
+
+    let caps_label = match caps {
+        Some(ref c) if c.is_empty() => "none".to_string(),
+        Some(ref c) => c.join(","),
+        None => "unknown".to_string(),
+    };
+
+    if run_as_root {
+        warn!("exporter runs as root (uid 0), a non-root user is sufficient");
+    }
+    if let Some(ref c) = caps {
+        let dangerous: Vec<&str> = c
+            .iter()
+            .copied()
+            .filter(|cap| DANGEROUS_CAPS.contains(cap))
+            .collect();
+        if !dangerous.is_empty() {
+            warn!(
+                capabilities = %dangerous.join(","),
+                "exporter holds capabilities it does not need, consider dropping them"
+            );
+        }
+    }
+    if proc_ro == Some(false) {
+        warn!(path = %cfg.proc_root.display(), "proc is mounted read-write");
+    }
+    if sys_ro == Some(false) {
+        warn!(path = %cfg.sys_root.display(), "sys is mounted read-write");
+    }
+
+    info!(
+        run_as_root,
+        capabilities = %caps_label,
+        proc_read_only = flag(proc_ro),
+        sys_read_only = flag(sys_ro),
+        "security self-check"
+    );
+
+    metrics
+        .security_info
+        .with_label_values(&[
+            if run_as_root { "true" } else { "false" },
+            &caps_label,
+            flag(proc_ro),
+            flag(sys_ro),
+        ])
+        .set(1);
+}
@@ -0,0 +1,127 @@
+//! Sidecar ve sdíleném PID namespace (shareProcessNamespace, SIBLING_CONTAINERS):
+//! viditelné procesy se seskupí podle kontejneru, ze kterého pochází,
+//! a exportují se agregace per kontejner.
+//!
+//! Kontejner procesu se pozná z ID v `/proc/<pid>/cgroup` (i v privátním
+//! cgroup namespace tam zůstane poslední segment `cri-containerd-<id>.scope`).
+//! Když ID chybí, rozhodne zařízení kořene `/proc/<pid>/root` - každý
+//! kontejner má vlastní overlay mount.
+//!
+//! Jméno kontejneru se bere z CONTAINERS_LOG_DIR (`/var/log/containers`,
+//! symlinky `<pod>_<namespace>_<container>-<id>.log`); jinak zůstane ID.
+
+use std::{collections::HashMap, os::unix::fs::MetadataExt, path::Path, sync::Mutex};
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+
+use crate::{
+    kubepods,
+    metrics::{LiveSeries, SiblingMetrics},
+    monotonic::CounterOffsets,
+    procfs::{self, GroupAggregate},
+};
+
+/// Offsety CPU counteru per kontejner - ukončený proces nesmí counter snížit.
+pub static OFFSETS: Lazy<Mutex<CounterOffsets>> = Lazy::new(|| Mutex::new(CounterOffsets::new()));
+
+/// ID kontejneru → jméno z `<pod>_<namespace>_<container>-<id>.log`.
+pub fn container_names(containers_log_dir: &Path) -> HashMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(containers_log_dir) else {
+        return HashMap::new();
+    };
+
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let stem = name.strip_suffix(".log")?;
+            let container_and_id = stem.rsplit('_').next()?;
+            let (container, id) = container_and_id.rsplit_once('-')?;
+            Some((id.to_string(), container.to_string()))
+        })
+        .collect()
+}
+
+/// Klíč skupiny pro PID: ID kontejneru, nebo `dev-<major>:<minor>` kořene.
+fn container_key(proc_root: &Path, pid: i32) -> Option<String> {
+    let raw = std::fs::read_to_string(proc_root.join(pid.to_string()).join("cgroup")).ok()?;
+    let from_cgroup = raw.lines().find_map(|l| {
+        let path = l.splitn(3, ':').nth(2)?;
+        kubepods::container_id_from_segment(path.rsplit('/').next()?)
+    });
+    if from_cgroup.is_some() {
+        return from_cgroup;
+    }
+
+    let dev = std::fs::metadata(proc_root.join(pid.to_string()).join("root"))
+        .ok()?
+        .dev();
+    Some(format!(
+        "dev-{}:{}",
+        libc::major(dev as libc::dev_t),
+        libc::minor(dev as libc::dev_t)
+    ))
+}
+
+pub fn update(metrics: &SiblingMetrics, proc_root: &Path, containers_log_dir: &Path) -> Result<()> {
+    let mut groups: HashMap<String, GroupAggregate> = HashMap::new();
+
+    for pid in procfs::list_pids(proc_root)? {
+        let Some(key) = container_key(proc_root, pid) else {
+            continue;
+        };
+        let Ok(sample) = procfs::read_proc_sample(proc_root, pid) else {
+            continue;
+        };
+        groups.entry(key).or_default().add(&sample);
+    }
+
+    let names = container_names(containers_log_dir);
+
+    let mut live = LiveSeries::default();
+    let mut offsets = OFFSETS.lock().expect("siblings OFFSETS mutex poisoned");
+    for (key, agg) in &groups {
+        let short: String = key.chars().take(12).collect();
+        let container = names.get(key).map(String::as_str).unwrap_or(&short);
+        let labels = [container];
+
+        metrics
+            .processes
+            .with_label_values(&labels)
+            .set(agg.processes);
+        metrics
+            .cpu_seconds
+            .with_label_values(&labels)
+            .set(offsets.adjust(container, agg.cpu_seconds));
+        metrics
+            .rss_bytes
+            .with_label_values(&labels)
+            .set(agg.rss_bytes);
+        live.insert(&labels);
+    }
+
+    live.prune(&metrics.processes);
+    for vec in [&metrics.cpu_seconds, &metrics.rss_bytes] {
+        live.prune(vec);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn container_names_from_containers_log_dir() {
+        let names = container_names(&Config::for_fixtures().containers_log_dir);
+        assert_eq!(
+            names.get("9b8a7f6e5d4c3b2a19f8e7d6c5b4a39281706f5e4d3c2b1a0f9e8d7c6b5a4f31"),
+            Some(&"envoy".to_string())
+        );
+        assert_eq!(names.len(), 1);
+        assert!(container_names(Path::new("/nonexistent")).is_empty());
+    }
+}
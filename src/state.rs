@@ -0,0 +1,110 @@
+//! Volitelný stavový soubor (STATE_FILE, typicky na emptyDir), do kterého se
+//! průběžně ukládají klouzavá maxima, aby restart sidecaru nesmazal právě ta
+//! data, která jsou potřeba pro capacity planning. Spolu s nimi i offsety
+//! monotónních counterů, jinak by restart exportované countery vrátil na
+//! syrovou hodnotu zdroje.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::{cgroup, monotonic::CounterOffsets, net, nodepods, peaks, procfs, recursive, siblings};
+
+const STATE_VERSION: u32 = 2;
+
+/// Offsety counterů podle modulu → (poslední syrová hodnota, offset) per klíč
+type OffsetsSnapshot = HashMap<String, HashMap<String, (f64, f64)>>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedState {
+    version: u32,
+    /// Klíč okna → vzorky (unix timestamp, hodnota)
+    #[serde(default)]
+    peaks: HashMap<String, Vec<(f64, f64)>>,
+    #[serde(default)]
+    offsets: OffsetsSnapshot,
+}
+
+/// CounterOffsets jednotlivých collectorů pod jménem v souboru.
+fn offset_sets() -> [(&'static str, &'static Mutex<CounterOffsets>); 6] {
+    [
+        ("cgroup", &cgroup::OFFSETS),
+        ("procfs", &procfs::OFFSETS),
+        ("net", &net::OFFSETS),
+        ("recursive", &recursive::OFFSETS),
+        ("nodepods", &nodepods::OFFSETS),
+        ("siblings", &siblings::OFFSETS),
+    ]
+}
+
+/// Načte stav ze souboru; chybějící soubor není chyba (první start).
+pub fn load(path: &Path) -> Result<()> {
+    let raw = match fs::read(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!(path = %path.display(), "state file does not exist yet");
+            return Ok(());
+        }
+        Err(e) => return Err(e).context("read state file"),
+    };
+
+    let state: PersistedState = serde_json::from_slice(&raw).context("parse state file")?;
+    // verze 1 neměla offsety - peaky z ní jdou načíst dál
+    if !(1..=STATE_VERSION).contains(&state.version) {
+        anyhow::bail!(
+            "unsupported state file version {} (expected {})",
+            state.version,
+            STATE_VERSION
+        );
+    }
+
+    info!(
+        path = %path.display(),
+        peak_windows = state.peaks.len(),
+        offset_sets = state.offsets.len(),
+        "restored exporter state"
+    );
+    peaks::restore(state.peaks);
+    let mut saved = state.offsets;
+    for (name, offsets) in offset_sets() {
+        if let Some(values) = saved.remove(name) {
+            offsets
+                .lock()
+                .expect("OFFSETS mutex poisoned")
+                .restore(values);
+        }
+    }
+    Ok(())
+}
+
+/// Atomicky (tmp + rename) zapíše aktuální stav.
+pub fn save(path: &Path) -> Result<()> {
+    let state = PersistedState {
+        version: STATE_VERSION,
+        peaks: peaks::snapshot(),
+        offsets: offset_sets()
+            .into_iter()
+            .map(|(name, offsets)| {
+                let values = offsets.lock().expect("OFFSETS mutex poisoned").snapshot();
+                (name.to_string(), values)
+            })
+            .collect(),
+    };
+
+    // přípona se přidává, ne nahrazuje - STATE_FILE=x.tmp by jinak zapisoval sám do sebe
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, serde_json::to_vec(&state)?).context("write state file")?;
+    fs::rename(&tmp, path).context("rename state file")?;
+
+    debug!(path = %path.display(), "exporter state saved");
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Odhad, kolik steal time hypervisoru dopadlo na naši cgroupu.
+//!
+//! /proc/stat ukazuje steal jen za celý host; cgroup o něm nic neví. Steal
+//! za interval rozpočítáme podle podílu cgroupy na čase, kdy host CPU
+//! skutečně běžela (user+nice+system+irq+softirq): kdo víc běžel, tomu víc
+//! ukradli. Výsledek se akumuluje do cgroup_cpu_steal_impact_seconds,
+//! zvlášť pro každou root z CGROUP_ROOTS.
+//! Počítá se nad hodnotami, které collectory právě nastavily.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::metrics::Metrics;
+
+/// Host módy, které počítáme jako "CPU běžela" (guest je už v user).
+const BUSY_MODES: [&str; 5] = ["user", "nice", "system", "irq", "softirq"];
+
+#[derive(Default)]
+struct State {
+    /// (steal, busy, cgroup usage) z minulého updatu
+    prev: Option<(f64, f64, f64)>,
+    impact_seconds: f64,
+}
+
+/// Stav per jméno root
+static STATE: Lazy<Mutex<HashMap<String, State>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn host_cpu(metrics: &Metrics, mode: &str) -> f64 {
+    metrics
+        .host
+        .cpu_seconds_total
+        .with_label_values(&["all", mode])
+        .get()
+}
+
+pub fn update(metrics: &Metrics) {
+    let steal = host_cpu(metrics, "steal");
+    let busy: f64 = BUSY_MODES.iter().map(|m| host_cpu(metrics, m)).sum();
+
+    let mut states = STATE.lock().expect("steal STATE mutex poisoned");
+    for cg in &metrics.cgroups {
+        let usage = cg.cpu_usage_seconds.get();
+        let state = states.entry(cg.root.clone()).or_default();
+        if let Some((prev_steal, prev_busy, prev_usage)) = state.prev {
+            let d_steal = steal - prev_steal;
+            let d_busy = busy - prev_busy;
+            let d_usage = usage - prev_usage;
+            // záporné delty = reset/přepnutí cgroupy, interval přeskočíme
+            if d_steal > 0.0 && d_busy > 0.0 && d_usage >= 0.0 {
+                let share = (d_usage / d_busy).clamp(0.0, 1.0);
+                state.impact_seconds += d_steal * share;
+            }
+        }
+        state.prev = Some((steal, busy, usage));
+        cg.cpu_steal_impact_seconds.set(state.impact_seconds);
+    }
+}
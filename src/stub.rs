@@ -0,0 +1,149 @@
+//! Syntetické hodnoty metrik pro vývoj mimo Linux (`--dev-stub`).
+//!
+//! Nic se nečte z /proc ani /sys - countery rostou s časem od startu,
+//! gauge lehce kolísají, aby šlo ladit HTTP vrstvu, config a dashboardy.
+
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+
+use crate::metrics::{CgroupMetrics, Metrics};
+
+static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+
+pub fn update(metrics: &Metrics) {
+    let t = STARTED.elapsed().as_secs_f64();
+    // pomalá "vlna" 0..1 s periodou ~5 minut
+    let wave = ((t / 300.0) * std::f64::consts::TAU).sin() * 0.5 + 0.5;
+
+    for cg in &metrics.cgroups {
+        cgroup(cg, t, wave);
+    }
+
+    let p = &metrics.process;
+    p.cpu_user_seconds.set(t * 0.28);
+    p.cpu_system_seconds.set(t * 0.04);
+    p.start_time_seconds.set(1_700_000_000.0);
+    p.uptime_seconds.set(t);
+    p.availability.gauge.set(1.0);
+    p.mem_rss_bytes.set(200.0 * 1024.0 * 1024.0 * (1.0 + wave));
+    p.mem_vms_bytes.set(2.0 * 1024.0 * 1024.0 * 1024.0);
+    p.mem_swap_bytes.set(0.0);
+    p.mem_rss_hwm_bytes.set(400.0 * 1024.0 * 1024.0);
+    p.mem_vms_peak_bytes.set(2.5 * 1024.0 * 1024.0 * 1024.0);
+    p.io_rchar_bytes_total.set(t * 4096.0);
+    p.io_wchar_bytes_total.set(t * 1024.0);
+    p.io_syscr_total.set(t * 10.0);
+    p.io_syscw_total.set(t * 3.0);
+    p.io_read_bytes_total.set(t * 2048.0);
+    p.io_write_bytes_total.set(t * 512.0);
+    p.io_cancelled_write_bytes_total.set(0.0);
+
+    let h = &metrics.host;
+    for (mode, share) in [
+        ("user", 0.25),
+        ("nice", 0.0),
+        ("system", 0.08),
+        ("idle", 0.65),
+        ("iowait", 0.01),
+        ("irq", 0.0),
+        ("softirq", 0.01),
+        ("steal", 0.0),
+        ("guest", 0.0),
+        ("guest_nice", 0.0),
+    ] {
+        h.cpu_seconds_total
+            .with_label_values(&["all", mode])
+            .set(t * 4.0 * share);
+    }
+    let gib = 1024.0 * 1024.0 * 1024.0;
+    h.memory_total_bytes.set(16.0 * gib);
+    h.memory_free_bytes.set(4.0 * gib);
+    h.memory_available_bytes.set(9.0 * gib);
+    h.memory_cached_bytes.set(5.0 * gib);
+    h.memory_buffers_bytes.set(0.5 * gib);
+    h.swap_total_bytes.set(0.0);
+    h.swap_free_bytes.set(0.0);
+
+    for (state, v4, v6) in [
+        ("ESTABLISHED", 12 + (wave * 20.0) as i64, 1),
+        ("LISTEN", 2, 1),
+        ("TIME_WAIT", (wave * 40.0) as i64, 0),
+        ("CLOSE_WAIT", 0, 0),
+    ] {
+        metrics
+            .tcp
+            .connections
+            .with_label_values(&[state, "4"])
+            .set(v4);
+        metrics
+            .tcp
+            .connections
+            .with_label_values(&[state, "6"])
+            .set(v6);
+    }
+
+    let n = &metrics.net;
+    n.rx_bytes_total.set(t * 50_000.0);
+    n.tx_bytes_total.set(t * 20_000.0);
+    n.rx_packets_total.set(t * 80.0);
+    n.tx_packets_total.set(t * 60.0);
+    n.rx_errors_total.set(0.0);
+    n.tx_errors_total.set(0.0);
+    n.rx_dropped_total.set(0.0);
+    n.tx_dropped_total.set(0.0);
+}
+
+fn cgroup(cg: &CgroupMetrics, t: f64, wave: f64) {
+    cg.cpu_usage_seconds.set(t * 0.35);
+    cg.cpu_user_seconds.set(t * 0.30);
+    cg.cpu_system_seconds.set(t * 0.05);
+    cg.cpu_nr_periods.set((t * 10.0) as i64);
+    cg.cpu_nr_throttled.set((t * 10.0 * 0.1 * wave) as i64);
+    cg.cpu_throttled_seconds.set(t * 0.01 * wave);
+    cg.cpu_throttled_ratio.set(0.1 * wave);
+    cg.cpu_saturation_ratio.set(0.15 * wave);
+    cg.cpu_limit_cores.set(0.5);
+    cg.cpu_utilization_ratio.set(0.7 * wave);
+    cg.cpu_idle.set(0);
+    cg.cpu_uclamp_percent.with_label_values(&["min"]).set(0.0);
+    cg.cpu_uclamp_percent.with_label_values(&["max"]).set(100.0);
+    cg.mem_current_bytes
+        .set(256.0 * 1024.0 * 1024.0 * (1.0 + wave));
+    cg.mem_peak_bytes.set(512.0 * 1024.0 * 1024.0);
+    cg.mem_max_bytes.set(1024.0 * 1024.0 * 1024.0);
+    cg.mem_high_bytes.set(f64::INFINITY);
+    cg.mem_low_bytes.set(0.0);
+    cg.mem_min_bytes.set(0.0);
+    cg.mem_swap_current_bytes.set(16.0 * 1024.0 * 1024.0 * wave);
+    cg.mem_swap_max_bytes.set(f64::INFINITY);
+    cg.mem_swap_high_bytes.set(f64::INFINITY);
+    let working_set = cg.mem_current_bytes.get() * 0.8;
+    cg.mem_working_set_bytes.set(working_set);
+    cg.mem_headroom_bytes
+        .set(cg.mem_max_bytes.get() - working_set);
+    for (ev, v) in [
+        ("low", 0),
+        ("high", 0),
+        ("max", 3),
+        ("oom", 0),
+        ("oom_kill", 0),
+    ] {
+        cg.mem_events_total.with_label_values(&[ev]).set(v);
+    }
+    let current = cg.mem_current_bytes.get();
+    for (stat, v) in [
+        ("anon", current * 0.6),
+        ("file", current * 0.35),
+        ("kernel", current * 0.05),
+        ("inactive_file", current * 0.2),
+    ] {
+        cg.mem_stat.with_label_values(&[stat]).set(v);
+    }
+    cg.io_read_bytes.with_label_values(&["8:0"]).set(t * 8192.0);
+    cg.io_write_bytes
+        .with_label_values(&["8:0"])
+        .set(t * 32768.0);
+    cg.io_reads.with_label_values(&["8:0"]).set(t * 2.0);
+    cg.io_writes.with_label_values(&["8:0"]).set(t * 8.0);
+}
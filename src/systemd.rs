@@ -0,0 +1,55 @@
+//! Cgroupa systemd unity nebo slice (CGROUP_SYSTEMD_UNIT), ať bare-metal
+//! uživatelé nemusí cestu skládat ručně.
+//!
+//! Slice má cestu danou jménem - pomlčky jsou úrovně zanoření
+//! (`a-b.slice` je `a.slice/a-b.slice`). Ostatní unity (`.service`,
+//! `.scope`, ...) leží v libovolném slice, takže se hledají ve stromu.
+//! Čte se jen v2 hierarchie (u hybridu `unified`); D-Bus nepoužíváme.
+
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+/// Jak hluboko pod mountem unitu hledat (slice v slice v slice ...).
+const MAX_DEPTH: usize = 8;
+
+/// Relativní cesta slice podle jména (`-.slice` = kořen).
+fn slice_path(slice: &str) -> Option<PathBuf> {
+    let name = slice.strip_suffix(".slice")?;
+    if name == "-" {
+        return Some(PathBuf::new());
+    }
+    let mut path = PathBuf::new();
+    let mut prefix = String::new();
+    for part in name.split('-') {
+        if !prefix.is_empty() {
+            prefix.push('-');
+        }
+        prefix.push_str(part);
+        path.push(format!("{prefix}.slice"));
+    }
+    Some(path)
+}
+
+/// Adresář cgroupy unity pod `cgroup_mount`; None, když (zatím) neexistuje.
+pub fn resolve_unit(cgroup_mount: &Path, unit: &str) -> Option<PathBuf> {
+    let unified = cgroup_mount.join("unified");
+    let mount = if unified.join("cgroup.controllers").exists() {
+        unified
+    } else {
+        cgroup_mount.to_path_buf()
+    };
+
+    if let Some(rel) = slice_path(unit) {
+        let dir = mount.join(rel);
+        return dir.is_dir().then_some(dir);
+    }
+
+    WalkDir::new(&mount)
+        .min_depth(1)
+        .max_depth(MAX_DEPTH)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_type().is_dir() && e.file_name() == unit)
+        .map(|e| e.into_path())
+}
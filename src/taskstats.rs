@@ -0,0 +1,266 @@
+//! Delay accounting přes taskstats netlink (TASKSTATS): kolik času čekaly
+//! úlohy cílového procesu na CPU (runqueue), na blokové IO a na swap-in.
+//! Z /proc se to přesně zjistit nedá - schedstat má jen CPU a jen per task.
+//!
+//! Dotaz je per TGID, kernel sečte živá i ukončená vlákna procesu.
+//! Vyžaduje CAP_NET_ADMIN a initial net namespace (taskstats rodina
+//! v jiném netns neexistuje), delay accounting musí být zapnutý
+//! (`delayacct` na kernel cmdline nebo sysctl kernel.task_delayacct=1),
+//! jinak kernel vrací nuly.
+//!
+//! Socket i id rodiny se drží mezi cykly; po chybě netlinku (timeout,
+//! zbytek odpovědi v bufferu) se zahodí a další cyklus otevře nový.
+
+use std::{
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    path::Path,
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+
+use crate::metrics::{LiveSeries, TaskstatsMetrics};
+
+const CTRL_ATTR_FAMILY_ID: u16 = 1;
+const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+const TASKSTATS_CMD_GET: u8 = 1;
+const TASKSTATS_CMD_ATTR_TGID: u16 = 2;
+const TASKSTATS_TYPE_STATS: u16 = 3;
+const TASKSTATS_TYPE_AGGR_TGID: u16 = 5;
+/// Bez NLA_F_NESTED / NLA_F_NET_BYTEORDER
+const NLA_TYPE_MASK: u16 = 0x3fff;
+
+/// Offsety v `struct taskstats` (stabilní od verze 1): (count, delay_total v ns)
+const DELAYS: [(&str, usize, usize); 3] = [("cpu", 16, 24), ("blkio", 32, 40), ("swapin", 48, 56)];
+
+const NLMSG_HDRLEN: usize = 16;
+const GENL_HDRLEN: usize = 4;
+
+/// Otevřený socket s vyřešeným id rodiny TASKSTATS.
+struct Conn {
+    sock: Socket,
+    family: u16,
+}
+
+static CONN: Lazy<Mutex<Option<Conn>>> = Lazy::new(|| Mutex::new(None));
+
+fn align4(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+struct Socket {
+    fd: OwnedFd,
+    seq: u32,
+}
+
+impl Socket {
+    fn open() -> io::Result<Self> {
+        // SAFETY: socket() nepracuje s žádnou pamětí volajícího
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_GENERIC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: fd je právě otevřený a nikdo jiný ho nevlastní
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // SAFETY: sockaddr_nl je plain-old-data, nulová hodnota je platná
+        let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        // SAFETY: addr žije po celé volání a délka odpovídá jeho typu
+        let rc = unsafe {
+            libc::bind(
+                fd.as_raw_fd(),
+                (&addr as *const libc::sockaddr_nl).cast(),
+                std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            )
+        };
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // kernel odpovídá synchronně, timeout je jen pojistka - bez něj by
+        // ztracená odpověď zablokovala celý update
+        let timeout = libc::timeval {
+            tv_sec: 1,
+            tv_usec: 0,
+        };
+        // SAFETY: timeout žije po celé volání a délka odpovídá timeval
+        let rc = unsafe {
+            libc::setsockopt(
+                fd.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                (&timeout as *const libc::timeval).cast(),
+                std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+            )
+        };
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { fd, seq: 0 })
+    }
+
+    /// Pošle generic netlink request a vrátí atributy odpovědi (za genl hlavičkou).
+    fn request(&mut self, family: u16, cmd: u8, attrs: &[(u16, &[u8])]) -> io::Result<Vec<u8>> {
+        self.seq += 1;
+        let attrs_len: usize = attrs.iter().map(|(_, v)| align4(4 + v.len())).sum();
+        let len = NLMSG_HDRLEN + GENL_HDRLEN + attrs_len;
+
+        let mut msg = Vec::with_capacity(len);
+        msg.extend_from_slice(&(len as u32).to_ne_bytes());
+        msg.extend_from_slice(&family.to_ne_bytes());
+        msg.extend_from_slice(&(libc::NLM_F_REQUEST as u16).to_ne_bytes());
+        msg.extend_from_slice(&self.seq.to_ne_bytes());
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        // genlmsghdr: cmd, version, reserved
+        msg.extend_from_slice(&[cmd, 1, 0, 0]);
+        for (kind, value) in attrs {
+            msg.extend_from_slice(&((4 + value.len()) as u16).to_ne_bytes());
+            msg.extend_from_slice(&kind.to_ne_bytes());
+            msg.extend_from_slice(value);
+            msg.resize(align4(msg.len()), 0);
+        }
+
+        // SAFETY: msg je platný buffer délky msg.len() po celé volání
+        let sent = unsafe { libc::send(self.fd.as_raw_fd(), msg.as_ptr().cast(), msg.len(), 0) };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut buf = vec![0u8; 16 * 1024];
+        // SAFETY: kernel zapíše nejvýš buf.len() bajtů do vlastněného bufferu
+        let n = unsafe { libc::recv(self.fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len(), 0) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let buf = &buf[..n as usize];
+        if buf.len() < NLMSG_HDRLEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "short netlink reply",
+            ));
+        }
+        let msg_len = (u32::from_ne_bytes(buf[0..4].try_into().unwrap()) as usize).min(buf.len());
+        let msg_type = u16::from_ne_bytes(buf[4..6].try_into().unwrap());
+        if msg_type == libc::NLMSG_ERROR as u16 {
+            let code = buf
+                .get(NLMSG_HDRLEN..NLMSG_HDRLEN + 4)
+                .map(|b| i32::from_ne_bytes(b.try_into().unwrap()))
+                .unwrap_or(-libc::EIO);
+            return Err(io::Error::from_raw_os_error(-code));
+        }
+        Ok(buf
+            .get(NLMSG_HDRLEN + GENL_HDRLEN..msg_len)
+            .unwrap_or_default()
+            .to_vec())
+    }
+}
+
+/// Netlink atributy (typ, hodnota) ze souvislého bufferu.
+fn attrs(mut buf: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    std::iter::from_fn(move || {
+        let len = u16::from_ne_bytes(buf.get(0..2)?.try_into().ok()?) as usize;
+        let kind = u16::from_ne_bytes(buf.get(2..4)?.try_into().ok()?) & NLA_TYPE_MASK;
+        let value = buf.get(4..len)?;
+        buf = buf.get(align4(len)..).unwrap_or_default();
+        Some((kind, value))
+    })
+}
+
+fn find_attr(buf: &[u8], kind: u16) -> Option<&[u8]> {
+    attrs(buf).find(|&(k, _)| k == kind).map(|(_, v)| v)
+}
+
+fn family_id(sock: &mut Socket) -> Result<u16> {
+    let reply = sock
+        .request(
+            libc::GENL_ID_CTRL as u16,
+            libc::CTRL_CMD_GETFAMILY as u8,
+            &[(CTRL_ATTR_FAMILY_NAME, b"TASKSTATS\0")],
+        )
+        .context("resolve TASKSTATS netlink family (needs initial net namespace)")?;
+    let id = find_attr(&reply, CTRL_ATTR_FAMILY_ID).context("family id missing in reply")?;
+    Ok(u16::from_ne_bytes(
+        id.get(0..2).context("short family id")?.try_into()?,
+    ))
+}
+
+/// (count, delay_total_ns) pro cpu, blkio, swapin.
+fn tgid_delays(sock: &mut Socket, family: u16, tgid: i32) -> Result<[(u64, u64); 3]> {
+    let reply = sock
+        .request(
+            family,
+            TASKSTATS_CMD_GET,
+            &[(TASKSTATS_CMD_ATTR_TGID, &(tgid as u32).to_ne_bytes())],
+        )
+        .with_context(|| format!("taskstats for tgid {tgid}"))?;
+    let stats = find_attr(&reply, TASKSTATS_TYPE_AGGR_TGID)
+        .and_then(|nested| find_attr(nested, TASKSTATS_TYPE_STATS))
+        .context("taskstats missing in reply")?;
+    let u64_at = |off: usize| {
+        stats
+            .get(off..off + 8)
+            .map(|b| u64::from_ne_bytes(b.try_into().unwrap()))
+            .unwrap_or(0)
+    };
+    Ok(DELAYS.map(|(_, count, total)| (u64_at(count), u64_at(total))))
+}
+
+fn connect() -> Result<Conn> {
+    let mut sock = Socket::open().context("open generic netlink socket")?;
+    let family = family_id(&mut sock)?;
+    Ok(Conn { sock, family })
+}
+
+pub fn update(metrics: &TaskstatsMetrics, proc_root: &Path, pids: &[i32]) -> Result<()> {
+    let mut guard = CONN.lock().expect("taskstats CONN mutex poisoned");
+    let conn = match guard.as_mut() {
+        Some(conn) => conn,
+        None => guard.insert(connect()?),
+    };
+
+    let mut live = LiveSeries::default();
+    for &pid in pids {
+        let delays = match tgid_delays(&mut conn.sock, conn.family, pid) {
+            Ok(delays) => delays,
+            // ESRCH - TGID už neexistuje
+            Err(e)
+                if e.downcast_ref::<io::Error>()
+                    .is_some_and(|e| e.raw_os_error() == Some(libc::ESRCH)) =>
+            {
+                continue;
+            }
+            // socket je v neznámém stavu - příští cyklus otevře nový
+            Err(e) => {
+                *guard = None;
+                return Err(e);
+            }
+        };
+        let comm = std::fs::read_to_string(proc_root.join(pid.to_string()).join("comm"))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let pid = pid.to_string();
+        for ((kind, _, _), (count, total_ns)) in DELAYS.iter().zip(delays) {
+            let labels = [pid.as_str(), comm.as_str(), kind];
+            live.insert(&labels);
+            metrics
+                .delay_seconds_total
+                .with_label_values(&labels)
+                .set(total_ns as f64 / 1e9);
+            metrics
+                .delays_total
+                .with_label_values(&labels)
+                .set(count as f64);
+        }
+    }
+    live.prune(&metrics.delay_seconds_total);
+    live.prune(&metrics.delays_total);
+    Ok(())
+}
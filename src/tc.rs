@@ -0,0 +1,308 @@
+//! Statistiky traffic control (qdisc + class) přes rtnetlink pro TC_INTERFACES.
+//!
+//! Pody omezené CNI bandwidth pluginem shapuje tbf/fq a jejich dropy v
+//! countrech interface vidět nejsou. Netlink dump je ručně (RTM_GETQDISC /
+//! RTM_GETTCLASS, atribut TCA_STATS2) - kvůli pár číslům netahat celou
+//! netlink knihovnu.
+
+use std::{ffi::CString, io};
+
+use anyhow::{Context, Result, bail};
+
+use crate::metrics::TcMetrics;
+
+const RTM_GETQDISC: u16 = 38;
+const RTM_GETTCLASS: u16 = 42;
+const RTM_NEWQDISC: u16 = 36;
+const RTM_NEWTCLASS: u16 = 40;
+
+const NLMSG_ERROR: u16 = 2;
+const NLMSG_DONE: u16 = 3;
+const NLM_F_REQUEST: u16 = 0x01;
+const NLM_F_DUMP: u16 = 0x300;
+
+const TCA_KIND: u16 = 1;
+const TCA_STATS2: u16 = 7;
+const TCA_STATS_BASIC: u16 = 1;
+const TCA_STATS_QUEUE: u16 = 3;
+
+const TC_H_ROOT: u32 = 0xFFFF_FFFF;
+const TC_H_INGRESS: u32 = 0xFFFF_FFF1;
+
+const NLMSG_HDRLEN: usize = 16;
+const TCMSG_LEN: usize = 20;
+
+#[derive(Debug, Default)]
+struct TcObject {
+    kind: String,
+    handle: u32,
+    parent: u32,
+    bytes: u64,
+    packets: u32,
+    qlen: u32,
+    backlog: u32,
+    drops: u32,
+    requeues: u32,
+    overlimits: u32,
+}
+
+/// "maj:min" v hexu jako `tc`, root/ingress slovem.
+fn format_handle(h: u32) -> String {
+    match h {
+        TC_H_ROOT => "root".to_string(),
+        TC_H_INGRESS => "ingress".to_string(),
+        _ => format!("{:x}:{:x}", h >> 16, h & 0xFFFF),
+    }
+}
+
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+fn u16_at(b: &[u8], off: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(b.get(off..off + 2)?.try_into().ok()?))
+}
+
+fn u32_at(b: &[u8], off: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(b.get(off..off + 4)?.try_into().ok()?))
+}
+
+fn u64_at(b: &[u8], off: usize) -> Option<u64> {
+    Some(u64::from_ne_bytes(b.get(off..off + 8)?.try_into().ok()?))
+}
+
+/// Iterace přes rtattr (len u16, type u16, payload zarovnaný na 4).
+fn attrs(mut b: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    std::iter::from_fn(move || {
+        let len = u16_at(b, 0)? as usize;
+        let ty = u16_at(b, 2)? & 0x3FFF; // bez NLA_F_NESTED / NLA_F_NET_BYTEORDER
+        if len < 4 || len > b.len() {
+            return None;
+        }
+        let payload = &b[4..len];
+        b = &b[align4(len).min(b.len())..];
+        Some((ty, payload))
+    })
+}
+
+fn parse_object(msg: &[u8]) -> Option<TcObject> {
+    // tcmsg: family u8, pad u8, pad u16, ifindex i32, handle u32, parent u32, info u32
+    let mut obj = TcObject {
+        handle: u32_at(msg, 8)?,
+        parent: u32_at(msg, 12)?,
+        ..Default::default()
+    };
+
+    for (ty, payload) in attrs(msg.get(TCMSG_LEN..)?) {
+        match ty {
+            TCA_KIND => {
+                obj.kind = String::from_utf8_lossy(payload)
+                    .trim_end_matches('\0')
+                    .to_string();
+            }
+            TCA_STATS2 => {
+                for (ty, stats) in attrs(payload) {
+                    match ty {
+                        // gnet_stats_basic: bytes u64, packets u32
+                        TCA_STATS_BASIC => {
+                            obj.bytes = u64_at(stats, 0).unwrap_or(0);
+                            obj.packets = u32_at(stats, 8).unwrap_or(0);
+                        }
+                        // gnet_stats_queue: qlen, backlog, drops, requeues, overlimits
+                        TCA_STATS_QUEUE => {
+                            obj.qlen = u32_at(stats, 0).unwrap_or(0);
+                            obj.backlog = u32_at(stats, 4).unwrap_or(0);
+                            obj.drops = u32_at(stats, 8).unwrap_or(0);
+                            obj.requeues = u32_at(stats, 12).unwrap_or(0);
+                            obj.overlimits = u32_at(stats, 16).unwrap_or(0);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(obj)
+}
+
+/// Netlink socket, který se při dropu zavře.
+struct NetlinkSocket(i32);
+
+impl Drop for NetlinkSocket {
+    fn drop(&mut self) {
+        // SAFETY: fd vlastní jen tahle struktura a po dropu se už nepoužije
+        unsafe { libc::close(self.0) };
+    }
+}
+
+/// Dump qdisců nebo tříd jednoho interface.
+fn dump(ifindex: i32, request: u16, reply: u16) -> Result<Vec<TcObject>> {
+    // SAFETY: socket() nepracuje s žádnou pamětí volajícího
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+            libc::NETLINK_ROUTE,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error()).context("netlink socket");
+    }
+    let sock = NetlinkSocket(fd);
+
+    // dump jde přes víc recv - bez timeoutu by ztracené NLMSG_DONE
+    // zablokovalo celý update
+    let timeout = libc::timeval {
+        tv_sec: 1,
+        tv_usec: 0,
+    };
+    // SAFETY: timeout žije po celé volání a délka odpovídá timeval
+    let rc = unsafe {
+        libc::setsockopt(
+            sock.0,
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            (&timeout as *const libc::timeval).cast(),
+            std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+        )
+    };
+    if rc < 0 {
+        return Err(io::Error::last_os_error()).context("netlink SO_RCVTIMEO");
+    }
+
+    let mut req = Vec::with_capacity(NLMSG_HDRLEN + TCMSG_LEN);
+    req.extend_from_slice(&((NLMSG_HDRLEN + TCMSG_LEN) as u32).to_ne_bytes());
+    req.extend_from_slice(&request.to_ne_bytes());
+    req.extend_from_slice(&(NLM_F_REQUEST | NLM_F_DUMP).to_ne_bytes());
+    req.extend_from_slice(&1u32.to_ne_bytes()); // seq
+    req.extend_from_slice(&0u32.to_ne_bytes()); // pid = kernel
+    req.extend_from_slice(&[libc::AF_UNSPEC as u8, 0, 0, 0]);
+    req.extend_from_slice(&ifindex.to_ne_bytes());
+    req.extend_from_slice(&[0u8; 12]); // handle, parent, info
+
+    // SAFETY: req je platný buffer délky req.len() po celé volání
+    let sent = unsafe { libc::send(sock.0, req.as_ptr().cast(), req.len(), 0) };
+    if sent < 0 {
+        return Err(io::Error::last_os_error()).context("netlink send");
+    }
+
+    let mut objects = Vec::new();
+    let mut buf = vec![0u8; 32 * 1024];
+    loop {
+        // SAFETY: kernel zapíše nejvýš buf.len() bajtů do vlastněného bufferu
+        let n = unsafe { libc::recv(sock.0, buf.as_mut_ptr().cast(), buf.len(), 0) };
+        if n < 0 {
+            return Err(io::Error::last_os_error()).context("netlink recv");
+        }
+        if n == 0 {
+            bail!("netlink socket closed before NLMSG_DONE");
+        }
+        let mut b = &buf[..n as usize];
+
+        while b.len() >= NLMSG_HDRLEN {
+            let len = u32_at(b, 0).unwrap_or(0) as usize;
+            let ty = u16_at(b, 4).unwrap_or(0);
+            if len < NLMSG_HDRLEN || len > b.len() {
+                bail!("malformed netlink message");
+            }
+            let payload = &b[NLMSG_HDRLEN..len];
+
+            match ty {
+                NLMSG_DONE => return Ok(objects),
+                NLMSG_ERROR => {
+                    let errno = u32_at(payload, 0).unwrap_or(0) as i32;
+                    if errno != 0 {
+                        return Err(io::Error::from_raw_os_error(-errno)).context("netlink dump");
+                    }
+                }
+                t if t == reply => {
+                    // dump filtruje ifindex jen u tříd, u qdisců je vrácený všechny
+                    let msg_if = payload
+                        .get(4..8)
+                        .and_then(|s| s.try_into().ok())
+                        .map(i32::from_ne_bytes);
+                    if msg_if == Some(ifindex)
+                        && let Some(obj) = parse_object(payload)
+                    {
+                        objects.push(obj);
+                    }
+                }
+                _ => {}
+            }
+            b = &b[align4(len).min(b.len())..];
+        }
+    }
+}
+
+fn ifindex(iface: &str) -> Option<i32> {
+    let name = CString::new(iface).ok()?;
+    // SAFETY: name je NUL-terminated CString, který žije po celé volání
+    let idx = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    (idx != 0).then_some(idx as i32)
+}
+
+pub fn update(metrics: &TcMetrics, interfaces: &[String]) -> Result<()> {
+    // qdisc může být nahrazen / smazán - staré série zahodit
+    for vec in [
+        &metrics.sent_bytes,
+        &metrics.sent_packets,
+        &metrics.drops,
+        &metrics.overlimits,
+        &metrics.requeues,
+        &metrics.backlog_bytes,
+        &metrics.backlog_packets,
+    ] {
+        vec.reset();
+    }
+
+    let mut errors = Vec::new();
+    for iface in interfaces {
+        let Some(idx) = ifindex(iface) else {
+            // interface v tomhle net namespace neexistuje
+            continue;
+        };
+
+        for (object, request, reply) in [
+            ("qdisc", RTM_GETQDISC, RTM_NEWQDISC),
+            ("class", RTM_GETTCLASS, RTM_NEWTCLASS),
+        ] {
+            let objects = match dump(idx, request, reply) {
+                Ok(o) => o,
+                Err(e) => {
+                    errors.push(format!("{iface} {object}: {e:#}"));
+                    continue;
+                }
+            };
+
+            for obj in objects {
+                let handle = format_handle(obj.handle);
+                let parent = format_handle(obj.parent);
+                let labels = [
+                    iface.as_str(),
+                    object,
+                    obj.kind.as_str(),
+                    handle.as_str(),
+                    parent.as_str(),
+                ];
+                for (vec, v) in [
+                    (&metrics.sent_bytes, obj.bytes as f64),
+                    (&metrics.sent_packets, obj.packets as f64),
+                    (&metrics.drops, obj.drops as f64),
+                    (&metrics.overlimits, obj.overlimits as f64),
+                    (&metrics.requeues, obj.requeues as f64),
+                    (&metrics.backlog_bytes, obj.backlog as f64),
+                    (&metrics.backlog_packets, obj.qlen as f64),
+                ] {
+                    vec.with_label_values(&labels).set(v);
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("{}", errors.join("; "));
+    }
+    Ok(())
+}
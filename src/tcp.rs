@@ -4,11 +4,16 @@ use std::{
     collections::HashMap,
     fs::File,
     io::{self, BufRead, BufReader},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
 };
 
 use anyhow::{Context, Result};
 
-use crate::metrics::TcpMetrics;
+use crate::{config::PeerCidr, metrics::TcpMetrics};
+
+/// Počty spojení per (jméno služby z PEER_CIDRS, kód stavu).
+type PeerCounts<'a> = HashMap<(&'a str, u8), i64>;
 
 /// Aktualizuje metriky TCP spojení (podle stavu a IP verze).
 ///
@@ -16,13 +21,30 @@ use crate::metrics::TcpMetrics;
 /// adresy `::ffff:W.X.Y.Z`) se v /proc/net/tcp6 objevují jako IPv6.
 /// Abychom dostali realistické počty IPv4/IPv6 spojení, rozeznáváme
 /// tyto adresy a počítáme je jako `ip_version = "4"`.
-pub fn update(metrics: &TcpMetrics) -> Result<()> {
+///
+/// S PEER_CIDRS navíc počítá spojení per logická služba podle remote adresy
+/// (první CIDR, který adresu obsahuje).
+pub fn update(metrics: &TcpMetrics, proc_root: &Path, peers: &[PeerCidr]) -> Result<()> {
     let mut counts: HashMap<(u8, &'static str), i64> = HashMap::new();
+    let mut peer_counts = PeerCounts::new();
 
-    collect_from_path("/proc/net/tcp", "4", &mut counts).context("read /proc/net/tcp")?;
+    collect_from_path(
+        &proc_root.join("net/tcp"),
+        "4",
+        &mut counts,
+        peers,
+        &mut peer_counts,
+    )
+    .context("read /proc/net/tcp")?;
 
     // IPv6 může být vypnuté - chybu ENOENT ignorujeme.
-    match collect_from_path("/proc/net/tcp6", "6", &mut counts) {
+    match collect_from_path(
+        &proc_root.join("net/tcp6"),
+        "6",
+        &mut counts,
+        peers,
+        &mut peer_counts,
+    ) {
         Ok(()) => {}
         Err(e) if e.kind() == io::ErrorKind::NotFound => {}
         Err(e) => return Err(e).context("read /proc/net/tcp6"),
@@ -55,6 +77,17 @@ pub fn update(metrics: &TcpMetrics) -> Result<()> {
         }
     }
 
+    if let Some(ref peer_connections) = metrics.peer_connections {
+        for peer in peers {
+            for &code in &TCP_STATE_CODES {
+                let value = *peer_counts.get(&(peer.name.as_str(), code)).unwrap_or(&0);
+                peer_connections
+                    .with_label_values(&[&peer.name, tcp_state_name(code)])
+                    .set(value);
+            }
+        }
+    }
+
     Ok(())
 }
 
@@ -62,10 +95,12 @@ pub fn update(metrics: &TcpMetrics) -> Result<()> {
 ///
 /// U `/proc/net/tcp6` navíc detekuje IPv4-mapped IPv6 adresy (prefix
 /// `0000000000000000FFFF0000`) a počítá taková spojení jako IPv4.
-fn collect_from_path(
-    path: &str,
+fn collect_from_path<'a>(
+    path: &Path,
     ip_version: &'static str,
     counts: &mut HashMap<(u8, &'static str), i64>,
+    peers: &'a [PeerCidr],
+    peer_counts: &mut PeerCounts<'a>,
 ) -> io::Result<()> {
     let file = File::open(path)?;
     let reader = BufReader::new(file);
@@ -101,12 +136,40 @@ fn collect_from_path(
             }
 
             *counts.entry((code, effective_ip_version)).or_insert(0) += 1;
+
+            if !peers.is_empty()
+                && let Some(remote) = cols.get(2).and_then(|r| parse_addr(r))
+                && let Some(peer) = peers.iter().find(|p| p.contains(&remote))
+            {
+                *peer_counts.entry((peer.name.as_str(), code)).or_insert(0) += 1;
+            }
         }
     }
 
     Ok(())
 }
 
+/// LISTEN sockety z jedné tabulky /proc/<pid>/net/tcp{,6}: inode → port.
+pub fn listening_ports(path: &Path) -> io::Result<HashMap<u64, u16>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut ports = HashMap::new();
+    for line in reader.lines().skip(1) {
+        let line = line?;
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        if cols.get(3) != Some(&"0A") {
+            continue;
+        }
+        let port = cols[1]
+            .rsplit_once(':')
+            .and_then(|(_, p)| u16::from_str_radix(p, 16).ok());
+        let inode = cols.get(9).and_then(|i| i.parse::<u64>().ok());
+        if let (Some(port), Some(inode)) = (port, inode) {
+            ports.insert(inode, port);
+        }
+    }
+    Ok(ports)
+}
+
 /// Vrací `true`, pokud je adresa z /proc/net/tcp6 ve formátu
 /// IPv4-mapped IPv6 (`::ffff:W.X.Y.Z`).
 fn is_ipv4_mapped_addr(addr_port: &str) -> bool {
@@ -126,6 +189,34 @@ fn is_ipv4_mapped_addr(addr_port: &str) -> bool {
     addr_hex[..24].eq_ignore_ascii_case("0000000000000000FFFF0000")
 }
 
+/// Převede adresu z /proc/net/tcp{,6} (`0100007F:0050`) na IpAddr.
+///
+/// Kernel vypisuje každé 32bitové slovo adresy v nativním pořadí bajtů;
+/// IPv4-mapped IPv6 adresy vracíme jako IPv4, aby seděly na IPv4 CIDRy.
+fn parse_addr(addr_port: &str) -> Option<IpAddr> {
+    let (addr_hex, _) = addr_port.split_once(':')?;
+    let word = |i: usize| -> Option<[u8; 4]> {
+        let v = u32::from_str_radix(addr_hex.get(i * 8..i * 8 + 8)?, 16).ok()?;
+        Some(v.to_ne_bytes())
+    };
+
+    match addr_hex.len() {
+        8 => Some(IpAddr::V4(Ipv4Addr::from(word(0)?))),
+        32 => {
+            let mut octets = [0u8; 16];
+            for i in 0..4 {
+                octets[i * 4..i * 4 + 4].copy_from_slice(&word(i)?);
+            }
+            let v6 = Ipv6Addr::from(octets);
+            Some(match v6.to_ipv4_mapped() {
+                Some(v4) => IpAddr::V4(v4),
+                None => IpAddr::V6(v6),
+            })
+        }
+        _ => None,
+    }
+}
+
 fn tcp_state_name(code: u8) -> &'static str {
     match code {
         0x01 => "ESTABLISHED",
@@ -143,3 +234,45 @@ fn tcp_state_name(code: u8) -> &'static str {
         _ => "UNKNOWN",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use prometheus::Registry;
+
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn update_counts_fixture_connections() {
+        let mut cfg = Config::for_fixtures();
+        let db = PeerCidr {
+            name: "db".to_string(),
+            network: "10.0.0.0".parse().unwrap(),
+            prefix_len: 8,
+        };
+        cfg.peer_cidrs = vec![db];
+        let metrics = TcpMetrics::new(&Registry::new(), &cfg).unwrap();
+
+        update(&metrics, &cfg.proc_root, &cfg.peer_cidrs).unwrap();
+
+        let v4 = |state| metrics.connections.with_label_values(&[state, "4"]).get();
+        assert_eq!(v4("ESTABLISHED"), 3);
+        assert_eq!(v4("LISTEN"), 1);
+        let peers = metrics.peer_connections.as_ref().unwrap();
+        // jedno z /proc/net/tcp, jedno IPv4-mapped z tcp6
+        assert_eq!(peers.with_label_values(&["db", "ESTABLISHED"]).get(), 2);
+    }
+
+    #[test]
+    fn parse_addr_handles_v4_and_mapped_v6() {
+        assert_eq!(
+            parse_addr("0100007F:1F90"),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(
+            parse_addr("0000000000000000FFFF00000100007F:1F90"),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(parse_addr("garbage"), None);
+    }
+}
@@ -0,0 +1,73 @@
+//! Vyhodnocení THRESHOLDS nad aktuálním obsahem registry.
+//!
+//! Každý cyklus se pro každé pravidlo najdou vzorky metriky (volitelně
+//! zúžené selektorem labelů); stačí, aby podmínku splnil jeden z nich.
+//! `for` funguje jako u Prometheus alertů - breach až po nepřetržitém trvání.
+
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+
+use once_cell::sync::Lazy;
+use prometheus::proto::MetricFamily;
+
+use crate::{
+    config::{Config, Threshold},
+    metrics::{Metrics, sample_value},
+};
+
+/// Od kdy je podmínka pravidla nepřetržitě splněná (klíč = jméno pravidla).
+static PENDING_SINCE: Lazy<Mutex<HashMap<String, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn evaluate(metrics: &Metrics, cfg: &Config) {
+    if cfg.thresholds.is_empty() {
+        return;
+    }
+
+    let families = metrics.registry.gather();
+    let now = Instant::now();
+    let mut pending = PENDING_SINCE
+        .lock()
+        .expect("threshold PENDING_SINCE mutex poisoned");
+
+    for rule in &cfg.thresholds {
+        let firing = matches(&families, cfg.metrics_prefix.as_deref(), rule);
+
+        let breached = if firing {
+            let since = *pending.entry(rule.name.clone()).or_insert(now);
+            now.duration_since(since) >= rule.for_duration
+        } else {
+            pending.remove(&rule.name);
+            false
+        };
+
+        metrics
+            .threshold_breached
+            .with_label_values(&[&rule.name])
+            .set(breached as i64);
+    }
+}
+
+fn matches(families: &[MetricFamily], prefix: Option<&str>, rule: &Threshold) -> bool {
+    let full_name = match prefix {
+        Some(p) => format!("{p}_{}", rule.metric),
+        None => rule.metric.clone(),
+    };
+
+    families
+        .iter()
+        .filter(|mf| mf.name() == full_name || mf.name() == rule.metric)
+        .flat_map(|mf| {
+            mf.get_metric()
+                .iter()
+                .map(move |m| (mf.get_field_type(), m))
+        })
+        .filter(|(_, m)| {
+            rule.labels.iter().all(|(k, v)| {
+                m.get_label()
+                    .iter()
+                    .any(|lp| lp.name() == k && lp.value() == v)
+            })
+        })
+        .filter_map(|(t, m)| sample_value(m, t))
+        .any(|v| rule.op.eval(v, rule.value))
+}
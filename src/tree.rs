@@ -0,0 +1,141 @@
+//! Strom procesů pod cílovými PIDy pro GET /tree - náhrada `ps --forest`
+//! v distroless image bez procps.
+
+use std::{collections::HashMap, path::Path, sync::Mutex, time::Instant};
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::procfs::list_pids;
+
+#[derive(Debug, Serialize)]
+pub struct TreeNode {
+    pub pid: i32,
+    pub comm: String,
+    /// Stav z /proc/<pid>/stat (R, S, D, Z, ...)
+    pub state: String,
+    pub rss_bytes: u64,
+    /// CPU (user+system) spotřebovaná od předchozího GET /tree; null poprvé
+    pub cpu_seconds_since_last: Option<f64>,
+    pub children: Vec<TreeNode>,
+}
+
+struct ProcStat {
+    ppid: i32,
+    comm: String,
+    state: String,
+    cpu_seconds: f64,
+    rss_bytes: u64,
+}
+
+/// Čas a CPU sekundy per PID z minulého volání.
+type PrevCpu = (Option<Instant>, HashMap<i32, f64>);
+
+static PREV_CPU: Lazy<Mutex<PrevCpu>> = Lazy::new(|| Mutex::new((None, HashMap::new())));
+
+fn read_stat(proc_root: &Path, pid: i32) -> Option<ProcStat> {
+    let content = std::fs::read_to_string(proc_root.join(pid.to_string()).join("stat")).ok()?;
+    // comm je v závorkách a může obsahovat mezery i závorky
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let comm = content.get(open + 1..close)?.to_string();
+    let rest: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+
+    // rest[0] = state (pole 3), ppid = 4, utime = 14, stime = 15, rss = 24 (v stránkách)
+    let field = |n: usize| rest.get(n - 3).and_then(|v| v.parse::<u64>().ok());
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) } as f64;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+
+    Some(ProcStat {
+        ppid: rest.get(1)?.parse().ok()?,
+        comm,
+        state: rest.first()?.to_string(),
+        cpu_seconds: if ticks > 0.0 {
+            (field(14)? + field(15)?) as f64 / ticks
+        } else {
+            0.0
+        },
+        rss_bytes: field(24).unwrap_or(0) * page_size,
+    })
+}
+
+/// Postaví podstromy pro všechny `roots`, které existují; root, který je
+/// potomkem jiného rootu, se znovu nevypisuje. Vrací i dobu od minulého
+/// volání (interval pro cpu_seconds_since_last).
+pub fn build(proc_root: &Path, roots: &[i32]) -> Result<(Option<f64>, Vec<TreeNode>)> {
+    let mut stats = HashMap::new();
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for pid in list_pids(proc_root)? {
+        if let Some(stat) = read_stat(proc_root, pid) {
+            children.entry(stat.ppid).or_default().push(pid);
+            stats.insert(pid, stat);
+        }
+    }
+    for kids in children.values_mut() {
+        kids.sort_unstable();
+    }
+
+    let mut prev = PREV_CPU.lock().expect("tree PREV_CPU mutex poisoned");
+    let interval_seconds = prev.0.map(|at| at.elapsed().as_secs_f64());
+    let had_prev = interval_seconds.is_some();
+    let prev_cpu = std::mem::take(&mut prev.1);
+
+    fn node(
+        pid: i32,
+        stats: &HashMap<i32, ProcStat>,
+        children: &HashMap<i32, Vec<i32>>,
+        prev_cpu: &HashMap<i32, f64>,
+        had_prev: bool,
+    ) -> Option<TreeNode> {
+        let stat = stats.get(&pid)?;
+        // nový proces (nebo znovu použitý PID) → celá jeho dosavadní CPU
+        let cpu_seconds_since_last = had_prev.then(|| {
+            prev_cpu
+                .get(&pid)
+                .map_or(stat.cpu_seconds, |p| (stat.cpu_seconds - p).max(0.0))
+        });
+
+        Some(TreeNode {
+            pid,
+            comm: stat.comm.clone(),
+            state: stat.state.clone(),
+            rss_bytes: stat.rss_bytes,
+            cpu_seconds_since_last,
+            children: children
+                .get(&pid)
+                .into_iter()
+                .flatten()
+                .filter_map(|&c| node(c, stats, children, prev_cpu, had_prev))
+                .collect(),
+        })
+    }
+
+    let is_nested = |pid: i32| {
+        let mut cur = stats.get(&pid).map(|s| s.ppid);
+        let mut depth = 0;
+        while let Some(p) = cur
+            && depth < 4096
+        {
+            if roots.contains(&p) {
+                return true;
+            }
+            cur = stats.get(&p).map(|s| s.ppid);
+            depth += 1;
+        }
+        false
+    };
+
+    let tree = roots
+        .iter()
+        .filter(|&&pid| !is_nested(pid))
+        .filter_map(|&pid| node(pid, &stats, &children, &prev_cpu, had_prev))
+        .collect();
+
+    *prev = (
+        Some(Instant::now()),
+        stats.iter().map(|(&pid, s)| (pid, s.cpu_seconds)).collect(),
+    );
+
+    Ok((interval_seconds, tree))
+}
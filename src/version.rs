@@ -0,0 +1,27 @@
+//! Informace o buildu (naplněné v build.rs).
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_COMMIT: &str = env!("BUILD_GIT_COMMIT");
+pub const BUILD_DATE: &str = env!("BUILD_DATE");
+const FEATURES: &str = env!("BUILD_FEATURES");
+
+/// Zapnuté cargo features, "none" pokud žádné.
+pub fn features() -> &'static str {
+    if FEATURES.is_empty() {
+        "none"
+    } else {
+        FEATURES
+    }
+}
+
+/// Víceřádkový výstup pro `--version`.
+pub fn long_version() -> String {
+    format!(
+        "{} {}\ncommit:     {}\nbuild date: {}\nfeatures:   {}\n",
+        env!("CARGO_PKG_NAME"),
+        VERSION,
+        GIT_COMMIT,
+        BUILD_DATE,
+        features()
+    )
+}
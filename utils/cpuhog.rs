@@ -0,0 +1,62 @@
+// cpuhog [threads] [duty_percent] [duration_secs]
+//
+// Spustí N vláken, každé pálí CPU `duty_percent` % času v 100ms slotech
+// (zbytek slotu spí). duration_secs=0 → běží do nekonečna.
+use std::{
+    env, thread,
+    time::{Duration, Instant},
+};
+
+const SLOT: Duration = Duration::from_millis(100);
+
+fn main() {
+    let threads: usize = env::args().nth(1).unwrap_or("1".into()).parse().unwrap();
+    let duty: u32 = env::args().nth(2).unwrap_or("100".into()).parse().unwrap();
+    let secs: u64 = env::args().nth(3).unwrap_or("0".into()).parse().unwrap();
+
+    let duty = duty.min(100);
+    let busy = SLOT * duty / 100;
+    let deadline = (secs > 0).then(|| Instant::now() + Duration::from_secs(secs));
+
+    println!(
+        "Burning {} thread(s) at {}% duty cycle for {}...",
+        threads,
+        duty,
+        if secs > 0 {
+            format!("{}s", secs)
+        } else {
+            "ever".into()
+        }
+    );
+
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            thread::spawn(move || {
+                let mut x: f64 = 0.0;
+                loop {
+                    let slot_start = Instant::now();
+                    if deadline.is_some_and(|d| slot_start >= d) {
+                        break;
+                    }
+
+                    // čistě CPU v user-space
+                    while slot_start.elapsed() < busy {
+                        x += (x + 1.0).sin().cos().tan();
+                        if x > 1e9 {
+                            x = 0.0;
+                        }
+                    }
+
+                    if let Some(idle) = SLOT.checked_sub(slot_start.elapsed()) {
+                        thread::sleep(idle);
+                    }
+                }
+                x
+            })
+        })
+        .collect();
+
+    for h in handles {
+        let _ = h.join();
+    }
+}
@@ -0,0 +1,94 @@
+// iohog <file> [mib] [write|read|rw] [buffered|direct] [block_kib]
+//
+// Zapíše a/nebo přečte `mib` MiB do/z `file` po blocích `block_kib` KiB.
+// `direct` otevře soubor s O_DIRECT (obchází page cache → čisté io.stat
+// a read_bytes/write_bytes v /proc/<pid>/io), `buffered` jde přes page cache.
+use std::{
+    alloc::{Layout, alloc_zeroed, dealloc},
+    env,
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    os::unix::fs::OpenOptionsExt,
+    time::Instant,
+};
+
+#[cfg(any(target_arch = "aarch64", target_arch = "arm"))]
+const O_DIRECT: i32 = 0o200000;
+#[cfg(not(any(target_arch = "aarch64", target_arch = "arm")))]
+const O_DIRECT: i32 = 0o40000;
+
+// O_DIRECT vyžaduje zarovnaný buffer
+const ALIGN: usize = 4096;
+
+fn open(path: &str, write: bool, direct: bool) -> File {
+    let mut opts = OpenOptions::new();
+    if write {
+        opts.write(true).create(true).truncate(true);
+    } else {
+        opts.read(true);
+    }
+    if direct {
+        opts.custom_flags(O_DIRECT);
+    }
+    opts.open(path).unwrap()
+}
+
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .expect("usage: iohog <file> [mib] [write|read|rw] [buffered|direct] [block_kib]");
+    let mib: usize = env::args().nth(2).unwrap_or("256".into()).parse().unwrap();
+    let mode = env::args().nth(3).unwrap_or("rw".into());
+    let direct = env::args().nth(4).unwrap_or("buffered".into()) == "direct";
+    let block_kib: usize = env::args().nth(5).unwrap_or("1024".into()).parse().unwrap();
+
+    let block = (block_kib * 1024).div_ceil(ALIGN) * ALIGN;
+    let blocks = (mib * 1024 * 1024).div_ceil(block);
+
+    let layout = Layout::from_size_align(block, ALIGN).unwrap();
+    let ptr = unsafe { alloc_zeroed(layout) };
+    assert!(!ptr.is_null(), "buffer allocation failed");
+    let buf = unsafe { std::slice::from_raw_parts_mut(ptr, block) };
+    buf.iter_mut().enumerate().for_each(|(i, b)| *b = i as u8);
+
+    if mode == "write" || mode == "rw" {
+        let mut f = open(&path, true, direct);
+        let start = Instant::now();
+        for _ in 0..blocks {
+            f.write_all(buf).unwrap();
+        }
+        f.sync_all().unwrap();
+        let secs = start.elapsed().as_secs_f64();
+        println!(
+            "Wrote {} MiB in {:.2}s ({:.1} MiB/s, direct={})",
+            mib,
+            secs,
+            mib as f64 / secs,
+            direct
+        );
+    }
+
+    if mode == "read" || mode == "rw" {
+        let mut f = open(&path, false, direct);
+        let start = Instant::now();
+        let mut total = 0usize;
+        loop {
+            let n = f.read(buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            total += n;
+        }
+        let secs = start.elapsed().as_secs_f64();
+        let read_mib = total as f64 / 1024.0 / 1024.0;
+        println!(
+            "Read {:.0} MiB in {:.2}s ({:.1} MiB/s, direct={})",
+            read_mib,
+            secs,
+            read_mib / secs,
+            direct
+        );
+    }
+
+    unsafe { dealloc(ptr, layout) };
+}
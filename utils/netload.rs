@@ -0,0 +1,107 @@
+// netload listen <addr> [backlog] [accept|noaccept] [secs]
+// netload connect <addr> [count] [hold_secs] [close|keep]
+//
+// `listen` otevře listening socket s daným backlogem; `noaccept` spojení
+// nepřijímá → plní se accept queue a po jejím zaplnění roste listen overflow.
+// `connect` otevře N klientských spojení, drží je `hold_secs` a pak je
+// (při `close`) zavře z klientské strany → TIME_WAIT na straně klienta.
+use std::{
+    env,
+    io::Read,
+    net::{TcpListener, TcpStream},
+    os::fd::AsRawFd,
+    thread,
+    time::{Duration, Instant},
+};
+
+unsafe extern "C" {
+    // opakované listen() na Linuxu jen změní backlog
+    fn listen(fd: i32, backlog: i32) -> i32;
+}
+
+fn arg(n: usize, default: &str) -> String {
+    env::args().nth(n).unwrap_or(default.into())
+}
+
+fn run_listen(addr: &str) {
+    let backlog: i32 = arg(3, "128").parse().unwrap();
+    let accept = arg(4, "accept") == "accept";
+    let secs: u64 = arg(5, "300").parse().unwrap();
+
+    let listener = TcpListener::bind(addr).unwrap();
+    assert_eq!(
+        unsafe { listen(listener.as_raw_fd(), backlog) },
+        0,
+        "listen() failed"
+    );
+    println!(
+        "Listening on {} (backlog={}, accept={}) for {}s...",
+        addr, backlog, accept, secs
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(secs);
+    if !accept {
+        thread::sleep(Duration::from_secs(secs));
+        return;
+    }
+
+    listener.set_nonblocking(true).unwrap();
+    let mut accepted = 0usize;
+    while Instant::now() < deadline {
+        match listener.accept() {
+            Ok((mut s, _)) => {
+                // čteme do EOF v samostatném vlákně, ať klient zavírá první
+                thread::spawn(move || {
+                    let mut buf = [0u8; 1024];
+                    while matches!(s.read(&mut buf), Ok(n) if n > 0) {}
+                });
+                accepted += 1;
+            }
+            Err(_) => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+    println!("Accepted {} connections", accepted);
+}
+
+fn run_connect(addr: &str) {
+    let count: usize = arg(3, "100").parse().unwrap();
+    let hold: u64 = arg(4, "30").parse().unwrap();
+    let close = arg(5, "close") == "close";
+
+    let mut conns = Vec::with_capacity(count);
+    let mut failed = 0;
+    for _ in 0..count {
+        match TcpStream::connect_timeout(&addr.parse().unwrap(), Duration::from_secs(2)) {
+            Ok(s) => conns.push(s),
+            Err(_) => failed += 1,
+        }
+    }
+    println!(
+        "Opened {} connections to {} ({} failed), holding {}s...",
+        conns.len(),
+        addr,
+        failed,
+        hold
+    );
+    thread::sleep(Duration::from_secs(hold));
+
+    if close {
+        drop(conns);
+        println!("Closed from client side (expect TIME_WAIT)");
+    } else {
+        println!("Keeping connections open, Ctrl+C to exit");
+        loop {
+            thread::sleep(Duration::from_secs(3600));
+        }
+    }
+}
+
+fn main() {
+    let mode = arg(1, "");
+    let addr = arg(2, "127.0.0.1:18080");
+    match mode.as_str() {
+        "listen" => run_listen(&addr),
+        "connect" => run_connect(&addr),
+        _ => eprintln!("usage: netload listen|connect <addr> ..."),
+    }
+}